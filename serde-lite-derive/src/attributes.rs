@@ -1,16 +1,16 @@
+use proc_macro2::{Literal, TokenStream};
+use quote::{format_ident, quote};
 use syn::{
-    parse::ParseStream, punctuated::Punctuated, token::Comma, Attribute, Expr, Field, Lit, Meta,
-    Result, Variant,
+    Attribute, Data, DataEnum, Expr, Field, Fields, GenericArgument, Member, PathArguments, Type,
+    Variant, WherePredicate,
 };
 
+use crate::check;
+
 /// Get the rename attribute for a given field or the field name.
 pub fn get_field_name(field: &Field) -> String {
-    if let Some(v) = get_attr_value(&field.attrs, "rename") {
-        if let Some(Lit::Str(n)) = v.lit() {
-            return n.value();
-        } else {
-            panic!("invalid rename attribute");
-        }
+    if let Some(name) = get_str(&field.attrs, "rename") {
+        return name;
     }
 
     field.ident.as_ref().unwrap().to_string()
@@ -18,189 +18,347 @@ pub fn get_field_name(field: &Field) -> String {
 
 /// Get the skip_serializing_if path for a given field (if present).
 pub fn get_skip_field_serializing_if(field: &Field) -> Option<String> {
-    if let Some(v) = get_attr_value(&field.attrs, "skip_serializing_if") {
-        if let Some(Lit::Str(n)) = v.lit() {
-            Some(n.value())
-        } else {
-            panic!("invalid skip_serializing_if attribute");
-        }
-    } else {
-        None
-    }
+    get_str(&field.attrs, "skip_serializing_if")
 }
 
 /// Get field serializer path (if present).
 pub fn get_field_serializer(field: &Field) -> Option<String> {
-    if let Some(v) = get_attr_value(&field.attrs, "serialize_with") {
-        if let Some(Lit::Str(n)) = v.lit() {
-            Some(n.value())
-        } else {
-            panic!("invalid serialize_with attribute");
-        }
-    } else {
-        None
-    }
+    get_str(&field.attrs, "serialize_with")
 }
 
 /// Get field deserializer path (if present).
 pub fn get_field_deserializer(field: &Field) -> Option<String> {
-    if let Some(v) = get_attr_value(&field.attrs, "deserialize_with") {
-        if let Some(Lit::Str(n)) = v.lit() {
-            Some(n.value())
-        } else {
-            panic!("invalid deserialize_with attribute");
-        }
-    } else {
-        None
-    }
+    get_str(&field.attrs, "deserialize_with")
 }
 
 /// Get field serializer/deserializer module path (if present).
 pub fn get_field_with(field: &Field) -> Option<String> {
-    if let Some(v) = get_attr_value(&field.attrs, "with") {
-        if let Some(Lit::Str(n)) = v.lit() {
-            Some(n.value())
-        } else {
-            panic!("invalid with attribute");
-        }
-    } else {
-        None
-    }
+    get_str(&field.attrs, "with")
 }
 
 /// Get field updater path (if present).
 pub fn get_field_updater(field: &Field) -> Option<String> {
-    if let Some(v) = get_attr_value(&field.attrs, "update_with") {
-        if let Some(Lit::Str(n)) = v.lit() {
-            Some(n.value())
-        } else {
-            panic!("invalid update_with attribute");
-        }
-    } else {
-        None
-    }
+    get_str(&field.attrs, "update_with")
+}
+
+/// Get the field `on_update` hook path (if present).
+pub fn get_on_update(field: &Field) -> Option<String> {
+    get_str(&field.attrs, "on_update")
+}
+
+/// Get the field `update_if` predicate path (if present).
+pub fn get_update_if(field: &Field) -> Option<String> {
+    get_str(&field.attrs, "update_if")
+}
+
+/// Get field schema function (if present).
+pub fn get_field_schema(field: &Field) -> Option<String> {
+    get_str(&field.attrs, "schema_with")
+}
+
+/// Check if a given field or container has a default value.
+pub fn has_default(attrs: &[Attribute]) -> bool {
+    has_flag(attrs, "default") || get_default(attrs).is_some()
 }
 
 /// Get field default function (if present).
 pub fn get_field_default(field: &Field) -> Option<String> {
-    if let Some(v) = get_attr_value(&field.attrs, "default") {
-        if let Some(Lit::Str(n)) = v.lit() {
-            Some(n.value())
-        } else {
-            panic!("invalid default attribute");
-        }
-    } else {
-        None
-    }
+    get_default(&field.attrs)
+}
+
+/// Get default function given using the `default = "..."` attribute (if
+/// present).
+pub fn get_default(attrs: &[Attribute]) -> Option<String> {
+    get_str(attrs, "default")
+}
+
+/// Field validator.
+#[derive(Clone)]
+pub enum Validator {
+    Range {
+        min: Option<Expr>,
+        max: Option<Expr>,
+    },
+    Length {
+        min: Option<Expr>,
+        max: Option<Expr>,
+    },
+    NonEmpty,
+    Regex(String),
+    Custom(String),
+}
+
+/// Get validators for a given field.
+pub fn get_field_validators(field: &Field) -> Vec<Validator> {
+    check::parse(&field.attrs)
+        .get_validators()
+        .cloned()
+        .collect()
 }
 
 /// Get enum tag attribute (if present).
 pub fn get_enum_tag(attrs: &[Attribute]) -> Option<String> {
-    if let Some(v) = get_attr_value(attrs, "tag") {
-        if let Some(Lit::Str(n)) = v.lit() {
-            Some(n.value())
-        } else {
-            panic!("invalid tag attribute");
-        }
-    } else {
-        None
-    }
+    get_str(attrs, "tag")
 }
 
 /// Get enum content attribute (if present).
 pub fn get_enum_content(attrs: &[Attribute]) -> Option<String> {
-    if let Some(v) = get_attr_value(attrs, "content") {
-        if let Some(Lit::Str(n)) = v.lit() {
-            Some(n.value())
-        } else {
-            panic!("invalid content attribute");
-        }
-    } else {
-        None
-    }
+    get_str(attrs, "content")
 }
 
 /// Get `from` attribute (if present).
 pub fn get_from(attrs: &[Attribute]) -> Option<String> {
-    if let Some(v) = get_attr_value(attrs, "from") {
-        if let Some(Lit::Str(n)) = v.lit() {
-            Some(n.value())
-        } else {
-            panic!("invalid from attribute");
-        }
+    get_str(attrs, "from")
+}
+
+/// Get `try_from` attribute (if present).
+pub fn get_try_from(attrs: &[Attribute]) -> Option<String> {
+    get_str(attrs, "try_from")
+}
+
+/// Get `into` attribute (if present).
+pub fn get_into(attrs: &[Attribute]) -> Option<String> {
+    get_str(attrs, "into")
+}
+
+/// Get `try_into` attribute (if present).
+pub fn get_try_into(attrs: &[Attribute]) -> Option<String> {
+    get_str(attrs, "try_into")
+}
+
+/// Get `repr` attribute (if present).
+pub fn get_repr(attrs: &[Attribute]) -> Option<String> {
+    get_str(attrs, "repr")
+}
+
+/// Get the container `validate_with` attribute (if present).
+pub fn get_validate_with(attrs: &[Attribute]) -> Option<String> {
+    get_str(attrs, "validate_with")
+}
+
+/// Get the container `after_update` hook path (if present).
+pub fn get_after_update(attrs: &[Attribute]) -> Option<String> {
+    get_str(attrs, "after_update")
+}
+
+/// Get traits derived for the companion patch type (if present).
+pub fn get_patch_derive(attrs: &[Attribute]) -> Option<String> {
+    get_str(attrs, "patch_derive")
+}
+
+/// Get the only non-skipped field of a given transparent container (if the
+/// container is transparent).
+pub fn get_transparent_field<'a>(
+    attrs: &[Attribute],
+    data: &'a Data,
+) -> Option<(Member, &'a Field)> {
+    if !has_flag(attrs, "transparent") {
+        return None;
+    }
+
+    let fields = if let Data::Struct(data) = data {
+        &data.fields
     } else {
-        None
+        panic!("the transparent attribute is supported only for structs");
+    };
+
+    let mut res = None;
+
+    for (index, field) in fields.iter().enumerate() {
+        if has_flag(&field.attrs, "skip") {
+            continue;
+        }
+
+        if res.is_some() {
+            panic!("transparent struct must have exactly one non-skipped field");
+        }
+
+        let member = if let Some(ident) = &field.ident {
+            Member::Named(ident.clone())
+        } else {
+            Member::Unnamed(index.into())
+        };
+
+        res = Some((member, field));
     }
+
+    let res = res.expect("transparent struct must have exactly one non-skipped field");
+
+    Some(res)
+}
+
+/// Get where predicates given using the `bound` attribute for a given
+/// derived trait (if present).
+///
+/// The attribute can be either `bound = "..."` applying to all derived traits
+/// or `bound(serialize = "...", deserialize = "...", ...)`.
+pub fn get_bound(attrs: &[Attribute], key: &str) -> Option<Vec<WherePredicate>> {
+    check::parse(attrs)
+        .get_bound(key)
+        .map(|bound| bound.to_vec())
+}
+
+/// Keyed update of a vector field.
+#[derive(Clone)]
+pub struct UpdateByKey {
+    /// Name of the key field of the vector elements.
+    pub key: String,
+    /// Remove existing elements without a match in the input.
+    pub remove_unmatched: bool,
+}
+
+/// Get the update_by_key attribute for a given field (if present).
+///
+/// The attribute can be either `update_by_key = "..."` keeping all unmatched
+/// elements or `update_by_key(key = "...", unmatched = "keep" | "remove")`.
+pub fn get_update_by_key(field: &Field) -> Option<UpdateByKey> {
+    check::parse(&field.attrs).get_update_by_key().cloned()
 }
 
 /// Get the rename attribute for a given enum variant or the variant name.
 pub fn get_variant_name(variant: &Variant) -> String {
-    if let Some(v) = get_attr_value(&variant.attrs, "rename") {
-        if let Some(Lit::Str(n)) = v.lit() {
-            return n.value();
-        } else {
-            panic!("invalid rename attribute");
-        }
+    if let Some(name) = get_str(&variant.attrs, "rename") {
+        return name;
     }
 
     variant.ident.to_string()
 }
 
-/// Get value of a given attribute.
-pub fn get_attr_value(attrs: &[Attribute], name: &str) -> Option<Expr> {
-    for attr in attrs {
-        if attr.path().is_ident("serde") {
-            if let Ok(nested) = attr.parse_args_with(parse_nested_meta) {
-                for meta in nested {
-                    if let Meta::NameValue(a) = meta {
-                        if a.path.is_ident(name) {
-                            return Some(a.value);
-                        }
-                    }
-                }
-            }
+/// Get serializer/deserializer module path for a given enum variant (if
+/// present).
+pub fn get_variant_with(variant: &Variant) -> Option<String> {
+    get_str(&variant.attrs, "with")
+}
+
+/// Get the `crate` attribute (if present).
+///
+/// The attribute is accepted only within `#[serde_lite(...)]` because
+/// `#[serde(crate = "...")]` refers to the serde crate.
+pub fn get_crate(attrs: &[Attribute]) -> Option<String> {
+    get_str(attrs, "crate")
+}
+
+/// Get discriminant expressions of all variants of a given enum.
+///
+/// Variants without an explicit discriminant get the discriminant of the
+/// previous variant plus one (the first one gets zero).
+pub fn get_discriminants(data: &DataEnum) -> Vec<TokenStream> {
+    let mut res = Vec::new();
+
+    let mut base = None;
+    let mut offset = 0;
+
+    for variant in &data.variants {
+        if let Some((_, expr)) = &variant.discriminant {
+            base = Some(expr);
+            offset = 0;
         }
+
+        let loffset = Literal::usize_unsuffixed(offset);
+
+        let discriminant = match base {
+            Some(base) if offset > 0 => quote!((#base) + #loffset),
+            Some(base) => quote!(#base),
+            None => quote!(#loffset),
+        };
+
+        res.push(discriminant);
+
+        offset += 1;
     }
 
-    None
+    res
+}
+
+/// Check if a given enum variant is the fallback for unknown variants.
+pub fn is_other_variant(variant: &Variant) -> bool {
+    has_flag(&variant.attrs, "other")
+}
+
+/// Check if a given enum variant captures unknown variants (i.e. it is a
+/// tuple variant marked with the `other` attribute).
+pub fn is_capturing_variant(variant: &Variant) -> bool {
+    is_other_variant(variant) && !matches!(variant.fields, Fields::Unit)
+}
+
+/// Get string value of a given attribute (if present).
+fn get_str(attrs: &[Attribute], name: &str) -> Option<String> {
+    check::parse(attrs).get_str(name).map(String::from)
 }
 
 /// Check if a given attribute flag is present.
 pub fn has_flag(attrs: &[Attribute], name: &str) -> bool {
-    for attr in attrs {
-        if attr.path().is_ident("serde") {
-            if let Ok(nested) = attr.parse_args_with(parse_nested_meta) {
-                for meta in nested {
-                    if let Meta::Path(a) = meta {
-                        if a.is_ident(name) {
-                            return true;
-                        }
-                    }
-                }
-            }
-        }
+    check::parse(attrs).has_flag(name)
+}
+
+/// Get the inner type of a given flattened `Option` field.
+///
+/// `None` is returned if the field is not flattened or if it is not an
+/// `Option`.
+pub fn get_flattened_option(field: &Field) -> Option<&Type> {
+    if has_flag(&field.attrs, "flatten") {
+        get_option_type(&field.ty)
+    } else {
+        None
     }
+}
+
+/// Get an expression returning keys consumed by a given flattened field.
+///
+/// Fields with a custom deserializer can consume any key.
+pub fn get_flattened_keys(field: &Field) -> TokenStream {
+    if get_field_deserializer(field).is_some() || get_field_with(field).is_some() {
+        quote!(None::<Vec<&'static str>>)
+    } else {
+        let ty = &field.ty;
+
+        quote!(<#ty as serde_lite::Deserialize>::flattened_keys())
+    }
+}
 
-    false
+/// Get the inner type of a given `Option` type.
+///
+/// `None` is returned if the type is not an `Option`.
+pub fn get_option_type(ty: &Type) -> Option<&Type> {
+    get_wrapped_type(ty, "Option")
 }
 
-/// Helper trait.
-trait ExprEx {
-    /// Get the literal expression (if any).
-    fn lit(&self) -> Option<&Lit>;
+/// Check if a given type is `Patch<T>`.
+pub fn is_patch_type(ty: &Type) -> bool {
+    get_wrapped_type(ty, "Patch").is_some()
 }
 
-impl ExprEx for Expr {
-    fn lit(&self) -> Option<&Lit> {
-        if let Expr::Lit(l) = self {
-            Some(&l.lit)
-        } else {
-            None
+/// Get the companion patch type of a given type (i.e. the type name with the
+/// `Patch` suffix).
+///
+/// `None` is returned if the type is not a named type.
+pub fn get_patch_type(ty: &Type) -> Option<Type> {
+    let mut ty = match ty {
+        Type::Path(t) if t.qself.is_none() => t.clone(),
+        _ => return None,
+    };
+
+    let segment = ty.path.segments.last_mut()?;
+
+    segment.ident = format_ident!("{}Patch", segment.ident);
+
+    Some(Type::Path(ty))
+}
+
+/// Get the type parameter of a given generic type with a given name.
+fn get_wrapped_type<'a>(ty: &'a Type, name: &str) -> Option<&'a Type> {
+    let path = match ty {
+        Type::Path(t) if t.qself.is_none() => &t.path,
+        _ => return None,
+    };
+
+    let segment = path.segments.last().filter(|s| s.ident == name)?;
+
+    if let PathArguments::AngleBracketed(args) = &segment.arguments {
+        if let Some(GenericArgument::Type(inner)) = args.args.first() {
+            return Some(inner);
         }
     }
-}
 
-/// Helper function.
-fn parse_nested_meta(stream: ParseStream) -> Result<Punctuated<Meta, Comma>> {
-    Punctuated::parse_terminated(stream)
+    None
 }
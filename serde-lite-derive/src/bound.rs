@@ -0,0 +1,268 @@
+use std::collections::HashSet;
+
+use syn::{
+    Data, DeriveInput, Field, GenericArgument, Generics, Ident, PathArguments, ReturnType, Type,
+    TypeParamBound, WherePredicate,
+};
+
+use crate::attributes;
+
+/// Derived trait.
+#[derive(Copy, Clone)]
+pub enum Trait {
+    Serialize,
+    Deserialize,
+    Update,
+    Schema,
+}
+
+impl Trait {
+    /// Get the key used for this trait in the `bound(...)` attribute.
+    fn key(self) -> &'static str {
+        match self {
+            Self::Serialize => "serialize",
+            Self::Deserialize => "deserialize",
+            Self::Update => "update",
+            Self::Schema => "schema",
+        }
+    }
+
+    /// Get path of the trait.
+    fn path(self) -> syn::Path {
+        match self {
+            Self::Serialize => syn::parse_quote!(serde_lite::Serialize),
+            Self::Deserialize => syn::parse_quote!(serde_lite::Deserialize),
+            Self::Update => syn::parse_quote!(serde_lite::Update),
+            Self::Schema => syn::parse_quote!(serde_lite::Schema),
+        }
+    }
+
+    /// Check if a given field needs the trait to be implemented for its
+    /// type.
+    fn is_required_by(self, field: &Field) -> bool {
+        if attributes::has_flag(&field.attrs, "skip") {
+            return false;
+        }
+
+        let with = attributes::get_field_with(field).is_some();
+
+        match self {
+            Self::Serialize => {
+                !(attributes::has_flag(&field.attrs, "skip_serializing")
+                    || attributes::get_field_serializer(field).is_some()
+                    || with)
+            }
+            Self::Deserialize => {
+                !(attributes::has_flag(&field.attrs, "skip_deserializing")
+                    || attributes::get_field_deserializer(field).is_some()
+                    || with)
+            }
+            Self::Update => {
+                !(attributes::has_flag(&field.attrs, "skip_deserializing")
+                    || attributes::has_flag(&field.attrs, "skip_updating")
+                    || attributes::has_flag(&field.attrs, "read_only")
+                    || attributes::get_field_updater(field).is_some()
+                    || with)
+            }
+            Self::Schema => attributes::get_field_schema(field).is_none(),
+        }
+    }
+}
+
+/// Get generics of a given type extended with where predicates required by
+/// the derived implementation of a given trait.
+pub fn with_bound(input: &DeriveInput, tr: Trait) -> Generics {
+    let mut generics = input.generics.clone();
+
+    let predicates = get_where_predicates(input, tr);
+
+    if !predicates.is_empty() {
+        generics.make_where_clause().predicates.extend(predicates);
+    }
+
+    generics
+}
+
+/// Get where predicates required by the derived implementation of a given
+/// trait.
+///
+/// The predicates given using the container `bound` attribute are used if
+/// present. Otherwise, the trait bound is inferred for all type parameters
+/// used in fields that need to be (de)serialized using the trait. The
+/// inferred bound can be replaced using the field `bound` attribute.
+pub fn get_where_predicates(input: &DeriveInput, tr: Trait) -> Vec<WherePredicate> {
+    if let Some(predicates) = attributes::get_bound(&input.attrs, tr.key()) {
+        return predicates;
+    }
+
+    let delegated = attributes::get_from(&input.attrs).is_some()
+        || attributes::get_try_from(&input.attrs).is_some()
+        || attributes::get_into(&input.attrs).is_some()
+        || attributes::get_try_into(&input.attrs).is_some();
+
+    if delegated {
+        return Vec::new();
+    }
+
+    let params = input
+        .generics
+        .type_params()
+        .map(|param| &param.ident)
+        .collect::<HashSet<_>>();
+
+    let mut fields = Vec::new();
+
+    match &input.data {
+        Data::Struct(data) => fields.extend(data.fields.iter()),
+        Data::Enum(data) => {
+            for variant in &data.variants {
+                if attributes::get_variant_with(variant).is_none() {
+                    fields.extend(variant.fields.iter());
+                }
+            }
+        }
+        Data::Union(_) => (),
+    }
+
+    let mut predicates = Vec::new();
+    let mut used = Vec::new();
+
+    // elements of vectors updated by key are serialized to get their keys
+    let mut serialized = Vec::new();
+
+    // Deserialize is a supertrait of Update, so the update bounds must
+    // include all bounds of Deserialize (including fields that are not
+    // updated)
+    let mut deserialized = Vec::new();
+
+    for field in &fields {
+        if !matches!(tr, Trait::Update) || !Trait::Deserialize.is_required_by(field) {
+            continue;
+        } else if let Some(bound) = attributes::get_bound(&field.attrs, Trait::Deserialize.key()) {
+            predicates.extend(bound);
+        } else {
+            collect_type_params(&field.ty, &params, &mut deserialized);
+        }
+    }
+
+    for field in fields {
+        if !tr.is_required_by(field) {
+            continue;
+        } else if let Some(bound) = attributes::get_bound(&field.attrs, tr.key()) {
+            predicates.extend(bound);
+        } else {
+            collect_type_params(&field.ty, &params, &mut used);
+
+            if let (Trait::Update, Some(_)) = (tr, attributes::get_update_by_key(field)) {
+                collect_type_params(&field.ty, &params, &mut serialized);
+            }
+        }
+    }
+
+    let path = tr.path();
+    let serialize = Trait::Serialize.path();
+    let deserialize = Trait::Deserialize.path();
+
+    // keep the order of the type parameters as declared
+    for param in input.generics.type_params() {
+        let ident = &param.ident;
+
+        if used.contains(&ident) {
+            predicates.push(syn::parse_quote!(#ident: #path));
+        } else if deserialized.contains(&ident) {
+            predicates.push(syn::parse_quote!(#ident: #deserialize));
+        }
+
+        if serialized.contains(&ident) {
+            predicates.push(syn::parse_quote!(#ident: #serialize));
+        }
+    }
+
+    predicates
+}
+
+/// Collect all type parameters used in a given type.
+fn collect_type_params<'a>(ty: &'a Type, params: &HashSet<&Ident>, used: &mut Vec<&'a Ident>) {
+    match ty {
+        Type::Array(t) => collect_type_params(&t.elem, params, used),
+        Type::Group(t) => collect_type_params(&t.elem, params, used),
+        Type::Paren(t) => collect_type_params(&t.elem, params, used),
+        Type::Ptr(t) => collect_type_params(&t.elem, params, used),
+        Type::Reference(t) => collect_type_params(&t.elem, params, used),
+        Type::Slice(t) => collect_type_params(&t.elem, params, used),
+        Type::Tuple(t) => {
+            for elem in &t.elems {
+                collect_type_params(elem, params, used);
+            }
+        }
+        Type::Path(t) => {
+            if let Some(qself) = &t.qself {
+                collect_type_params(&qself.ty, params, used);
+            }
+
+            let path = &t.path;
+
+            // PhantomData<T> does not require anything from T
+            if path.segments.last().map(|s| s.ident == "PhantomData") == Some(true) {
+                return;
+            }
+
+            if path.leading_colon.is_none() {
+                if let Some(first) = path.segments.first() {
+                    if params.contains(&first.ident) {
+                        used.push(&first.ident);
+                    }
+                }
+            }
+
+            for segment in &path.segments {
+                match &segment.arguments {
+                    PathArguments::AngleBracketed(args) => {
+                        for arg in &args.args {
+                            match arg {
+                                GenericArgument::Type(t) => collect_type_params(t, params, used),
+                                GenericArgument::AssocType(t) => {
+                                    collect_type_params(&t.ty, params, used)
+                                }
+                                _ => (),
+                            }
+                        }
+                    }
+                    PathArguments::Parenthesized(args) => {
+                        for input in &args.inputs {
+                            collect_type_params(input, params, used);
+                        }
+
+                        if let ReturnType::Type(_, output) = &args.output {
+                            collect_type_params(output, params, used);
+                        }
+                    }
+                    PathArguments::None => (),
+                }
+            }
+        }
+        Type::TraitObject(t) => collect_bound_type_params(&t.bounds, params, used),
+        Type::ImplTrait(t) => collect_bound_type_params(&t.bounds, params, used),
+        _ => (),
+    }
+}
+
+/// Collect all type parameters used in given trait bounds.
+fn collect_bound_type_params<'a, I>(bounds: I, params: &HashSet<&Ident>, used: &mut Vec<&'a Ident>)
+where
+    I: IntoIterator<Item = &'a TypeParamBound>,
+{
+    for bound in bounds {
+        if let TypeParamBound::Trait(bound) = bound {
+            for segment in &bound.path.segments {
+                if let PathArguments::AngleBracketed(args) = &segment.arguments {
+                    for arg in &args.args {
+                        if let GenericArgument::Type(t) = arg {
+                            collect_type_params(t, params, used);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,930 @@
+use proc_macro2::Span;
+use quote::quote;
+use syn::{
+    parse::Parser, punctuated::Punctuated, spanned::Spanned, token::Comma, Attribute, Data,
+    DeriveInput, Error, Expr, ExprPath, Fields, Lit, LitStr, Meta, Result, Type, WherePredicate,
+};
+
+use crate::{
+    attributes::{self, UpdateByKey, Validator},
+    bound::Trait,
+};
+
+/// Expected attribute value.
+#[derive(Copy, Clone)]
+enum Kind {
+    /// Attribute without any value (e.g. `skip`).
+    Flag,
+    /// String literal (e.g. `rename = "foo"`).
+    Str,
+    /// String literal containing a path (e.g. `with = "foo::bar"`).
+    Path,
+    /// String literal containing a type (e.g. `from = "Foo"`).
+    Type,
+    /// Either a flag or a string literal containing a path.
+    FlagOrPath,
+    /// Field validators.
+    Validate,
+    /// Where predicates.
+    Bound,
+    /// Key of vector elements (e.g. `update_by_key = "id"`).
+    UpdateByKey,
+    /// String literal containing comma-separated paths (e.g.
+    /// `patch_derive = "Debug, Clone"`).
+    PathList,
+}
+
+/// Known container attributes.
+const CONTAINER_ATTRS: &[(&str, Kind)] = &[
+    ("tag", Kind::Str),
+    ("content", Kind::Str),
+    ("validate_with", Kind::Path),
+    ("after_update", Kind::Path),
+    ("from", Kind::Type),
+    ("try_from", Kind::Type),
+    ("into", Kind::Type),
+    ("try_into", Kind::Type),
+    ("transparent", Kind::Flag),
+    ("default", Kind::FlagOrPath),
+    ("bound", Kind::Bound),
+    ("crate", Kind::Path),
+    ("repr", Kind::Type),
+    ("patch_derive", Kind::PathList),
+];
+
+/// Types allowed in the `repr` attribute.
+const REPR_TYPES: &[&str] = &["u8", "u16", "u32", "u64", "i8", "i16", "i32", "i64"];
+
+/// Known field attributes.
+const FIELD_ATTRS: &[(&str, Kind)] = &[
+    ("default", Kind::FlagOrPath),
+    ("flatten", Kind::Flag),
+    ("rename", Kind::Str),
+    ("skip", Kind::Flag),
+    ("skip_serializing", Kind::Flag),
+    ("skip_serializing_if", Kind::Path),
+    ("skip_deserializing", Kind::Flag),
+    ("serialize_with", Kind::Path),
+    ("deserialize_with", Kind::Path),
+    ("update_with", Kind::Path),
+    ("skip_updating", Kind::Flag),
+    ("read_only", Kind::Flag),
+    ("update_once", Kind::Flag),
+    ("update_by_key", Kind::UpdateByKey),
+    ("on_update", Kind::Path),
+    ("update_if", Kind::Path),
+    ("with", Kind::Path),
+    ("schema_with", Kind::Path),
+    ("validate", Kind::Validate),
+    ("bound", Kind::Bound),
+    ("patchable", Kind::Flag),
+];
+
+/// Known enum variant attributes.
+const VARIANT_ATTRS: &[(&str, Kind)] = &[
+    ("rename", Kind::Str),
+    ("with", Kind::Path),
+    ("other", Kind::Flag),
+];
+
+/// Location of attributes.
+#[derive(Copy, Clone)]
+enum Location {
+    Container,
+    Field,
+    Variant,
+    /// Any location (used for reading already checked attributes).
+    Any,
+}
+
+impl Location {
+    /// Get the expected value of a given attribute (if the attribute is
+    /// known).
+    fn kind(self, name: &str) -> Option<Kind> {
+        let known: &[&[(&str, Kind)]] = match self {
+            Self::Container => &[CONTAINER_ATTRS],
+            Self::Field => &[FIELD_ATTRS],
+            Self::Variant => &[VARIANT_ATTRS],
+            Self::Any => &[CONTAINER_ATTRS, FIELD_ATTRS, VARIANT_ATTRS],
+        };
+
+        known
+            .iter()
+            .flat_map(|known| known.iter())
+            .find(|(known, _)| *known == name)
+            .map(|(_, kind)| *kind)
+    }
+
+    /// Get attributes supported only by serde.
+    fn serde_only(self) -> &'static [&'static str] {
+        match self {
+            Self::Container => SERDE_CONTAINER_ATTRS,
+            Self::Field => SERDE_FIELD_ATTRS,
+            Self::Variant => SERDE_VARIANT_ATTRS,
+            Self::Any => &[],
+        }
+    }
+
+    /// Get the location name used in error messages.
+    fn name(self) -> &'static str {
+        match self {
+            Self::Container => "container",
+            Self::Field => "field",
+            Self::Variant => "variant",
+            Self::Any => "",
+        }
+    }
+}
+
+/// Attributes supported only by serde that can be used on containers.
+const SERDE_CONTAINER_ATTRS: &[&str] = &[
+    "rename",
+    "rename_all",
+    "rename_all_fields",
+    "deny_unknown_fields",
+    "untagged",
+    "remote",
+    "crate",
+    "expecting",
+    "variant_identifier",
+    "field_identifier",
+];
+
+/// Attributes supported only by serde that can be used on fields.
+const SERDE_FIELD_ATTRS: &[&str] = &["alias", "borrow", "getter"];
+
+/// Attributes supported only by serde that can be used on enum variants.
+const SERDE_VARIANT_ATTRS: &[&str] = &[
+    "alias",
+    "rename_all",
+    "skip",
+    "skip_serializing",
+    "skip_deserializing",
+    "serialize_with",
+    "deserialize_with",
+    "bound",
+    "borrow",
+    "untagged",
+];
+
+/// Check all serde attributes of a given type.
+///
+/// All problems are reported at once as a single error that can be turned
+/// into a set of `compile_error!` invocations.
+pub fn check(input: &DeriveInput, tr: Trait) -> Result<()> {
+    let mut errors = Errors::default();
+
+    // types using the serde_lite attribute are expected to derive also the
+    // serde traits
+    let shared = uses_serde_lite(input);
+
+    let container = check_attrs(&input.attrs, Location::Container, shared, &mut errors);
+
+    match &input.data {
+        Data::Struct(data) => {
+            for key in ["tag", "content", "repr"] {
+                if let Some(span) = container.get(key) {
+                    errors.push(Error::new(
+                        span,
+                        format!("`{key}` can be used only on enums"),
+                    ));
+                }
+            }
+
+            let default = container.get("default").is_some();
+
+            check_fields(&data.fields, false, default, shared, &mut errors);
+        }
+        Data::Enum(data) => {
+            for key in ["transparent", "default"] {
+                if let Some(span) = container.get(key) {
+                    errors.push(Error::new(
+                        span,
+                        format!("`{key}` can be used only on structs"),
+                    ));
+                }
+            }
+
+            if let Some(span) = container.get("content") {
+                if container.get("tag").is_none() {
+                    errors.push(Error::new(
+                        span,
+                        "`content` can be used only together with `tag`",
+                    ));
+                }
+            }
+
+            if data.variants.is_empty() {
+                let msg = match tr {
+                    Trait::Serialize => Some("enum with no variants cannot be serialized"),
+                    Trait::Deserialize => Some("enum with no variants cannot be deserialized"),
+                    Trait::Schema => Some("enum with no variants cannot be described"),
+                    Trait::Update => None,
+                };
+
+                if let Some(msg) = msg {
+                    errors.push(Error::new_spanned(&input.ident, msg));
+                }
+            }
+
+            let mut other = None;
+
+            for variant in &data.variants {
+                let attrs = check_attrs(&variant.attrs, Location::Variant, shared, &mut errors);
+
+                if let Some(span) = attrs.get("with") {
+                    if variant.fields.is_empty() {
+                        errors.push(Error::new(
+                            span,
+                            "`with` cannot be used on variants without fields",
+                        ));
+                    }
+                }
+
+                if let Some(span) = attrs.get("other") {
+                    let valid = match &variant.fields {
+                        Fields::Unit => true,
+                        Fields::Unnamed(fields) => fields.unnamed.len() == 1,
+                        Fields::Named(_) => false,
+                    };
+
+                    if !valid {
+                        errors.push(Error::new(
+                            span,
+                            "`other` can be used only on unit variants and tuple variants with a single field",
+                        ));
+                    } else if other.replace(span).is_some() {
+                        errors.push(Error::new(
+                            span,
+                            "`other` can be used only on a single variant",
+                        ));
+                    }
+
+                    attrs.check_exclusive("with", "other", &mut errors);
+                }
+
+                if container.get("repr").is_some() {
+                    if let Some(span) = attrs.get("rename") {
+                        errors.push(Error::new(
+                            span,
+                            "`rename` cannot be used on variants of enums with `repr`",
+                        ));
+                    }
+
+                    let valid =
+                        matches!(variant.fields, Fields::Unit) || attrs.get("other").is_some();
+
+                    if !valid {
+                        errors.push(Error::new_spanned(
+                            &variant.ident,
+                            "enums with `repr` can contain only unit variants",
+                        ));
+                    }
+                }
+
+                check_fields(&variant.fields, true, false, shared, &mut errors);
+            }
+        }
+        Data::Union(_) => {
+            let name = match tr {
+                Trait::Serialize => "Serialize",
+                Trait::Deserialize => "Deserialize",
+                Trait::Update => "Update",
+                Trait::Schema => "Schema",
+            };
+
+            errors.push(Error::new_spanned(
+                &input.ident,
+                format!("derive {name} is not supported for union types"),
+            ));
+        }
+    }
+
+    container.check_exclusive("try_from", "from", &mut errors);
+    container.check_exclusive("try_into", "into", &mut errors);
+    container.check_exclusive("tag", "repr", &mut errors);
+
+    // invalid values are reported by parse_value()
+    if let (Some(span), Some(repr)) = (container.get("repr"), container.get_str("repr")) {
+        if !REPR_TYPES.contains(&repr) {
+            errors.push(Error::new(
+                span,
+                format!("`repr` must be one of: {}", REPR_TYPES.join(", ")),
+            ));
+        }
+    }
+
+    if let Some(span) = container.get("transparent") {
+        for key in ["from", "try_from", "into", "try_into", "default"] {
+            container.check_exclusive(key, "transparent", &mut errors);
+        }
+
+        if let Data::Struct(data) = &input.data {
+            let count = data
+                .fields
+                .iter()
+                .filter(|field| !attributes::has_flag(&field.attrs, "skip"))
+                .count();
+
+            if count != 1 {
+                errors.push(Error::new(
+                    span,
+                    "transparent struct must have exactly one non-skipped field",
+                ));
+            }
+        }
+    }
+
+    errors.finish()
+}
+
+/// Check attributes of given fields.
+///
+/// The fields are either struct fields or enum variant fields. The
+/// `container_default` flag indicates that the container has a default value.
+fn check_fields(
+    fields: &Fields,
+    variant: bool,
+    container_default: bool,
+    shared: bool,
+    errors: &mut Errors,
+) {
+    // span of the first tuple field that can be skipped during serialization
+    let mut skippable: Option<Span> = None;
+
+    for field in fields {
+        let attrs = check_attrs(&field.attrs, Location::Field, shared, errors);
+
+        // skipping a tuple field during serialization would shift all
+        // following elements, so it is allowed only for trailing fields
+        // that can be deserialized from a shorter array
+        if field.ident.is_none() && attrs.get("skip").is_none() {
+            let defaulted = container_default
+                || attrs.get("default").is_some()
+                || attributes::is_patch_type(&field.ty);
+
+            if let Some(span) = attrs.get("skip_serializing_if") {
+                if !defaulted {
+                    errors.push(Error::new(
+                        span,
+                        "`skip_serializing_if` can be used on tuple fields only if they have \
+                        default values",
+                    ));
+                }
+
+                skippable = skippable.or(Some(span));
+            } else if let Some(span) = skippable.take() {
+                errors.push(Error::new(
+                    span,
+                    "`skip_serializing_if` can be used on tuple fields only if all following \
+                    fields can be skipped as well",
+                ));
+            }
+        }
+
+        if variant {
+            if let Some(span) = attrs.get("on_update") {
+                errors.push(Error::new(
+                    span,
+                    "`on_update` cannot be used on fields of enum variants",
+                ));
+            }
+        }
+
+        if field.ident.is_none() {
+            for key in ["flatten", "rename"] {
+                if let Some(span) = attrs.get(key) {
+                    errors.push(Error::new(
+                        span,
+                        format!("`{key}` can be used only on named fields"),
+                    ));
+                }
+            }
+        }
+
+        attrs.check_exclusive("rename", "flatten", errors);
+
+        for key in ["serialize_with", "deserialize_with", "update_with"] {
+            attrs.check_exclusive(key, "with", errors);
+        }
+
+        attrs.check_exclusive("read_only", "skip_updating", errors);
+        attrs.check_exclusive("update_once", "skip_updating", errors);
+        attrs.check_exclusive("update_once", "read_only", errors);
+
+        for key in ["read_only", "update_once"] {
+            attrs.check_exclusive(key, "flatten", errors);
+        }
+
+        for key in [
+            "update_with",
+            "with",
+            "flatten",
+            "skip_updating",
+            "read_only",
+            "update_once",
+        ] {
+            attrs.check_exclusive("update_by_key", key, errors);
+        }
+
+        for key in ["skip", "skip_deserializing", "skip_updating", "read_only"] {
+            attrs.check_exclusive("on_update", key, errors);
+            attrs.check_exclusive("update_if", key, errors);
+        }
+
+        attrs.check_exclusive("update_if", "flatten", errors);
+
+        for key in [
+            "skip",
+            "skip_deserializing",
+            "skip_updating",
+            "read_only",
+            "flatten",
+            "serialize_with",
+            "deserialize_with",
+            "update_with",
+            "with",
+        ] {
+            attrs.check_exclusive("patchable", key, errors);
+        }
+
+        if let Some(span) = attrs.get("patchable") {
+            if attributes::get_option_type(&field.ty).is_some() {
+                errors.push(Error::new(
+                    span,
+                    "`patchable` cannot be used on `Option` fields",
+                ));
+            } else if attributes::get_patch_type(&field.ty).is_none() {
+                errors.push(Error::new(
+                    span,
+                    "`patchable` can be used only on fields of named types",
+                ));
+            }
+        }
+
+        if let Some(span) = attrs.get("update_once") {
+            if attributes::get_option_type(&field.ty).is_none() {
+                errors.push(Error::new(
+                    span,
+                    "`update_once` can be used only on `Option` fields",
+                ));
+            }
+        }
+    }
+}
+
+/// Check if the serde_lite attribute is used anywhere within a given type.
+fn uses_serde_lite(input: &DeriveInput) -> bool {
+    let is_serde_lite = |attr: &Attribute| attr.path().is_ident("serde_lite");
+
+    let fields_use_serde_lite = |fields: &Fields| {
+        fields
+            .iter()
+            .any(|field| field.attrs.iter().any(is_serde_lite))
+    };
+
+    if input.attrs.iter().any(is_serde_lite) {
+        return true;
+    }
+
+    match &input.data {
+        Data::Struct(data) => fields_use_serde_lite(&data.fields),
+        Data::Enum(data) => data.variants.iter().any(|variant| {
+            variant.attrs.iter().any(is_serde_lite) || fields_use_serde_lite(&variant.fields)
+        }),
+        Data::Union(_) => false,
+    }
+}
+
+/// Parse serde attributes of a single item.
+///
+/// The attributes are expected to be checked using `check()`, so any invalid
+/// attribute is already reported as a compile error. Invalid attributes are
+/// left out of the result.
+pub fn parse(attrs: &[Attribute]) -> Attrs {
+    check_attrs(attrs, Location::Any, true, &mut Errors::default())
+}
+
+/// Parse given attributes and check them against the known attributes of a
+/// given location.
+///
+/// Items of `#[serde(...)]` attributes that are supported only by serde are
+/// ignored if the attributes are shared with serde.
+fn check_attrs(
+    attrs: &[Attribute],
+    location: Location,
+    shared: bool,
+    errors: &mut Errors,
+) -> Attrs {
+    let mut res = Attrs::default();
+
+    for attr in attrs {
+        let namespace = if attr.path().is_ident("serde_lite") {
+            "serde_lite"
+        } else if attr.path().is_ident("serde") {
+            "serde"
+        } else {
+            continue;
+        };
+
+        let nested = match attr.parse_args_with(Punctuated::<Meta, Comma>::parse_terminated) {
+            Ok(nested) => nested,
+            Err(err) => {
+                errors.push(err);
+                continue;
+            }
+        };
+
+        for meta in nested {
+            let path = meta.path();
+
+            let name = path
+                .get_ident()
+                .map(|ident| ident.to_string())
+                .unwrap_or_default();
+
+            // the crate attribute of serde refers to the serde crate
+            let kind = location
+                .kind(&name)
+                .filter(|_| namespace == "serde_lite" || name != "crate");
+
+            let kind = if let Some(kind) = kind {
+                kind
+            } else if namespace == "serde"
+                && shared
+                && location.serde_only().contains(&name.as_str())
+            {
+                continue;
+            } else if let Location::Any = location {
+                continue;
+            } else {
+                let name = quote!(#path).to_string().replace(' ', "");
+
+                let msg = if name == "crate" {
+                    String::from("use `#[serde_lite(crate = \"...\")]` to set path to serde-lite")
+                } else {
+                    format!("unknown {namespace} {} attribute `{name}`", location.name())
+                };
+
+                errors.push(Error::new_spanned(path, msg));
+
+                continue;
+            };
+
+            let duplicate = res
+                .items
+                .iter()
+                .any(|item| item.name == name && item.namespace == namespace);
+
+            if duplicate && !matches!(kind, Kind::Validate) {
+                errors.push(Error::new_spanned(
+                    path,
+                    format!("duplicate {namespace} attribute `{name}`"),
+                ));
+            }
+
+            let value = parse_value(&name, kind, &meta)
+                .map_err(|err| errors.push(err))
+                .ok();
+
+            res.items.push(Item {
+                name,
+                namespace,
+                span: path.span(),
+                value,
+            });
+        }
+    }
+
+    // items of the serde_lite attributes override the serde ones
+    res.items.sort_by_key(|item| item.namespace != "serde_lite");
+
+    res
+}
+
+/// Parse value of a given attribute.
+fn parse_value(name: &str, kind: Kind, meta: &Meta) -> Result<Value> {
+    match (kind, meta) {
+        (Kind::Flag, Meta::Path(_)) => Ok(Value::Flag),
+        (Kind::Flag, _) => Err(Error::new_spanned(
+            meta,
+            format!("unexpected value, expected just `{name}`"),
+        )),
+        (Kind::FlagOrPath, Meta::Path(_)) => Ok(Value::Flag),
+        (Kind::Str, _) => get_str(name, meta).map(|lit| Value::Str(lit.value())),
+        (Kind::Path, _) | (Kind::FlagOrPath, _) => {
+            let lit = get_str(name, meta)?;
+
+            parse_path(&lit).map(Value::Str)
+        }
+        (Kind::Type, _) => {
+            let lit = get_str(name, meta)?;
+
+            lit.parse::<Type>()
+                .map(|_| Value::Str(lit.value()))
+                .map_err(|_| Error::new(lit.span(), "expected a type"))
+        }
+        (Kind::Validate, Meta::List(l)) => {
+            let nested = l.parse_args_with(Punctuated::<Meta, Comma>::parse_terminated)?;
+
+            nested
+                .iter()
+                .map(parse_validator)
+                .collect::<Result<_>>()
+                .map(Value::Validators)
+        }
+        (Kind::Validate, _) => {
+            let lit = get_str(name, meta)?;
+
+            parse_path(&lit).map(|path| Value::Validators(vec![Validator::Custom(path)]))
+        }
+        (Kind::Bound, Meta::List(l)) => {
+            let nested = l.parse_args_with(Punctuated::<Meta, Comma>::parse_terminated)?;
+
+            let mut res = Vec::new();
+
+            for meta in nested {
+                let known = ["serialize", "deserialize", "update", "schema"];
+
+                let key = known
+                    .iter()
+                    .find(|key| meta.path().is_ident(key))
+                    .ok_or_else(|| {
+                        Error::new_spanned(
+                            meta.path(),
+                            "expected `serialize`, `deserialize`, `update` or `schema`",
+                        )
+                    })?;
+
+                let lit = get_str("bound", &meta)?;
+
+                res.push((Some(*key), parse_where_predicates(&lit)?));
+            }
+
+            Ok(Value::Bound(res))
+        }
+        (Kind::Bound, _) => {
+            let lit = get_str(name, meta)?;
+
+            Ok(Value::Bound(vec![(None, parse_where_predicates(&lit)?)]))
+        }
+        (Kind::UpdateByKey, Meta::List(l)) => {
+            let nested = l.parse_args_with(Punctuated::<Meta, Comma>::parse_terminated)?;
+
+            let mut key = None;
+            let mut remove_unmatched = false;
+
+            for meta in nested {
+                if meta.path().is_ident("key") {
+                    key = Some(get_str("key", &meta)?.value());
+                } else if meta.path().is_ident("unmatched") {
+                    let lit = get_str("unmatched", &meta)?;
+
+                    remove_unmatched = match lit.value().as_str() {
+                        "keep" => false,
+                        "remove" => true,
+                        _ => return Err(Error::new(lit.span(), "expected `keep` or `remove`")),
+                    };
+                } else {
+                    return Err(Error::new_spanned(
+                        meta.path(),
+                        "expected `key` or `unmatched`",
+                    ));
+                }
+            }
+
+            let key = key.ok_or_else(|| Error::new_spanned(l, "missing `key`"))?;
+
+            Ok(Value::UpdateByKey(UpdateByKey {
+                key,
+                remove_unmatched,
+            }))
+        }
+        (Kind::UpdateByKey, _) => get_str(name, meta).map(|lit| {
+            Value::UpdateByKey(UpdateByKey {
+                key: lit.value(),
+                remove_unmatched: false,
+            })
+        }),
+        (Kind::PathList, _) => {
+            let lit = get_str(name, meta)?;
+
+            lit.parse_with(Punctuated::<syn::Path, Comma>::parse_terminated)
+                .map(|_| Value::Str(lit.value()))
+                .map_err(|_| Error::new(lit.span(), "expected comma-separated paths"))
+        }
+    }
+}
+
+/// Parse a given field validator.
+fn parse_validator(meta: &Meta) -> Result<Validator> {
+    match meta {
+        Meta::Path(p) if p.is_ident("non_empty") => Ok(Validator::NonEmpty),
+        Meta::List(l) if l.path.is_ident("range") || l.path.is_ident("length") => {
+            let nested = l.parse_args_with(Punctuated::<Meta, Comma>::parse_terminated)?;
+
+            if nested.is_empty() {
+                return Err(Error::new_spanned(l, "expected `min` and/or `max`"));
+            }
+
+            let mut min = None;
+            let mut max = None;
+
+            for meta in nested {
+                match meta {
+                    Meta::NameValue(a) if a.path.is_ident("min") => min = Some(a.value),
+                    Meta::NameValue(a) if a.path.is_ident("max") => max = Some(a.value),
+                    _ => {
+                        return Err(Error::new_spanned(
+                            meta,
+                            "expected `min = ...` or `max = ...`",
+                        ))
+                    }
+                }
+            }
+
+            if l.path.is_ident("range") {
+                Ok(Validator::Range { min, max })
+            } else {
+                Ok(Validator::Length { min, max })
+            }
+        }
+        Meta::NameValue(a) if a.path.is_ident("regex") => {
+            get_str("regex", meta).map(|lit| Validator::Regex(lit.value()))
+        }
+        Meta::NameValue(a) if a.path.is_ident("custom") => {
+            let lit = get_str("custom", meta)?;
+
+            parse_path(&lit).map(Validator::Custom)
+        }
+        _ => Err(Error::new_spanned(
+            meta.path(),
+            "unknown validator, expected `range`, `length`, `non_empty`, `regex` or `custom`",
+        )),
+    }
+}
+
+/// Get string literal value of a given attribute.
+fn get_str(name: &str, meta: &Meta) -> Result<LitStr> {
+    if let Meta::NameValue(a) = meta {
+        if let Expr::Lit(l) = &a.value {
+            if let Lit::Str(s) = &l.lit {
+                return Ok(s.clone());
+            }
+        }
+
+        Err(Error::new_spanned(&a.value, "expected a string literal"))
+    } else {
+        Err(Error::new_spanned(
+            meta,
+            format!("expected `{name} = \"...\"`"),
+        ))
+    }
+}
+
+/// Parse a path contained in a given string literal.
+fn parse_path(lit: &LitStr) -> Result<String> {
+    lit.parse::<ExprPath>()
+        .map(|_| lit.value())
+        .map_err(|_| Error::new(lit.span(), "expected a path"))
+}
+
+/// Parse where predicates contained in a given string literal.
+fn parse_where_predicates(lit: &LitStr) -> Result<Vec<WherePredicate>> {
+    Punctuated::<WherePredicate, Comma>::parse_terminated
+        .parse_str(&lit.value())
+        .map(|predicates| predicates.into_iter().collect())
+        .map_err(|err| Error::new(lit.span(), format!("invalid bound: {err}")))
+}
+
+/// Parsed value of an attribute.
+enum Value {
+    /// Attribute without any value.
+    Flag,
+    /// String value (e.g. a name, a path or a type).
+    Str(String),
+    /// Field validators.
+    Validators(Vec<Validator>),
+    /// Where predicates for all derived traits (`None`) or for a given
+    /// derived trait.
+    Bound(Vec<(Option<&'static str>, Vec<WherePredicate>)>),
+    /// Keyed update of a vector field.
+    UpdateByKey(UpdateByKey),
+}
+
+/// Single parsed attribute.
+struct Item {
+    name: String,
+    namespace: &'static str,
+    span: Span,
+    /// The value is missing if it is not valid.
+    value: Option<Value>,
+}
+
+/// Parsed serde attributes of a single item (a container, a field or an
+/// enum variant).
+#[derive(Default)]
+pub struct Attrs {
+    items: Vec<Item>,
+}
+
+impl Attrs {
+    /// Check if a given flag is present.
+    pub fn has_flag(&self, name: &str) -> bool {
+        self.values(name).any(|value| matches!(value, Value::Flag))
+    }
+
+    /// Get string value of a given attribute (if present).
+    pub fn get_str(&self, name: &str) -> Option<&str> {
+        self.values(name).find_map(|value| match value {
+            Value::Str(s) => Some(s.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Get all validators given using the `validate` attribute.
+    pub fn get_validators(&self) -> impl Iterator<Item = &Validator> {
+        self.values("validate")
+            .filter_map(|value| match value {
+                Value::Validators(validators) => Some(validators.iter()),
+                _ => None,
+            })
+            .flatten()
+    }
+
+    /// Get where predicates given using the `bound` attribute for a given
+    /// derived trait (if present).
+    pub fn get_bound(&self, key: &str) -> Option<&[WherePredicate]> {
+        self.values("bound")
+            .filter_map(|value| match value {
+                Value::Bound(bounds) => Some(bounds.iter()),
+                _ => None,
+            })
+            .flatten()
+            .find(|(k, _)| k.map(|k| k == key).unwrap_or(true))
+            .map(|(_, predicates)| predicates.as_slice())
+    }
+
+    /// Get the `update_by_key` attribute (if present).
+    pub fn get_update_by_key(&self) -> Option<&UpdateByKey> {
+        self.values("update_by_key").find_map(|value| match value {
+            Value::UpdateByKey(v) => Some(v),
+            _ => None,
+        })
+    }
+
+    /// Get valid values of a given attribute.
+    fn values<'a, 'b>(&'a self, name: &'b str) -> impl Iterator<Item = &'a Value> + 'b
+    where
+        'a: 'b,
+    {
+        self.items
+            .iter()
+            .filter(move |item| item.name == name)
+            .filter_map(|item| item.value.as_ref())
+    }
+
+    /// Get span of a given attribute (if present).
+    fn get(&self, name: &str) -> Option<Span> {
+        self.items
+            .iter()
+            .find(|item| item.name == name)
+            .map(|item| item.span)
+    }
+
+    /// Report an error if both given attributes are present.
+    fn check_exclusive(&self, name: &str, other: &str, errors: &mut Errors) {
+        if let Some(span) = self.get(name) {
+            if self.get(other).is_some() {
+                errors.push(Error::new(
+                    span,
+                    format!("`{name}` cannot be used together with `{other}`"),
+                ));
+            }
+        }
+    }
+}
+
+/// Error accumulator.
+#[derive(Default)]
+struct Errors(Option<Error>);
+
+impl Errors {
+    /// Add a given error.
+    fn push(&mut self, err: Error) {
+        if let Some(errors) = &mut self.0 {
+            errors.combine(err);
+        } else {
+            self.0 = Some(err);
+        }
+    }
+
+    /// Return all collected errors (if any).
+    fn finish(self) -> Result<()> {
+        if let Some(err) = self.0 {
+            Err(err)
+        } else {
+            Ok(())
+        }
+    }
+}
@@ -2,34 +2,217 @@ use std::str::FromStr;
 
 use proc_macro2::{Literal, Span, TokenStream};
 use quote::quote;
-use syn::{Attribute, Data, DataEnum, Fields, FieldsNamed, FieldsUnnamed, Ident, Variant};
+use syn::{
+    Attribute, Data, DataEnum, Field, Fields, FieldsNamed, FieldsUnnamed, Ident, Index, Member,
+    Variant,
+};
 use synstructure::AddBounds;
 
-use crate::attributes;
+use crate::{
+    attributes,
+    bound::{self, Trait},
+    check, validate,
+};
 
 /// Expand the derive Deserialize.
 // TODO: use features of synstructure more extensively.
 pub(crate) fn derive_deserialize(mut s: synstructure::Structure) -> TokenStream {
+    if let Err(err) = check::check(s.ast(), Trait::Deserialize) {
+        return err.to_compile_error();
+    }
+
     let deserialize = if let Some(delegated_type) = attributes::get_from(&s.ast().attrs) {
         expand_delegation(&delegated_type)
+    } else if let Some(delegated_type) = attributes::get_try_from(&s.ast().attrs) {
+        expand_fallible_delegation(&delegated_type)
+    } else if let Some((member, field)) =
+        attributes::get_transparent_field(&s.ast().attrs, &s.ast().data)
+    {
+        expand_transparent(&s.ast().data, member, field)
     } else {
         match s.ast().data.clone() {
-            Data::Struct(data) => expand_for_struct(data.fields),
+            Data::Struct(data) => expand_for_struct(data.fields, &s.ast().attrs),
             Data::Enum(data) => expand_for_enum(data, &s.ast().attrs),
             Data::Union(_) => panic!("derive Deserialize is not supported for union types"),
         }
     };
 
-    s.add_bounds(AddBounds::Generics);
-    s.bound_impl(
-        quote!(serde_lite::Deserialize),
+    let deserialize = if let Some(path) = attributes::get_validate_with(&s.ast().attrs) {
+        let path = TokenStream::from_str(&path)
+            .expect("invalid path given for the validate_with attribute");
+
         quote! {
-            #[allow(unused_variables)]
-            fn deserialize(__val: &serde_lite::Intermediate) -> Result<Self, serde_lite::Error> {
-                #deserialize
+            let __res: Result<Self, serde_lite::Error> = { #deserialize };
+
+            __res.and_then(|__res| {
+                #path(&__res)?;
+                Ok(__res)
+            })
+        }
+    } else {
+        deserialize
+    };
+
+    s.add_bounds(AddBounds::None);
+
+    for predicate in bound::get_where_predicates(s.ast(), Trait::Deserialize) {
+        s.add_where_predicate(predicate);
+    }
+
+    let flattened_keys = expand_flattened_keys(&s.ast().data, &s.ast().attrs).map(|keys| {
+        quote! {
+            #[allow(unused_mut)]
+            fn flattened_keys() -> Option<Vec<&'static str>> {
+                #keys
             }
-        },
-    )
+        }
+    });
+
+    let body = quote! {
+        #[allow(unused_variables)]
+        fn deserialize(__val: &serde_lite::Intermediate) -> Result<Self, serde_lite::Error> {
+            #deserialize
+        }
+
+        #flattened_keys
+    };
+
+    if let Some(path) = attributes::get_crate(&s.ast().attrs) {
+        let path =
+            TokenStream::from_str(&path).expect("invalid path given for the crate attribute");
+
+        s.gen_impl(quote! {
+            use #path as serde_lite;
+
+            gen impl serde_lite::Deserialize for @Self {
+                #body
+            }
+        })
+    } else {
+        s.bound_impl(quote!(serde_lite::Deserialize), body)
+    }
+}
+
+/// Expand body of the `flattened_keys` method (if the type consumes only
+/// known keys when it is flattened).
+fn expand_flattened_keys(data: &Data, attrs: &[Attribute]) -> Option<TokenStream> {
+    if let Some(ty) = attributes::get_from(attrs).or_else(|| attributes::get_try_from(attrs)) {
+        let ty = TokenStream::from_str(&ty).expect("invalid type given for the from attribute");
+
+        return Some(quote! {
+            <#ty as serde_lite::Deserialize>::flattened_keys()
+        });
+    }
+
+    if let Some((_, field)) = attributes::get_transparent_field(attrs, data) {
+        return Some(attributes::get_flattened_keys(field));
+    }
+
+    let data = match data {
+        Data::Struct(data) => {
+            return match &data.fields {
+                Fields::Named(fields) => {
+                    let keys = named_fields_keys(fields);
+
+                    Some(quote!(Some(#keys)))
+                }
+                _ => None,
+            };
+        }
+        Data::Enum(data) => data,
+        Data::Union(_) => return None,
+    };
+
+    // unknown variants can consume any key
+    if attributes::get_repr(attrs).is_some()
+        || data.variants.iter().any(attributes::is_other_variant)
+    {
+        return None;
+    }
+
+    let tag = if let Some(tag) = attributes::get_enum_tag(attrs) {
+        tag
+    } else {
+        let names = data.variants.iter().map(attributes::get_variant_name);
+
+        return Some(quote! {
+            Some(vec![#(#names),*])
+        });
+    };
+
+    if let Some(content) = attributes::get_enum_content(attrs) {
+        return Some(quote! {
+            Some(vec![#tag, #content])
+        });
+    }
+
+    let mut keys = TokenStream::new();
+
+    for variant in &data.variants {
+        if attributes::get_variant_with(variant).is_some() {
+            return None;
+        }
+
+        match &variant.fields {
+            Fields::Named(fields) => {
+                let variant_keys = named_fields_keys(fields);
+
+                keys.extend(quote! {
+                    __keys.extend(#variant_keys);
+                });
+            }
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                let variant_keys = attributes::get_flattened_keys(&fields.unnamed[0]);
+
+                keys.extend(quote! {
+                    __keys.extend(#variant_keys?);
+                });
+            }
+            Fields::Unnamed(_) => return None,
+            Fields::Unit => (),
+        }
+    }
+
+    Some(quote! {
+        let mut __keys = vec![#tag];
+
+        #keys
+
+        Some(__keys)
+    })
+}
+
+/// Generate a block returning keys consumed by given named fields.
+///
+/// The block returns from the enclosing function with `None` if any of the
+/// flattened fields can consume any key.
+fn named_fields_keys(fields: &FieldsNamed) -> TokenStream {
+    let mut names = Vec::new();
+    let mut flattened = TokenStream::new();
+
+    for field in &fields.named {
+        if is_skipped(field) {
+            continue;
+        }
+
+        if attributes::has_flag(&field.attrs, "flatten") {
+            let keys = attributes::get_flattened_keys(field);
+
+            flattened.extend(quote! {
+                __keys.extend(#keys?);
+            });
+        } else {
+            names.push(attributes::get_field_name(field));
+        }
+    }
+
+    quote! {{
+        let mut __keys: Vec<&'static str> = vec![#(#names),*];
+
+        #flattened
+
+        __keys
+    }}
 }
 
 /// Expand Deserialize as delegation.
@@ -41,14 +224,75 @@ fn expand_delegation(delegated_type: &str) -> TokenStream {
     }
 }
 
+/// Expand Deserialize as fallible delegation.
+fn expand_fallible_delegation(delegated_type: &str) -> TokenStream {
+    let ty = TokenStream::from_str(delegated_type)
+        .expect("invalid type given for the try_from attribute");
+
+    quote! {
+        <#ty as serde_lite::Deserialize>::deserialize(__val).and_then(|v| {
+            <Self as std::convert::TryFrom<#ty>>::try_from(v).map_err(serde_lite::Error::custom)
+        })
+    }
+}
+
+/// Expand Deserialize for a transparent struct.
+fn expand_transparent(data: &Data, member: Member, field: &Field) -> TokenStream {
+    let deserializer = field_deserializer(field);
+    let validate = field_validator(field);
+
+    let mut constructor = TokenStream::new();
+
+    if let Data::Struct(data) = data {
+        for (index, other) in data.fields.iter().enumerate() {
+            if std::ptr::eq(other, field) {
+                continue;
+            }
+
+            let member = if let Some(ident) = &other.ident {
+                Member::Named(ident.clone())
+            } else {
+                Member::Unnamed(index.into())
+            };
+
+            constructor.extend(quote! {
+                #member: Default::default(),
+            });
+        }
+    }
+
+    quote! {
+        let __field = #deserializer(__val)#validate?;
+
+        Ok(Self {
+            #member: __field,
+            #constructor
+        })
+    }
+}
+
 /// Expand Deserialize for a given struct.
-fn expand_for_struct(fields: Fields) -> TokenStream {
-    match fields {
-        Fields::Named(fields) => expand_struct_named_fields(fields),
-        Fields::Unnamed(fields) => expand_struct_unnamed_fields(fields),
+fn expand_for_struct(fields: Fields, attrs: &[Attribute]) -> TokenStream {
+    let default = container_default(attrs);
+
+    let deserialize = match fields {
+        Fields::Named(fields) => expand_struct_named_fields(fields, default.is_some()),
+        Fields::Unnamed(fields) => expand_struct_unnamed_fields(fields, default.is_some()),
         Fields::Unit => quote! {
             Ok(Self)
         },
+    };
+
+    // the container default value is created only once and its fields are
+    // moved into the missing fields
+    if let Some(default) = default {
+        quote! {
+            let __default: Self = #default;
+
+            #deserialize
+        }
+    } else {
+        deserialize
     }
 }
 
@@ -58,7 +302,9 @@ fn expand_for_enum(data: DataEnum, attrs: &[Attribute]) -> TokenStream {
         panic!("enum with no variants cannot be deserialized")
     }
 
-    if let Some(tag) = attributes::get_enum_tag(attrs) {
+    if let Some(repr) = attributes::get_repr(attrs) {
+        expand_repr_enum(&repr, data)
+    } else if let Some(tag) = attributes::get_enum_tag(attrs) {
         let content = attributes::get_enum_content(attrs);
 
         expand_internally_tagged_enum(&tag, content.as_deref(), data)
@@ -67,9 +313,53 @@ fn expand_for_enum(data: DataEnum, attrs: &[Attribute]) -> TokenStream {
     }
 }
 
+/// Expand Deserialize for an enum represented by its discriminants.
+fn expand_repr_enum(repr: &str, data: DataEnum) -> TokenStream {
+    let repr = TokenStream::from_str(repr).expect("invalid type given for the repr attribute");
+
+    let discriminants = attributes::get_discriminants(&data);
+
+    let mut deserialize = TokenStream::new();
+
+    let mut other = quote! {
+        Err(serde_lite::Error::UnknownEnumVariant)
+    };
+
+    for (variant, discriminant) in data.variants.iter().zip(discriminants) {
+        let ident = &variant.ident;
+
+        if attributes::is_capturing_variant(variant) {
+            other = quote! {
+                Ok(Self::#ident(__discriminant))
+            };
+        } else {
+            if attributes::is_other_variant(variant) {
+                other = quote! {
+                    Ok(Self::#ident)
+                };
+            }
+
+            deserialize.extend(quote! {
+                if __discriminant == (#discriminant) {
+                    Ok(Self::#ident)
+                } else
+            });
+        }
+    }
+
+    quote! {
+        let __discriminant = <#repr as serde_lite::Deserialize>::deserialize(__val)?;
+
+        #deserialize
+        {
+            #other
+        }
+    }
+}
+
 /// Expand Deserialize for named struct fields.
-fn expand_struct_named_fields(fields: FieldsNamed) -> TokenStream {
-    let (deserialize, constructor) = deserialize_named_fields(&fields);
+fn expand_struct_named_fields(fields: FieldsNamed, default: bool) -> TokenStream {
+    let (deserialize, constructor) = deserialize_named_fields(&fields, None, default);
 
     quote! {
         #deserialize
@@ -81,8 +371,8 @@ fn expand_struct_named_fields(fields: FieldsNamed) -> TokenStream {
 }
 
 /// Expand Deserialize for unnamed struct fields.
-fn expand_struct_unnamed_fields(fields: FieldsUnnamed) -> TokenStream {
-    let (deserialize, constructor) = deserialize_unnamed_fields(&fields);
+fn expand_struct_unnamed_fields(fields: FieldsUnnamed, default: bool) -> TokenStream {
+    let (deserialize, constructor) = deserialize_unnamed_fields(&fields, default);
 
     quote! {
         #deserialize
@@ -100,7 +390,13 @@ fn expand_internally_tagged_enum(
 ) -> TokenStream {
     let mut deserialize = TokenStream::new();
 
+    let other = construct_other_variant(&data);
+
     for variant in data.variants.into_iter() {
+        if attributes::is_capturing_variant(&variant) {
+            continue;
+        }
+
         let sname = attributes::get_variant_name(&variant);
         let lname = Literal::string(&sname);
         let constructor = if content_field.is_some() {
@@ -112,7 +408,7 @@ fn expand_internally_tagged_enum(
         } else {
             // Here the enum content is a part of the currently deserialized
             // object, so we don't need to check anything.
-            construct_enum_variant_with_content(&variant)
+            construct_enum_variant_with_content(&variant, Some(tag_field))
         };
 
         deserialize.extend(quote! {
@@ -150,7 +446,7 @@ fn expand_internally_tagged_enum(
 
         match __variant {
             #deserialize
-            _ => Err(serde_lite::Error::UnknownEnumVariant),
+            _ => #other,
         }
     }
 }
@@ -160,41 +456,43 @@ fn expand_externally_tagged_enum(data: DataEnum) -> TokenStream {
     let mut plain = TokenStream::new();
     let mut with_content = TokenStream::new();
 
-    for (index, variant) in data.variants.into_iter().enumerate() {
+    let other = construct_other_variant(&data);
+
+    for variant in data.variants.into_iter() {
+        if attributes::is_capturing_variant(&variant) {
+            continue;
+        }
+
         let sname = attributes::get_variant_name(&variant);
         let lname = Literal::string(&sname);
-        let constructor_with_content = construct_enum_variant_with_content(&variant);
+        let constructor_with_content = construct_enum_variant_with_content(&variant, None);
         let constructor_without_content = construct_enum_variant_without_content(&variant, None);
 
         plain.extend(quote! {
             #lname => { #constructor_without_content }
         });
 
-        if index == 0 {
-            with_content.extend(quote! {
-                if let Some(__content) = __obj.get(#lname) {
-                    #constructor_with_content
-                }
-            })
-        } else {
-            with_content.extend(quote! {
-                else if let Some(__content) = __obj.get(#lname) {
-                    #constructor_with_content
-                }
-            })
-        }
+        with_content.extend(quote! {
+            if let Some(__content) = __obj.get(#lname) {
+                #constructor_with_content
+            } else
+        });
     }
 
     quote! {
         if let Some(__obj) = __val.as_map() {
             #with_content
-            else {
+            if let Some(__variant) = __obj.keys().next().filter(|_| __obj.len() == 1) {
+                let __variant: &str = __variant;
+
+                #other
+            } else {
                 Err(serde_lite::Error::UnknownEnumVariant)
             }
         } else if let Some(__variant) = __val.as_str() {
             match __variant {
                 #plain
-                _ => Err(serde_lite::Error::UnknownEnumVariant),
+                _ => #other,
             }
         } else {
             Err(serde_lite::Error::invalid_value_static("enum variant"))
@@ -202,9 +500,38 @@ fn expand_externally_tagged_enum(data: DataEnum) -> TokenStream {
     }
 }
 
+/// Generate code for constructing the variant marked with the `other`
+/// attribute from an unknown variant with name `__variant`.
+fn construct_other_variant(data: &DataEnum) -> TokenStream {
+    let variant = data
+        .variants
+        .iter()
+        .find(|variant| attributes::is_other_variant(variant));
+
+    let variant = if let Some(variant) = variant {
+        variant
+    } else {
+        return quote! {
+            Err(serde_lite::Error::UnknownEnumVariant)
+        };
+    };
+
+    let ident = &variant.ident;
+
+    if attributes::is_capturing_variant(variant) {
+        quote! {
+            Ok(Self::#ident(serde_lite::other::UnknownVariant::capture(__variant, __val)))
+        }
+    } else {
+        quote! {
+            Ok(Self::#ident)
+        }
+    }
+}
+
 /// Generate code for constructing a given enum variant.
 fn construct_enum_variant(variant: &Variant, content_field: Option<&str>) -> TokenStream {
-    let with_content = construct_enum_variant_with_content(variant);
+    let with_content = construct_enum_variant_with_content(variant, None);
     let without_content = construct_enum_variant_without_content(variant, content_field);
 
     quote! {
@@ -218,14 +545,61 @@ fn construct_enum_variant(variant: &Variant, content_field: Option<&str>) -> Tok
 
 /// Generate code for constructing a given enum variant and use the available
 /// variant content.
-fn construct_enum_variant_with_content(variant: &Variant) -> TokenStream {
+///
+/// The tag field is given if the content is a part of an internally tagged
+/// enum object.
+fn construct_enum_variant_with_content(variant: &Variant, tag: Option<&str>) -> TokenStream {
+    if let Some(path) = attributes::get_variant_with(variant) {
+        return construct_enum_variant_with(variant, &path);
+    }
+
     match &variant.fields {
-        Fields::Named(fields) => construct_struct_enum_variant(variant, fields),
+        Fields::Named(fields) => construct_struct_enum_variant(variant, fields, tag),
         Fields::Unnamed(fields) => construct_tuple_enum_variant(variant, fields),
         Fields::Unit => construct_unit_enum_variant(variant),
     }
 }
 
+/// Generate code for constructing a given enum variant using a given module.
+///
+/// The deserialize function of the module is expected to return the variant
+/// field if there is only one or a tuple of all variant fields otherwise.
+fn construct_enum_variant_with(variant: &Variant, path: &str) -> TokenStream {
+    let path = TokenStream::from_str(path).expect("invalid path given for the with attribute");
+
+    let ident = &variant.ident;
+
+    let names = match &variant.fields {
+        Fields::Named(fields) if !fields.named.is_empty() => fields
+            .named
+            .iter()
+            .map(|field| field.ident.clone().unwrap())
+            .collect::<Vec<_>>(),
+        Fields::Unnamed(fields) if !fields.unnamed.is_empty() => (0..fields.unnamed.len())
+            .map(|index| Ident::new(&format!("f{}", index), Span::call_site()))
+            .collect::<Vec<_>>(),
+        _ => panic!("the with attribute cannot be used on variants without fields"),
+    };
+
+    let deconstructor = if names.len() == 1 {
+        quote! { #(#names)* }
+    } else {
+        quote! { ( #(#names),* ) }
+    };
+
+    let constructor = if let Fields::Named(_) = &variant.fields {
+        quote! { Self::#ident { #(#names),* } }
+    } else {
+        quote! { Self::#ident( #(#names),* ) }
+    };
+
+    quote! {
+        let #deconstructor = #path::deserialize(__content)?;
+
+        Ok(#constructor)
+    }
+}
+
 /// Generate code for constructing a given enum variant without any content.
 fn construct_enum_variant_without_content(
     variant: &Variant,
@@ -233,7 +607,7 @@ fn construct_enum_variant_without_content(
 ) -> TokenStream {
     match &variant.fields {
         Fields::Named(fields) if fields.named.is_empty() => {
-            return construct_struct_enum_variant(variant, fields);
+            return construct_struct_enum_variant(variant, fields, None);
         }
         Fields::Unnamed(fields) if fields.unnamed.is_empty() => {
             return construct_tuple_enum_variant(variant, fields);
@@ -258,7 +632,11 @@ fn construct_enum_variant_without_content(
 }
 
 /// Generate code for constructing a given struct-like enum variant.
-fn construct_struct_enum_variant(variant: &Variant, fields: &FieldsNamed) -> TokenStream {
+fn construct_struct_enum_variant(
+    variant: &Variant,
+    fields: &FieldsNamed,
+    tag: Option<&str>,
+) -> TokenStream {
     let mut init = TokenStream::new();
 
     if !fields.named.is_empty() {
@@ -267,7 +645,7 @@ fn construct_struct_enum_variant(variant: &Variant, fields: &FieldsNamed) -> Tok
         });
     }
 
-    let (deserialize, constructor) = deserialize_named_fields(fields);
+    let (deserialize, constructor) = deserialize_named_fields(fields, tag, false);
 
     let ident = &variant.ident;
 
@@ -291,7 +669,7 @@ fn construct_tuple_enum_variant(variant: &Variant, fields: &FieldsUnnamed) -> To
         });
     }
 
-    let (deserialize, constructor) = deserialize_unnamed_fields(fields);
+    let (deserialize, constructor) = deserialize_unnamed_fields(fields, false);
 
     let ident = &variant.ident;
 
@@ -313,7 +691,15 @@ fn construct_unit_enum_variant(variant: &Variant) -> TokenStream {
 }
 
 /// Generate code for deserializing given named fields.
-fn deserialize_named_fields(fields: &FieldsNamed) -> (TokenStream, TokenStream) {
+///
+/// Flattened fields get only the keys that are not claimed by the other
+/// fields (or by a given tag field). If the container has a default value,
+/// missing fields are taken from the `__default` variable.
+fn deserialize_named_fields(
+    fields: &FieldsNamed,
+    tag: Option<&str>,
+    container_default: bool,
+) -> (TokenStream, TokenStream) {
     let mut deserialize = TokenStream::new();
     let mut constructor = TokenStream::new();
 
@@ -327,35 +713,62 @@ fn deserialize_named_fields(fields: &FieldsNamed) -> (TokenStream, TokenStream)
         });
     }
 
+    let flattened = fields
+        .named
+        .iter()
+        .filter(|field| !is_skipped(field) && attributes::has_flag(&field.attrs, "flatten"))
+        .collect::<Vec<_>>();
+
+    if !flattened.is_empty() {
+        let claimed = fields
+            .named
+            .iter()
+            .filter(|field| !is_skipped(field) && !attributes::has_flag(&field.attrs, "flatten"))
+            .map(attributes::get_field_name)
+            .chain(tag.map(String::from));
+
+        deserialize.extend(quote! {
+            let __rest = serde_lite::Intermediate::Map(__obj.without_keys(&[#(#claimed),*]));
+        });
+    }
+
+    // flattened fields consuming any key (e.g. maps) do not get keys consumed
+    // by the other flattened fields
+    if flattened.len() > 1 {
+        let keys = flattened
+            .iter()
+            .map(|field| attributes::get_flattened_keys(field));
+
+        deserialize.extend(quote! {
+            let __flattened_keys = [#(#keys),*];
+        });
+    }
+
+    let mut flattened_index = 0;
+
     for field in &fields.named {
         let name = field.ident.as_ref().unwrap();
         let ty = &field.ty;
         let sname = attributes::get_field_name(field);
         let lname = Literal::string(&sname);
-        let deserializer = if let Some(path) = attributes::get_field_deserializer(field) {
-            TokenStream::from_str(&path)
-                .expect("invalid path given for the deserialize_with attribute")
-        } else if let Some(path) = attributes::get_field_with(field) {
-            let path =
-                TokenStream::from_str(&path).expect("invalid path given for the with attribute");
-
-            quote! { #path::deserialize_lite }
-        } else {
-            quote! {
-                <#ty as serde_lite::Deserialize>::deserialize
-            }
-        };
-
-        let skip = attributes::has_flag(&field.attrs, "skip")
-            || attributes::has_flag(&field.attrs, "skip_deserializing");
+        let deserializer = field_deserializer(field);
+        let validate = field_validator(field);
+        let skip = is_skipped(field);
+        let default = field_default(field, container_default.then(|| quote!(__default.#name)));
 
         if skip {
+            let default = skipped_field_default(container_default.then(|| quote!(__default.#name)));
+
             deserialize.extend(quote! {
-                let #name: #ty = Default::default();
+                let #name: #ty = #default;
             });
         } else if attributes::has_flag(&field.attrs, "flatten") {
+            let rest = flattened_rest(flattened.len(), flattened_index);
+
+            flattened_index += 1;
+
             deserialize.extend(quote! {
-                let #name = match #deserializer(__val) {
+                let #name = match #deserializer(&*#rest)#validate {
                     Ok(v) => Some(v),
                     Err(serde_lite::Error::NamedFieldErrors(errors)) => {
                         __field_errors.append(errors);
@@ -364,23 +777,12 @@ fn deserialize_named_fields(fields: &FieldsNamed) -> (TokenStream, TokenStream)
                     Err(err) => return Err(err),
                 };
             });
-        } else if attributes::has_flag(&field.attrs, "default") {
-            deserialize.extend(quote! {
-                let #name = __obj
-                    .get(#lname)
-                    .map(#deserializer)
-                    .unwrap_or_else(|| Ok(Default::default()))
-                    .map_err(|err| __field_errors.push(serde_lite::NamedFieldError::new_static(#lname, err)))
-                    .ok();
-            });
-        } else if let Some(func) = attributes::get_field_default(field) {
-            let func: TokenStream = func.parse().unwrap();
-
+        } else if let Some(default) = default {
             deserialize.extend(quote! {
-                let #name = __obj
-                    .get(#lname)
-                    .map(#deserializer)
-                    .unwrap_or_else(|| Ok(#func()))
+                let #name = match __obj.get(#lname) {
+                    Some(__v) => #deserializer(__v),
+                    None => Ok(#default),
+                }#validate
                     .map_err(|err| __field_errors.push(serde_lite::NamedFieldError::new_static(#lname, err)))
                     .ok();
             });
@@ -389,7 +791,7 @@ fn deserialize_named_fields(fields: &FieldsNamed) -> (TokenStream, TokenStream)
                 let #name = __obj
                     .get(#lname)
                     .map(#deserializer)
-                    .unwrap_or_else(|| Err(serde_lite::Error::MissingField))
+                    .unwrap_or_else(|| Err(serde_lite::Error::MissingField))#validate
                     .map_err(|err| __field_errors.push(serde_lite::NamedFieldError::new_static(#lname, err)))
                     .ok();
             });
@@ -418,11 +820,17 @@ fn deserialize_named_fields(fields: &FieldsNamed) -> (TokenStream, TokenStream)
 }
 
 /// Generate code for deserializing given unnamed fields.
-fn deserialize_unnamed_fields(fields: &FieldsUnnamed) -> (TokenStream, TokenStream) {
+///
+/// If the container has a default value, missing fields are taken from the
+/// `__default` variable.
+fn deserialize_unnamed_fields(
+    fields: &FieldsUnnamed,
+    container_default: bool,
+) -> (TokenStream, TokenStream) {
     match fields.unnamed.len() {
         0 => deserialize_unnamed_fields_0(),
-        1 => deserialize_unnamed_fields_1(fields),
-        _ => deserialize_unnamed_fields_n(fields),
+        1 => deserialize_unnamed_fields_1(fields, container_default),
+        _ => deserialize_unnamed_fields_n(fields, container_default),
     }
 }
 
@@ -437,7 +845,10 @@ fn deserialize_unnamed_fields_0() -> (TokenStream, TokenStream) {
 
 /// Generate code for deserializing given unnamed fields where the actual
 /// number of fields is one (e.g. single-element tuple struct).
-fn deserialize_unnamed_fields_1(fields: &FieldsUnnamed) -> (TokenStream, TokenStream) {
+fn deserialize_unnamed_fields_1(
+    fields: &FieldsUnnamed,
+    container_default: bool,
+) -> (TokenStream, TokenStream) {
     let mut deserialize = TokenStream::new();
     let mut constructor = TokenStream::new();
 
@@ -445,9 +856,20 @@ fn deserialize_unnamed_fields_1(fields: &FieldsUnnamed) -> (TokenStream, TokenSt
     let ty = &field.ty;
     let name = Ident::new("f0", Span::call_site());
 
-    deserialize.extend(quote! {
-        let #name = <#ty as serde_lite::Deserialize>::deserialize(__val)?;
-    });
+    if is_skipped(field) {
+        let default = skipped_field_default(container_default.then(|| quote!(__default.0)));
+
+        deserialize.extend(quote! {
+            let #name: #ty = #default;
+        });
+    } else {
+        let deserializer = field_deserializer(field);
+        let validate = field_validator(field);
+
+        deserialize.extend(quote! {
+            let #name = #deserializer(__val)#validate?;
+        });
+    }
 
     constructor.extend(quote! {
         #name,
@@ -458,11 +880,17 @@ fn deserialize_unnamed_fields_1(fields: &FieldsUnnamed) -> (TokenStream, TokenSt
 
 /// Generate code for deserializing given unnamed fields where the actual
 /// number of fields is greater than one (e.g. multiple-element tuple struct).
-fn deserialize_unnamed_fields_n(fields: &FieldsUnnamed) -> (TokenStream, TokenStream) {
+///
+/// Skipped fields do not consume any array elements and fields with default
+/// values may be missing at the end of the array.
+fn deserialize_unnamed_fields_n(
+    fields: &FieldsUnnamed,
+    container_default: bool,
+) -> (TokenStream, TokenStream) {
     let mut deserialize = TokenStream::new();
     let mut constructor = TokenStream::new();
 
-    let len = Literal::usize_unsuffixed(fields.unnamed.len());
+    let len = Literal::usize_unsuffixed(min_array_len(fields, container_default));
 
     deserialize.extend(quote! {
         let __arr = __val
@@ -476,21 +904,58 @@ fn deserialize_unnamed_fields_n(fields: &FieldsUnnamed) -> (TokenStream, TokenSt
         let mut __field_errors = serde_lite::ErrorList::new();
     });
 
+    let mut position = 0;
+
     for (index, field) in fields.unnamed.iter().enumerate() {
         let ty = &field.ty;
         let sname = format!("f{}", index);
         let name = Ident::new(&sname, Span::call_site());
-        let lindex = Literal::usize_unsuffixed(index);
+
+        let member = Index::from(index);
+        let container = container_default.then(|| quote!(__default.#member));
+
+        if is_skipped(field) {
+            let default = skipped_field_default(container);
+
+            deserialize.extend(quote! {
+                let #name: #ty = #default;
+            });
+
+            constructor.extend(quote! {
+                #name,
+            });
+
+            continue;
+        }
+
+        let lposition = Literal::usize_unsuffixed(position);
+        let deserializer = field_deserializer(field);
+        let validate = field_validator(field);
+
+        let value = if let Some(default) = field_default(field, container) {
+            quote! {
+                match __arr.get(#lposition) {
+                    Some(__v) => #deserializer(__v),
+                    None => Ok(#default),
+                }
+            }
+        } else {
+            quote! {
+                #deserializer(&__arr[#lposition])
+            }
+        };
 
         deserialize.extend(quote! {
-            let #name = <#ty as serde_lite::Deserialize>::deserialize(&__arr[#lindex])
-                .map_err(|err| __field_errors.push(serde_lite::UnnamedFieldError::new(#lindex, err)))
+            let #name = #value #validate
+                .map_err(|err| __field_errors.push(serde_lite::UnnamedFieldError::new(#lposition, err)))
                 .ok();
         });
 
         constructor.extend(quote! {
             unsafe { #name.unwrap_unchecked() },
         });
+
+        position += 1;
     }
 
     deserialize.extend(quote! {
@@ -501,3 +966,126 @@ fn deserialize_unnamed_fields_n(fields: &FieldsUnnamed) -> (TokenStream, TokenSt
 
     (deserialize, constructor)
 }
+
+/// Get the minimum length of an array that can be deserialized into given
+/// unnamed fields.
+fn min_array_len(fields: &FieldsUnnamed, container_default: bool) -> usize {
+    if container_default {
+        return 0;
+    }
+
+    let mut position = 0;
+    let mut len = 0;
+
+    for field in &fields.unnamed {
+        if is_skipped(field) {
+            continue;
+        }
+
+        position += 1;
+
+        if field_default(field, None).is_none() {
+            len = position;
+        }
+    }
+
+    len
+}
+
+/// Get expression constructing the part of the currently de-serialized object
+/// that belongs to a flattened field with a given index.
+fn flattened_rest(count: usize, index: usize) -> TokenStream {
+    if count > 1 {
+        let index = Literal::usize_unsuffixed(index);
+
+        quote!(serde_lite::flatten::field_rest(&__rest, &__flattened_keys, #index))
+    } else {
+        quote!(std::borrow::Cow::Borrowed(&__rest))
+    }
+}
+
+/// Check if a given field is skipped during deserialization.
+fn is_skipped(field: &Field) -> bool {
+    attributes::has_flag(&field.attrs, "skip")
+        || attributes::has_flag(&field.attrs, "skip_deserializing")
+}
+
+/// Get deserializer of a given field.
+fn field_deserializer(field: &Field) -> TokenStream {
+    let ty = &field.ty;
+
+    if let Some(path) = attributes::get_field_deserializer(field) {
+        TokenStream::from_str(&path).expect("invalid path given for the deserialize_with attribute")
+    } else if let Some(path) = attributes::get_field_with(field) {
+        let path = TokenStream::from_str(&path).expect("invalid path given for the with attribute");
+
+        quote! { #path::deserialize }
+    } else if let Some(inner) = attributes::get_flattened_option(field) {
+        quote! {
+            serde_lite::flatten::deserialize::<#inner>
+        }
+    } else {
+        quote! {
+            <#ty as serde_lite::Deserialize>::deserialize
+        }
+    }
+}
+
+/// Get expression constructing the default value of a given field (if the
+/// field has a default value).
+///
+/// The given container default is used for fields without their own default
+/// value. Missing `Patch` fields are always `Patch::Missing`.
+pub fn field_default(field: &Field, container: Option<TokenStream>) -> Option<TokenStream> {
+    if attributes::has_flag(&field.attrs, "default") {
+        Some(quote! { Default::default() })
+    } else if let Some(func) = attributes::get_field_default(field) {
+        let func =
+            TokenStream::from_str(&func).expect("invalid path given for the default attribute");
+
+        Some(quote! { #func() })
+    } else if attributes::is_patch_type(&field.ty) {
+        Some(quote! { serde_lite::Patch::Missing })
+    } else {
+        container
+    }
+}
+
+/// Get expression constructing the value of a skipped field.
+fn skipped_field_default(container: Option<TokenStream>) -> TokenStream {
+    container.unwrap_or_else(|| quote!(Default::default()))
+}
+
+/// Get expression constructing the default value of a container (if the
+/// container has a default value).
+fn container_default(attrs: &[Attribute]) -> Option<TokenStream> {
+    if attributes::has_flag(attrs, "default") {
+        Some(quote! { Default::default() })
+    } else if let Some(func) = attributes::get_default(attrs) {
+        let func =
+            TokenStream::from_str(&func).expect("invalid path given for the default attribute");
+
+        Some(quote! { #func() })
+    } else {
+        None
+    }
+}
+
+/// Generate code validating a deserialized value of a given field.
+///
+/// The code is meant to be chained after an expression evaluating to
+/// `Result<T, serde_lite::Error>`.
+fn field_validator(field: &Field) -> TokenStream {
+    let ty = &field.ty;
+
+    validate::validate_field(field, quote!(&__value))
+        .map(|validate| {
+            quote! {
+                .and_then(|__value: #ty| {
+                    #validate?;
+                    Ok(__value)
+                })
+            }
+        })
+        .unwrap_or_default()
+}
@@ -1,18 +1,55 @@
 mod attributes;
+mod bound;
+mod check;
 mod deserialize;
+mod patchable;
+mod schema;
 mod serialize;
 mod update;
+mod validate;
 
 use proc_macro::TokenStream;
+use quote::quote;
+use syn::Attribute;
 
-#[proc_macro_derive(Serialize, attributes(serde))]
+#[proc_macro_derive(Serialize, attributes(serde, serde_lite))]
 pub fn derive_serialize(input: TokenStream) -> TokenStream {
     serialize::derive_serialize(input)
 }
 
-#[proc_macro_derive(Update, attributes(serde))]
+#[proc_macro_derive(Schema, attributes(serde, serde_lite))]
+pub fn derive_schema(input: TokenStream) -> TokenStream {
+    schema::derive_schema(input)
+}
+
+#[proc_macro_derive(Update, attributes(serde, serde_lite))]
 pub fn derive_update(input: TokenStream) -> TokenStream {
     update::derive_update(input)
 }
 
-synstructure::decl_derive!([Deserialize, attributes(serde)] => deserialize::derive_deserialize);
+#[proc_macro_derive(Patchable, attributes(serde, serde_lite))]
+pub fn derive_patchable(input: TokenStream) -> TokenStream {
+    patchable::derive_patchable(input)
+}
+
+synstructure::decl_derive!([Deserialize, attributes(serde, serde_lite)] => deserialize::derive_deserialize);
+
+/// Make the generated code use the crate path given using the `crate`
+/// attribute (if present).
+fn with_crate_path(attrs: &[Attribute], expanded: proc_macro2::TokenStream) -> TokenStream {
+    if let Some(path) = attributes::get_crate(attrs) {
+        let path: proc_macro2::TokenStream = path
+            .parse()
+            .expect("invalid path given for the crate attribute");
+
+        TokenStream::from(quote! {
+            const _: () = {
+                use #path as serde_lite;
+
+                #expanded
+            };
+        })
+    } else {
+        TokenStream::from(expanded)
+    }
+}
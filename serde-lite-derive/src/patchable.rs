@@ -0,0 +1,438 @@
+use std::str::FromStr;
+
+use proc_macro2::{Literal, TokenStream};
+use quote::{format_ident, quote};
+use syn::{Attribute, Data, DeriveInput, Error, Field, Fields};
+
+use crate::{attributes, bound::Trait, check, deserialize, serialize, update};
+
+/// Expand derive Patchable.
+///
+/// A companion type named `<Type>Patch` is generated. The companion type
+/// contains all fields that can be updated. Every field is wrapped in an
+/// `Option` (or in a `Patch` if the field is already an `Option`) and missing
+/// fields are left out when serialized. The serialized companion type is used
+/// for updating the original type, so applying a patch is consistent with
+/// the derived `Update`.
+///
+/// Fields with custom (de)serializers get the same custom (de)serializers
+/// wrapped in generated functions handling the `Option`. Fields marked as
+/// `patchable` are wrapped companion types of their own types, so that they
+/// can be patched partially. Read-only fields are kept in the companion type,
+/// so that applying a patch containing them fails the same way as the derived
+/// `Update`.
+pub fn derive_patchable(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+
+    if let Err(err) = check::check(&input, Trait::Update) {
+        return err.to_compile_error().into();
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => Some(fields),
+            _ => None,
+        },
+        _ => None,
+    };
+
+    let fields = if let Some(fields) = fields {
+        fields
+            .named
+            .iter()
+            .filter(|field| is_patchable(field))
+            .collect::<Vec<_>>()
+    } else {
+        return Error::new_spanned(
+            &input.ident,
+            "derive Patchable is supported only for structs with named fields",
+        )
+        .to_compile_error()
+        .into();
+    };
+
+    for field in &fields {
+        if has_custom_functions(field) && attributes::has_flag(&field.attrs, "flatten") {
+            return Error::new_spanned(
+                field,
+                "derive Patchable does not support flattened fields with custom (de)serializers",
+            )
+            .to_compile_error()
+            .into();
+        }
+    }
+
+    let patch = patch_type(&input, &fields);
+
+    // the companion type gets the remaining traits derived the same way as
+    // if they were derived by the user
+    let serialize = TokenStream::from(serialize::derive_serialize(patch.clone().into()));
+    let update = TokenStream::from(update::derive_update(patch.clone().into()));
+
+    let mut patch = syn::parse2::<DeriveInput>(patch).expect("invalid patch type");
+
+    let deserialize = deserialize::derive_deserialize(synstructure::Structure::new(&patch));
+
+    // there is no derive macro registering the serde attributes on the
+    // companion type, so they have to be removed from its definition
+    strip_serde_attrs(&mut patch);
+
+    let methods = TokenStream::from(crate::with_crate_path(
+        &input.attrs,
+        expand_methods(&input, &fields),
+    ));
+
+    let expanded = quote! {
+        #patch
+        #serialize
+        #deserialize
+        #update
+        #methods
+    };
+
+    expanded.into()
+}
+
+/// Generate definition of the companion patch type.
+fn patch_type(input: &DeriveInput, fields: &[&Field]) -> TokenStream {
+    let vis = &input.vis;
+    let name = &input.ident;
+    let patch_name = format_ident!("{}Patch", name);
+    let generics = &input.generics;
+    let where_clause = &generics.where_clause;
+
+    let doc = format!("Partial update of [`{}`].", name);
+
+    let krate = attributes::get_crate(&input.attrs);
+
+    // the type definition is not affected by the crate attribute, so the
+    // path has to be used explicitly
+    let patch_type = if let Some(path) = &krate {
+        let path = TokenStream::from_str(path).expect("invalid path given for the crate attribute");
+
+        quote!(#path::Patch)
+    } else {
+        quote!(serde_lite::Patch)
+    };
+
+    let krate = krate.map(|path| {
+        quote! {
+            #[serde_lite(crate = #path)]
+        }
+    });
+
+    let derive = attributes::get_patch_derive(&input.attrs).map(|derive| {
+        let derive = TokenStream::from_str(&derive)
+            .expect("invalid paths given for the patch_derive attribute");
+
+        quote! {
+            #[derive(#derive)]
+        }
+    });
+
+    let fields = fields.iter().map(|field| {
+        let vis = &field.vis;
+        let ident = &field.ident;
+        let ty = &field.ty;
+        let sname = attributes::get_field_name(field);
+        let lname = Literal::string(&sname);
+
+        if has_custom_functions(field) {
+            let name = ident.as_ref().unwrap();
+
+            let serializer = custom_serializer(field).map(|_| {
+                let path = format!("Self::__serialize_{}", name);
+
+                quote!(serialize_with = #path,)
+            });
+
+            let deserializer = format!("Self::__deserialize_{}", name);
+            let updater = format!("Self::__update_{}", name);
+
+            quote! {
+                #[serde(
+                    rename = #lname,
+                    default,
+                    skip_serializing_if = "Option::is_none",
+                    #serializer
+                    deserialize_with = #deserializer,
+                    update_with = #updater
+                )]
+                #vis #ident: Option<#ty>
+            }
+        } else if attributes::has_flag(&field.attrs, "flatten") {
+            quote! {
+                #[serde(flatten)]
+                #vis #ident: Option<#ty>
+            }
+        } else if attributes::has_flag(&field.attrs, "patchable") {
+            let ty = attributes::get_patch_type(ty).expect("invalid patchable field type");
+
+            quote! {
+                #[serde(rename = #lname, default, skip_serializing_if = "Option::is_none")]
+                #vis #ident: Option<#ty>
+            }
+        } else if let Some(inner) = attributes::get_option_type(ty) {
+            quote! {
+                #[serde(rename = #lname)]
+                #vis #ident: #patch_type<#inner>
+            }
+        } else {
+            quote! {
+                #[serde(rename = #lname, default, skip_serializing_if = "Option::is_none")]
+                #vis #ident: Option<#ty>
+            }
+        }
+    });
+
+    quote! {
+        #[doc = #doc]
+        #derive
+        #krate
+        #vis struct #patch_name #generics #where_clause {
+            #(#fields,)*
+        }
+    }
+}
+
+/// Generate the `apply` and `diff` methods of the companion patch type.
+fn expand_methods(input: &DeriveInput, fields: &[&Field]) -> TokenStream {
+    let name = &input.ident;
+    let patch_name = format_ident!("{}Patch", name);
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let types = fields
+        .iter()
+        .filter(|field| {
+            !attributes::has_flag(&field.attrs, "read_only")
+                && !attributes::has_flag(&field.attrs, "patchable")
+        })
+        .map(|field| &field.ty);
+
+    let diff = fields.iter().map(|field| {
+        let ident = &field.ident;
+
+        if attributes::has_flag(&field.attrs, "patchable") {
+            let ty = attributes::get_patch_type(&field.ty).expect("invalid patchable field type");
+
+            return quote! {
+                #ident: {
+                    let __patch = <#ty>::diff(&old.#ident, &new.#ident);
+
+                    if __patch.is_empty() {
+                        None
+                    } else {
+                        Some(__patch)
+                    }
+                }
+            };
+        }
+
+        let (changed, unchanged) = if is_patch_field(field) {
+            (
+                quote!(serde_lite::Patch::from(Some(Clone::clone(&new.#ident)))),
+                quote!(serde_lite::Patch::Missing),
+            )
+        } else {
+            (quote!(Some(Clone::clone(&new.#ident))), quote!(None))
+        };
+
+        // read-only fields cannot be updated, so they are never present
+        if attributes::has_flag(&field.attrs, "read_only") {
+            return quote!(#ident: #unchanged);
+        }
+
+        quote! {
+            #ident: if old.#ident != new.#ident {
+                #changed
+            } else {
+                #unchanged
+            }
+        }
+    });
+
+    let is_empty = fields.iter().map(|field| {
+        let ident = &field.ident;
+
+        if is_patch_field(field) {
+            quote!(self.#ident.is_missing())
+        } else {
+            quote!(self.#ident.is_none())
+        }
+    });
+
+    let is_empty = if fields.is_empty() {
+        quote!(true)
+    } else {
+        quote!(#(#is_empty)&&*)
+    };
+
+    let wrappers = fields
+        .iter()
+        .filter(|field| has_custom_functions(field))
+        .map(|field| expand_wrappers(field));
+
+    quote! {
+        impl #impl_generics #patch_name #ty_generics #where_clause {
+            #(#wrappers)*
+
+            /// Apply the patch to a given value.
+            ///
+            /// The value is updated using its `Update` implementation.
+            pub fn apply(&self, target: &mut #name #ty_generics) -> Result<(), serde_lite::Error>
+            where
+                Self: serde_lite::Serialize,
+                #name #ty_generics: serde_lite::Update,
+            {
+                let __patch = serde_lite::Serialize::serialize(self)?;
+
+                serde_lite::Update::update(target, &__patch)
+            }
+
+            /// Check if the patch does not contain any field.
+            pub fn is_empty(&self) -> bool {
+                #is_empty
+            }
+
+            /// Create a patch changing a given old value into a given new
+            /// value.
+            ///
+            /// Only the fields that differ are present in the patch.
+            pub fn diff(old: &#name #ty_generics, new: &#name #ty_generics) -> Self
+            where
+                #(#types: PartialEq + Clone,)*
+            {
+                Self {
+                    #(#diff,)*
+                }
+            }
+        }
+    }
+}
+
+/// Generate functions wrapping custom (de)serializers of a given field, so
+/// that they can be used for the optional field of the companion type.
+///
+/// The companion type fields are updated by replacing their values.
+fn expand_wrappers(field: &Field) -> TokenStream {
+    let ident = field.ident.as_ref().unwrap();
+    let ty = &field.ty;
+
+    let serialize = custom_serializer(field).map(|path| {
+        let name = format_ident!("__serialize_{}", ident);
+
+        quote! {
+            #[doc(hidden)]
+            fn #name(
+                val: &Option<#ty>,
+            ) -> Result<serde_lite::Intermediate, serde_lite::Error> {
+                match val {
+                    Some(val) => #path(val),
+                    None => Ok(serde_lite::Intermediate::None),
+                }
+            }
+        }
+    });
+
+    // the value is always present if the field is present (even if the
+    // field type is an `Option` and the value is null)
+    let deserializer = custom_deserializer(field)
+        .unwrap_or_else(|| quote!(<#ty as serde_lite::Deserialize>::deserialize));
+
+    let deserialize = format_ident!("__deserialize_{}", ident);
+    let update = format_ident!("__update_{}", ident);
+
+    quote! {
+        #serialize
+
+        #[doc(hidden)]
+        fn #deserialize(
+            val: &serde_lite::Intermediate,
+        ) -> Result<Option<#ty>, serde_lite::Error> {
+            #deserializer(val).map(Some)
+        }
+
+        #[doc(hidden)]
+        fn #update(
+            target: &mut Option<#ty>,
+            val: &serde_lite::Intermediate,
+        ) -> Result<(), serde_lite::Error> {
+            *target = Self::#deserialize(val)?;
+
+            Ok(())
+        }
+    }
+}
+
+/// Check if a given field is represented by a `Patch` in the companion type.
+fn is_patch_field(field: &Field) -> bool {
+    attributes::get_option_type(&field.ty).is_some()
+        && !attributes::has_flag(&field.attrs, "flatten")
+        && !has_custom_functions(field)
+}
+
+/// Check if a given field uses custom (de)serializers or a custom updater.
+fn has_custom_functions(field: &Field) -> bool {
+    attributes::get_field_serializer(field).is_some()
+        || attributes::get_field_deserializer(field).is_some()
+        || attributes::get_field_updater(field).is_some()
+        || attributes::get_field_with(field).is_some()
+}
+
+/// Get path of the custom serializer of a given field (if any).
+fn custom_serializer(field: &Field) -> Option<TokenStream> {
+    if let Some(path) = attributes::get_field_serializer(field) {
+        let path = TokenStream::from_str(&path)
+            .expect("invalid path given for the serialize_with attribute");
+
+        Some(path)
+    } else if let Some(path) = attributes::get_field_with(field) {
+        let path = TokenStream::from_str(&path).expect("invalid path given for the with attribute");
+
+        Some(quote!(#path::serialize))
+    } else {
+        None
+    }
+}
+
+/// Get path of the custom deserializer of a given field (if any).
+fn custom_deserializer(field: &Field) -> Option<TokenStream> {
+    if let Some(path) = attributes::get_field_deserializer(field) {
+        let path = TokenStream::from_str(&path)
+            .expect("invalid path given for the deserialize_with attribute");
+
+        Some(path)
+    } else if let Some(path) = attributes::get_field_with(field) {
+        let path = TokenStream::from_str(&path).expect("invalid path given for the with attribute");
+
+        Some(quote!(#path::deserialize))
+    } else {
+        None
+    }
+}
+
+/// Remove all `#[serde(...)]` and `#[serde_lite(...)]` attributes from a
+/// given type definition.
+fn strip_serde_attrs(input: &mut DeriveInput) {
+    let is_serde =
+        |attr: &Attribute| attr.path().is_ident("serde") || attr.path().is_ident("serde_lite");
+
+    input.attrs.retain(|attr| !is_serde(attr));
+
+    if let Data::Struct(data) = &mut input.data {
+        for field in data.fields.iter_mut() {
+            field.attrs.retain(|attr| !is_serde(attr));
+        }
+    }
+}
+
+/// Check if a given field should be included in the companion patch type.
+///
+/// Read-only fields are included, so that patches containing them are
+/// rejected when applied.
+fn is_patchable(field: &Field) -> bool {
+    !["skip", "skip_deserializing", "skip_updating"]
+        .iter()
+        .any(|flag| attributes::has_flag(&field.attrs, flag))
+}
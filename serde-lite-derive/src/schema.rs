@@ -0,0 +1,468 @@
+use std::str::FromStr;
+
+use proc_macro2::{Literal, TokenStream};
+use quote::quote;
+use syn::{
+    Attribute, Data, DataEnum, DataStruct, DeriveInput, Field, Fields, FieldsNamed, FieldsUnnamed,
+    Generics, Ident, Variant,
+};
+
+use crate::{
+    attributes,
+    bound::{self, Trait},
+    check, validate,
+};
+
+/// Expand derive Schema.
+pub fn derive_schema(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+
+    if let Err(err) = check::check(&input, Trait::Schema) {
+        return err.to_compile_error().into();
+    }
+
+    let generics = bound::with_bound(&input, Trait::Schema);
+
+    let delegated_type = attributes::get_from(&input.attrs)
+        .or_else(|| attributes::get_try_from(&input.attrs))
+        .or_else(|| attributes::get_into(&input.attrs))
+        .or_else(|| attributes::get_try_into(&input.attrs));
+
+    let expanded = if let Some(delegated_type) = delegated_type {
+        expand_delegation(input.ident, generics, &delegated_type)
+    } else if let Some((_, field)) = attributes::get_transparent_field(&input.attrs, &input.data) {
+        expand_transparent(input.ident, generics, field)
+    } else {
+        match input.data {
+            Data::Struct(data) => expand_for_struct(input.ident, generics, data, &input.attrs),
+            Data::Enum(data) => expand_for_enum(input.ident, generics, data, &input.attrs),
+            Data::Union(_) => panic!("derive Schema is not supported for union types"),
+        }
+    };
+
+    crate::with_crate_path(&input.attrs, expanded)
+}
+
+/// Expand Schema for a type that delegates its (de)serialization to another
+/// type.
+fn expand_delegation(name: Ident, generics: Generics, delegated_type: &str) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let ty = TokenStream::from_str(delegated_type).expect("invalid delegated type");
+
+    quote! {
+        impl #impl_generics serde_lite::Schema for #name #ty_generics #where_clause {
+            fn schema() -> serde_lite::Intermediate {
+                <#ty as serde_lite::Schema>::schema()
+            }
+        }
+    }
+}
+
+/// Expand Schema for a transparent struct.
+fn expand_transparent(name: Ident, generics: Generics, field: &Field) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let schema = field_schema(field);
+
+    quote! {
+        impl #impl_generics serde_lite::Schema for #name #ty_generics #where_clause {
+            fn schema() -> serde_lite::Intermediate {
+                #schema
+            }
+        }
+    }
+}
+
+/// Expand Schema for a given struct.
+fn expand_for_struct(
+    name: Ident,
+    generics: Generics,
+    data: DataStruct,
+    attrs: &[Attribute],
+) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // all fields of a struct with a default value are optional
+    let default = attributes::has_default(attrs);
+
+    let schema = match data.fields {
+        Fields::Named(fields) => schema_for_named_fields(&fields, default),
+        Fields::Unnamed(fields) => schema_for_unnamed_fields(&fields, default),
+        Fields::Unit => schema_for_unit(),
+    };
+
+    let lname = Literal::string(&name.to_string());
+
+    quote! {
+        impl #impl_generics serde_lite::Schema for #name #ty_generics #where_clause {
+            fn schema() -> serde_lite::Intermediate {
+                serde_lite::named_schema::<Self, _>(#lname, || {
+                    #schema
+                })
+            }
+        }
+    }
+}
+
+/// Expand Schema for a given enum.
+fn expand_for_enum(
+    name: Ident,
+    generics: Generics,
+    data: DataEnum,
+    attrs: &[Attribute],
+) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    if data.variants.is_empty() {
+        panic!("enum with no variants cannot be described")
+    }
+
+    if let Some(repr) = attributes::get_repr(attrs) {
+        let schema = expand_repr_enum(&repr, &data);
+
+        return quote! {
+            impl #impl_generics serde_lite::Schema for #name #ty_generics #where_clause {
+                fn schema() -> serde_lite::Intermediate {
+                    #schema
+                }
+            }
+        };
+    }
+
+    let mut schema = quote! {
+        let mut __schema = serde_lite::EnumSchema::new();
+    };
+
+    // variants capturing unknown variants cannot be described
+    let variants = data
+        .variants
+        .iter()
+        .filter(|variant| !attributes::is_capturing_variant(variant));
+
+    if let Some(tag) = attributes::get_enum_tag(attrs) {
+        if let Some(content) = attributes::get_enum_content(attrs) {
+            for variant in variants {
+                schema.extend(adjacently_tagged_variant(&tag, &content, variant));
+            }
+        } else {
+            for variant in variants {
+                schema.extend(internally_tagged_variant(&tag, variant));
+            }
+        }
+    } else {
+        for variant in variants {
+            schema.extend(externally_tagged_variant(variant));
+        }
+    }
+
+    let lname = Literal::string(&name.to_string());
+
+    quote! {
+        impl #impl_generics serde_lite::Schema for #name #ty_generics #where_clause {
+            fn schema() -> serde_lite::Intermediate {
+                serde_lite::named_schema::<Self, _>(#lname, || {
+                    #schema
+
+                    __schema.into_schema()
+                })
+            }
+        }
+    }
+}
+
+/// Expand Schema for an enum represented by its discriminants.
+///
+/// The schema is not restricted to the known discriminants if there is a
+/// variant marked with the `other` attribute.
+fn expand_repr_enum(repr: &str, data: &DataEnum) -> TokenStream {
+    let repr = TokenStream::from_str(repr).expect("invalid type given for the repr attribute");
+
+    if data.variants.iter().any(attributes::is_other_variant) {
+        return quote! {
+            <#repr as serde_lite::Schema>::schema()
+        };
+    }
+
+    let discriminants = attributes::get_discriminants(data);
+
+    quote! {
+        let mut __schema = <#repr as serde_lite::Schema>::schema();
+
+        if let serde_lite::Intermediate::Map(__map) = &mut __schema {
+            let __values = vec![
+                #(<serde_lite::Intermediate as From<#repr>>::from(#discriminants)),*
+            ];
+
+            __map.insert_with_static_key("enum", serde_lite::Intermediate::Array(__values));
+        }
+
+        __schema
+    }
+}
+
+/// Generate code for describing a given variant of an externally tagged enum.
+fn externally_tagged_variant(variant: &Variant) -> TokenStream {
+    let sname = attributes::get_variant_name(variant);
+    let lname = Literal::string(&sname);
+
+    if is_unit_variant(variant) {
+        return quote! {
+            __schema.unit_variant(#lname);
+        };
+    }
+
+    let content = schema_for_variant_content(variant);
+
+    quote! {
+        let mut __variant = serde_lite::ObjectSchema::with_capacity(1);
+
+        __variant.property(#lname, { #content }, true);
+        __variant.additional_properties(false);
+
+        __schema.variant(__variant.into_schema());
+    }
+}
+
+/// Generate code for describing a given variant of an internally tagged enum.
+fn internally_tagged_variant(tag: &str, variant: &Variant) -> TokenStream {
+    let ltag = Literal::string(tag);
+    let sname = attributes::get_variant_name(variant);
+    let lname = Literal::string(&sname);
+
+    let mut schema = quote! {
+        let mut __variant = serde_lite::ObjectSchema::new();
+
+        __variant.property(#ltag, serde_lite::EnumSchema::tag(#lname), true);
+    };
+
+    match &variant.fields {
+        // content of variants with custom (de)serialization is unknown
+        _ if attributes::get_variant_with(variant).is_some() => (),
+        Fields::Named(fields) => {
+            schema.extend(describe_named_fields(fields, quote!(__variant), false));
+        }
+        Fields::Unnamed(fields) if !fields.unnamed.is_empty() => {
+            let content = schema_for_unnamed_fields(fields, false);
+
+            schema.extend(quote! {
+                __variant.flatten({ #content });
+            });
+        }
+        _ => (),
+    }
+
+    schema.extend(quote! {
+        __schema.variant(__variant.into_schema());
+    });
+
+    schema
+}
+
+/// Generate code for describing a given variant of an adjacently tagged enum.
+fn adjacently_tagged_variant(tag: &str, content: &str, variant: &Variant) -> TokenStream {
+    let ltag = Literal::string(tag);
+    let lcontent = Literal::string(content);
+    let sname = attributes::get_variant_name(variant);
+    let lname = Literal::string(&sname);
+
+    let (content, required) = if is_unit_variant(variant) {
+        (schema_for_unit(), false)
+    } else {
+        (schema_for_variant_content(variant), true)
+    };
+
+    quote! {
+        let mut __variant = serde_lite::ObjectSchema::with_capacity(2);
+
+        __variant.property(#ltag, serde_lite::EnumSchema::tag(#lname), true);
+        __variant.property(#lcontent, { #content }, #required);
+
+        __schema.variant(__variant.into_schema());
+    }
+}
+
+/// Generate code for describing content of a given enum variant.
+fn schema_for_variant_content(variant: &Variant) -> TokenStream {
+    if attributes::get_variant_with(variant).is_some() {
+        // content of variants with custom (de)serialization is unknown
+        return quote! {
+            serde_lite::Intermediate::Map(serde_lite::Map::new())
+        };
+    }
+
+    match &variant.fields {
+        Fields::Named(fields) => schema_for_named_fields(fields, false),
+        Fields::Unnamed(fields) => schema_for_unnamed_fields(fields, false),
+        Fields::Unit => schema_for_unit(),
+    }
+}
+
+/// Check if a given variant is serialized as its name only.
+fn is_unit_variant(variant: &Variant) -> bool {
+    match &variant.fields {
+        Fields::Named(_) => false,
+        Fields::Unnamed(fields) => fields.unnamed.is_empty(),
+        Fields::Unit => true,
+    }
+}
+
+/// Generate code for describing a unit value.
+fn schema_for_unit() -> TokenStream {
+    quote! {
+        let mut __map = serde_lite::Map::with_capacity(1);
+
+        __map.insert_with_static_key(
+            "type",
+            serde_lite::Intermediate::String(std::borrow::Cow::Borrowed("null")),
+        );
+
+        serde_lite::Intermediate::Map(__map)
+    }
+}
+
+/// Generate code for describing given named fields.
+fn schema_for_named_fields(fields: &FieldsNamed, container_default: bool) -> TokenStream {
+    let len = Literal::usize_unsuffixed(fields.named.len());
+
+    let describe = describe_named_fields(fields, quote!(__object), container_default);
+
+    quote! {
+        let mut __object = serde_lite::ObjectSchema::with_capacity(#len);
+
+        #describe
+
+        __object.into_schema()
+    }
+}
+
+/// Generate code for adding given named fields into a given object schema
+/// builder.
+///
+/// Fields with default values are optional.
+fn describe_named_fields(
+    fields: &FieldsNamed,
+    builder: TokenStream,
+    container_default: bool,
+) -> TokenStream {
+    let mut describe = TokenStream::new();
+
+    for field in &fields.named {
+        if attributes::has_flag(&field.attrs, "skip") {
+            continue;
+        }
+
+        let schema = field_schema(field);
+
+        let optional = attributes::get_flattened_option(field)
+            .filter(|_| attributes::get_field_schema(field).is_none());
+
+        if let Some(inner) = optional {
+            describe.extend(quote! {
+                #builder.flatten_optional(<#inner as serde_lite::Schema>::schema());
+            });
+        } else if attributes::has_flag(&field.attrs, "flatten") {
+            describe.extend(quote! {
+                #builder.flatten(#schema);
+            });
+        } else {
+            let sname = attributes::get_field_name(field);
+            let lname = Literal::string(&sname);
+
+            let required = !(container_default
+                || attributes::has_default(&field.attrs)
+                || attributes::is_patch_type(&field.ty)
+                || attributes::has_flag(&field.attrs, "skip_deserializing"));
+
+            describe.extend(quote! {
+                #builder.property(#lname, #schema, #required);
+            });
+        }
+    }
+
+    describe
+}
+
+/// Generate code for describing given unnamed fields.
+///
+/// Skipped fields are left out and fields with default values are optional.
+fn schema_for_unnamed_fields(fields: &FieldsUnnamed, container_default: bool) -> TokenStream {
+    match fields.unnamed.len() {
+        0 => schema_for_unit(),
+        1 if is_skipped(&fields.unnamed[0]) => schema_for_unit(),
+        1 => field_schema(&fields.unnamed[0]),
+        len => {
+            let len = Literal::usize_unsuffixed(len);
+
+            let mut schema = quote! {
+                let mut __tuple = serde_lite::TupleSchema::with_capacity(#len);
+            };
+
+            for field in &fields.unnamed {
+                if is_skipped(field) {
+                    continue;
+                }
+
+                let element = field_schema(field);
+
+                let optional = container_default
+                    || attributes::has_default(&field.attrs)
+                    || attributes::is_patch_type(&field.ty);
+
+                if optional {
+                    schema.extend(quote! {
+                        __tuple.optional_element(#element);
+                    });
+                } else {
+                    schema.extend(quote! {
+                        __tuple.element(#element);
+                    });
+                }
+            }
+
+            schema.extend(quote! {
+                __tuple.into_schema()
+            });
+
+            schema
+        }
+    }
+}
+
+/// Check if a given tuple field is left out of the schema.
+fn is_skipped(field: &Field) -> bool {
+    attributes::has_flag(&field.attrs, "skip")
+        || attributes::has_flag(&field.attrs, "skip_deserializing")
+}
+
+/// Generate code for getting schema of a given field.
+fn field_schema(field: &Field) -> TokenStream {
+    let ty = &field.ty;
+
+    let schema = if let Some(path) = attributes::get_field_schema(field) {
+        let path =
+            TokenStream::from_str(&path).expect("invalid path given for the schema_with attribute");
+
+        quote! {
+            #path()
+        }
+    } else {
+        quote! {
+            <#ty as serde_lite::Schema>::schema()
+        }
+    };
+
+    let describe_validators = validate::describe_field(field, quote!(__field_schema));
+
+    if describe_validators.is_empty() {
+        schema
+    } else {
+        quote! {
+            {
+                let mut __field_schema = #schema;
+                #describe_validators
+                __field_schema
+            }
+        }
+    }
+}
@@ -3,23 +3,104 @@ use std::str::FromStr;
 use proc_macro2::{Literal, Span, TokenStream};
 use quote::quote;
 use syn::{
-    Attribute, Data, DataEnum, DataStruct, DeriveInput, Fields, FieldsNamed, FieldsUnnamed,
-    Generics, Ident, Variant,
+    Attribute, Data, DataEnum, DataStruct, DeriveInput, Field, Fields, FieldsNamed, FieldsUnnamed,
+    Generics, Ident, Member, Variant,
 };
 
-use crate::attributes;
+use crate::{
+    attributes,
+    bound::{self, Trait},
+    check,
+};
 
 /// Expand derive Serialize.
 pub fn derive_serialize(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = syn::parse_macro_input!(input as DeriveInput);
 
-    let expanded = match input.data {
-        Data::Struct(data) => expand_for_struct(input.ident, input.generics, data, &input.attrs),
-        Data::Enum(data) => expand_for_enum(input.ident, input.generics, data, &input.attrs),
-        Data::Union(_) => panic!("derive Serialize is not supported for union types"),
+    if let Err(err) = check::check(&input, Trait::Serialize) {
+        return err.to_compile_error().into();
+    }
+
+    let generics = bound::with_bound(&input, Trait::Serialize);
+
+    let expanded = if let Some(delegated_type) = attributes::get_into(&input.attrs) {
+        expand_delegation(input.ident, generics, &delegated_type)
+    } else if let Some(delegated_type) = attributes::get_try_into(&input.attrs) {
+        expand_fallible_delegation(input.ident, generics, &delegated_type)
+    } else if let Some((member, field)) =
+        attributes::get_transparent_field(&input.attrs, &input.data)
+    {
+        expand_transparent(input.ident, generics, member, field)
+    } else {
+        match input.data {
+            Data::Struct(data) => expand_for_struct(input.ident, generics, data, &input.attrs),
+            Data::Enum(data) => expand_for_enum(input.ident, generics, data, &input.attrs),
+            Data::Union(_) => panic!("derive Serialize is not supported for union types"),
+        }
     };
 
-    proc_macro::TokenStream::from(expanded)
+    crate::with_crate_path(&input.attrs, expanded)
+}
+
+/// Expand Serialize as delegation.
+fn expand_delegation(name: Ident, generics: Generics, delegated_type: &str) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let ty =
+        TokenStream::from_str(delegated_type).expect("invalid type given for the into attribute");
+
+    quote! {
+        impl #impl_generics serde_lite::Serialize for #name #ty_generics #where_clause {
+            fn serialize(&self) -> Result<serde_lite::Intermediate, serde_lite::Error> {
+                let __val: #ty = <Self as Clone>::clone(self).into();
+
+                serde_lite::Serialize::serialize(&__val)
+            }
+        }
+    }
+}
+
+/// Expand Serialize as fallible delegation.
+fn expand_fallible_delegation(
+    name: Ident,
+    generics: Generics,
+    delegated_type: &str,
+) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let ty = TokenStream::from_str(delegated_type)
+        .expect("invalid type given for the try_into attribute");
+
+    quote! {
+        impl #impl_generics serde_lite::Serialize for #name #ty_generics #where_clause {
+            fn serialize(&self) -> Result<serde_lite::Intermediate, serde_lite::Error> {
+                let __val = <Self as std::convert::TryInto<#ty>>::try_into(<Self as Clone>::clone(self))
+                    .map_err(serde_lite::Error::custom)?;
+
+                serde_lite::Serialize::serialize(&__val)
+            }
+        }
+    }
+}
+
+/// Expand Serialize for a transparent struct.
+fn expand_transparent(
+    name: Ident,
+    generics: Generics,
+    member: Member,
+    field: &Field,
+) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let serializer = field_serializer(field);
+
+    quote! {
+        impl #impl_generics serde_lite::Serialize for #name #ty_generics #where_clause {
+            fn serialize(&self) -> Result<serde_lite::Intermediate, serde_lite::Error> {
+                #serializer(&self.#member)
+            }
+        }
+    }
 }
 
 /// Expand Serialize for a given struct.
@@ -88,10 +169,33 @@ fn expand_for_enum(
         panic!("enum with no variants cannot be serialized")
     }
 
+    if let Some(repr) = attributes::get_repr(attrs) {
+        let serialize = expand_repr_enum(&repr, &data);
+
+        return quote! {
+            impl #impl_generics serde_lite::Serialize for #name #ty_generics #where_clause {
+                fn serialize(&self) -> Result<serde_lite::Intermediate, serde_lite::Error> {
+                    #serialize
+                }
+            }
+        };
+    }
+
+    let tag = attributes::get_enum_tag(attrs);
+    let content = attributes::get_enum_content(attrs);
+
     let mut serialize_variants = TokenStream::new();
 
     for variant in &data.variants {
-        serialize_variants.extend(serialize_enum_variant(variant));
+        if attributes::is_capturing_variant(variant) {
+            serialize_variants.extend(serialize_capturing_variant(
+                variant,
+                tag.as_deref(),
+                content.as_deref(),
+            ));
+        } else {
+            serialize_variants.extend(serialize_enum_variant(variant));
+        }
     }
 
     let mut serialize = quote! {
@@ -100,8 +204,8 @@ fn expand_for_enum(
         };
     };
 
-    if let Some(tag) = attributes::get_enum_tag(attrs) {
-        if let Some(content) = attributes::get_enum_content(attrs) {
+    if let Some(tag) = tag {
+        if let Some(content) = content {
             serialize.extend(expand_adjacently_tagged_enum(&tag, &content));
         } else {
             serialize.extend(expand_internally_tagged_enum(&tag));
@@ -120,6 +224,37 @@ fn expand_for_enum(
     }
 }
 
+/// Expand Serialize for an enum represented by its discriminants.
+fn expand_repr_enum(repr: &str, data: &DataEnum) -> TokenStream {
+    let repr = TokenStream::from_str(repr).expect("invalid type given for the repr attribute");
+
+    let discriminants = attributes::get_discriminants(data);
+
+    let mut serialize = TokenStream::new();
+
+    for (variant, discriminant) in data.variants.iter().zip(discriminants) {
+        let ident = &variant.ident;
+
+        if attributes::is_capturing_variant(variant) {
+            serialize.extend(quote! {
+                Self::#ident(__other) => *__other,
+            });
+        } else {
+            serialize.extend(quote! {
+                Self::#ident => #discriminant,
+            });
+        }
+    }
+
+    quote! {
+        let __discriminant: #repr = match self {
+            #serialize
+        };
+
+        serde_lite::Serialize::serialize(&__discriminant)
+    }
+}
+
 /// Expand Serialize for an internally tagged enum.
 fn expand_internally_tagged_enum(tag: &str) -> TokenStream {
     let ltag = Literal::string(tag);
@@ -134,7 +269,9 @@ fn expand_internally_tagged_enum(tag: &str) -> TokenStream {
             );
 
             Ok(serde_lite::Intermediate::Map(__map))
-        } else if let serde_lite::Intermediate::Map(__map) = __content {
+        } else if let serde_lite::Intermediate::Map(mut __map) = __content {
+            __map.remove_keys(&[#ltag]);
+
             let mut __res = serde_lite::Map::with_capacity(__map.len() + 1);
 
             // NOTE: The tag must be inserted before the content because some
@@ -190,6 +327,10 @@ fn expand_externally_tagged_enum() -> TokenStream {
 
 /// Generate code for serializing a given enum variant.
 fn serialize_enum_variant(variant: &Variant) -> TokenStream {
+    if let Some(path) = attributes::get_variant_with(variant) {
+        return serialize_enum_variant_with(variant, &path);
+    }
+
     match &variant.fields {
         Fields::Named(fields) => serialize_struct_enum_variant(variant, fields),
         Fields::Unnamed(fields) => serialize_tuple_enum_variant(variant, fields),
@@ -197,6 +338,69 @@ fn serialize_enum_variant(variant: &Variant) -> TokenStream {
     }
 }
 
+/// Generate code for serializing a given variant capturing unknown variants.
+///
+/// The captured variant is serialized back as it was.
+fn serialize_capturing_variant(
+    variant: &Variant,
+    tag: Option<&str>,
+    content: Option<&str>,
+) -> TokenStream {
+    let ident = &variant.ident;
+
+    let tag = if let Some(tag) = tag {
+        quote!(Some(#tag))
+    } else {
+        quote!(None)
+    };
+
+    let content = if let Some(content) = content {
+        quote!(Some(#content))
+    } else {
+        quote!(None)
+    };
+
+    quote! {
+        Self::#ident(__other) => {
+            return serde_lite::other::UnknownVariant::restore(__other, #tag, #content);
+        }
+    }
+}
+
+/// Generate code for serializing a given enum variant using a given module.
+///
+/// All variant fields are passed to the serialize function of the module.
+fn serialize_enum_variant_with(variant: &Variant, path: &str) -> TokenStream {
+    let path = TokenStream::from_str(path).expect("invalid path given for the with attribute");
+
+    let ident = &variant.ident;
+    let sname = attributes::get_variant_name(variant);
+    let lname = Literal::string(&sname);
+
+    let (deconstructor, args) = match &variant.fields {
+        Fields::Named(fields) if !fields.named.is_empty() => {
+            let names = fields.named.iter().map(|field| &field.ident);
+            let args = names.clone();
+
+            (quote! { { #(#names),* } }, quote! { #(#args),* })
+        }
+        Fields::Unnamed(fields) if !fields.unnamed.is_empty() => {
+            let names = (0..fields.unnamed.len())
+                .map(|index| Ident::new(&format!("f{}", index), Span::call_site()))
+                .collect::<Vec<_>>();
+
+            (quote! { ( #(#names),* ) }, quote! { #(#names),* })
+        }
+        _ => panic!("the with attribute cannot be used on variants without fields"),
+    };
+
+    quote! {
+        Self::#ident #deconstructor => {
+            (#lname, #path::serialize(#args)?)
+        }
+    }
+}
+
 /// Generate code for serializing a given struct-like enum variant.
 fn serialize_struct_enum_variant(variant: &Variant, fields: &FieldsNamed) -> TokenStream {
     let (deconstructor, serialize) = serialize_named_fields(fields);
@@ -255,21 +459,22 @@ fn serialize_named_fields(fields: &FieldsNamed) -> (TokenStream, TokenStream) {
         let mut __field_errors = serde_lite::ErrorList::new();
     };
 
+    // keys of the named fields take precedence over the flattened ones and
+    // keys of the flattened fields take precedence over the keys of the
+    // following flattened fields
+    let claimed = fields
+        .named
+        .iter()
+        .filter(|field| !is_skipped(field) && !attributes::has_flag(&field.attrs, "flatten"))
+        .map(attributes::get_field_name)
+        .collect::<Vec<_>>();
+
     for field in &fields.named {
-        let name = &field.ident;
-        let ty = &field.ty;
+        let name = field.ident.as_ref().unwrap();
         let sname = attributes::get_field_name(field);
         let lname = Literal::string(&sname);
-        let serializer = attributes::get_field_serializer(field)
-            .map(|path| TokenStream::from_str(&path))
-            .map(|res| res.expect("invalid path given for the serialize_with attribute"))
-            .unwrap_or_else(|| {
-                quote! {
-                    <#ty as serde_lite::Serialize>::serialize
-                }
-            });
-        let skip = attributes::has_flag(&field.attrs, "skip")
-            || attributes::has_flag(&field.attrs, "skip_serializing");
+        let serializer = field_serializer(field);
+        let skip = is_skipped(field);
 
         deconstructor.extend(quote! {
             #name,
@@ -280,7 +485,14 @@ fn serialize_named_fields(fields: &FieldsNamed) -> (TokenStream, TokenStream) {
         } else if attributes::has_flag(&field.attrs, "flatten") {
             quote! {
                 match #serializer(#name) {
-                    Ok(serde_lite::Intermediate::Map(inner)) => __map.extend(inner),
+                    Ok(serde_lite::Intermediate::Map(mut inner)) => {
+                        inner.remove_keys(&[#(#claimed),*]);
+
+                        for (__k, __v) in inner {
+                            __map.entry(__k).or_insert(__v);
+                        }
+                    }
+                    Ok(serde_lite::Intermediate::None) => (),
                     Ok(_) => {
                         __field_errors.push(serde_lite::NamedFieldError::new_static(
                             #lname,
@@ -303,18 +515,7 @@ fn serialize_named_fields(fields: &FieldsNamed) -> (TokenStream, TokenStream) {
             }
         };
 
-        if let Some(path) = attributes::get_skip_field_serializing_if(field) {
-            let path = TokenStream::from_str(&path)
-                .expect("invalid path given for the skip_serializing_if attribute");
-
-            serialize.extend(quote! {
-                if !#path(#name) {
-                    #serialize_field
-                }
-            });
-        } else {
-            serialize.extend(serialize_field);
-        }
+        serialize.extend(skip_serializing_if(field, name, serialize_field));
     }
 
     serialize.extend(quote! {
@@ -330,7 +531,7 @@ fn serialize_named_fields(fields: &FieldsNamed) -> (TokenStream, TokenStream) {
 fn serialize_unnamed_fields(fields: &FieldsUnnamed) -> (TokenStream, TokenStream) {
     match fields.unnamed.len() {
         0 => serialize_unnamed_fields_0(),
-        1 => serialize_unnamed_fields_1(),
+        1 => serialize_unnamed_fields_1(fields),
         _ => serialize_unnamed_fields_n(fields),
     }
 }
@@ -351,25 +552,53 @@ fn serialize_unnamed_fields_0() -> (TokenStream, TokenStream) {
 
 /// Generate code for serializing given unnamed fields where the actual number
 /// of fields is one (e.g. single-element tuple struct).
-fn serialize_unnamed_fields_1() -> (TokenStream, TokenStream) {
+fn serialize_unnamed_fields_1(fields: &FieldsUnnamed) -> (TokenStream, TokenStream) {
     let mut deconstructor = TokenStream::new();
     let mut serialize = TokenStream::new();
 
+    let field = &fields.unnamed[0];
     let name = Ident::new("f0", Span::call_site());
 
     deconstructor.extend(quote! {
         #name
     });
 
-    serialize.extend(quote! {
-        let __val = #name.serialize()?;
-    });
+    if is_skipped(field) {
+        serialize.extend(quote! {
+            let __val = serde_lite::Intermediate::None;
+        });
+    } else {
+        let serializer = field_serializer(field);
+
+        let serialize_field = quote! {
+            #serializer(#name)?
+        };
+
+        if let Some(path) = attributes::get_skip_field_serializing_if(field) {
+            let path = TokenStream::from_str(&path)
+                .expect("invalid path given for the skip_serializing_if attribute");
+
+            serialize.extend(quote! {
+                let __val = if #path(#name) {
+                    serde_lite::Intermediate::None
+                } else {
+                    #serialize_field
+                };
+            });
+        } else {
+            serialize.extend(quote! {
+                let __val = #serialize_field;
+            });
+        }
+    }
 
     (deconstructor, serialize)
 }
 
 /// Generate code for serializing given unnamed fields where the actual number
 /// of fields is greater one (e.g. multiple-element tuple struct).
+///
+/// Skipped fields are left out of the resulting array.
 fn serialize_unnamed_fields_n(fields: &FieldsUnnamed) -> (TokenStream, TokenStream) {
     let mut deconstructor = TokenStream::new();
     let mut serialize = TokenStream::new();
@@ -383,7 +612,7 @@ fn serialize_unnamed_fields_n(fields: &FieldsUnnamed) -> (TokenStream, TokenStre
         });
     }
 
-    for (index, _) in fields.unnamed.iter().enumerate() {
+    for (index, field) in fields.unnamed.iter().enumerate() {
         let sname = format!("f{}", index);
         let name = Ident::new(&sname, Span::call_site());
         let lindex = Literal::usize_unsuffixed(index);
@@ -392,12 +621,20 @@ fn serialize_unnamed_fields_n(fields: &FieldsUnnamed) -> (TokenStream, TokenStre
             #name,
         });
 
-        serialize.extend(quote! {
-            match #name.serialize() {
+        if is_skipped(field) {
+            continue;
+        }
+
+        let serializer = field_serializer(field);
+
+        let serialize_field = quote! {
+            match #serializer(#name) {
                 Ok(v) => __arr.push(v),
                 Err(err) => __field_errors.push(serde_lite::UnnamedFieldError::new(#lindex, err)),
             }
-        });
+        };
+
+        serialize.extend(skip_serializing_if(field, &name, serialize_field));
     }
 
     serialize.extend(quote! {
@@ -410,3 +647,51 @@ fn serialize_unnamed_fields_n(fields: &FieldsUnnamed) -> (TokenStream, TokenStre
 
     (deconstructor, serialize)
 }
+
+/// Check if a given field is skipped during serialization.
+fn is_skipped(field: &Field) -> bool {
+    attributes::has_flag(&field.attrs, "skip")
+        || attributes::has_flag(&field.attrs, "skip_serializing")
+}
+
+/// Get serializer of a given field.
+fn field_serializer(field: &Field) -> TokenStream {
+    let ty = &field.ty;
+
+    if let Some(path) = attributes::get_field_serializer(field) {
+        TokenStream::from_str(&path).expect("invalid path given for the serialize_with attribute")
+    } else if let Some(path) = attributes::get_field_with(field) {
+        let path = TokenStream::from_str(&path).expect("invalid path given for the with attribute");
+
+        quote! { #path::serialize }
+    } else {
+        quote! {
+            <#ty as serde_lite::Serialize>::serialize
+        }
+    }
+}
+
+/// Wrap given field serialization code with the skip_serializing_if
+/// condition (if present).
+///
+/// Missing `Patch` fields are always skipped.
+fn skip_serializing_if(field: &Field, name: &Ident, serialize_field: TokenStream) -> TokenStream {
+    if let Some(path) = attributes::get_skip_field_serializing_if(field) {
+        let path = TokenStream::from_str(&path)
+            .expect("invalid path given for the skip_serializing_if attribute");
+
+        quote! {
+            if !#path(#name) {
+                #serialize_field
+            }
+        }
+    } else if attributes::is_patch_type(&field.ty) {
+        quote! {
+            if !serde_lite::Patch::is_missing(#name) {
+                #serialize_field
+            }
+        }
+    } else {
+        serialize_field
+    }
+}
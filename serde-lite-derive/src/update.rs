@@ -1,25 +1,176 @@
 use std::str::FromStr;
 
 use proc_macro2::{Literal, Span, TokenStream};
-use quote::quote;
+use quote::{format_ident, quote};
 use syn::{
-    Attribute, Data, DataEnum, DataStruct, DeriveInput, Fields, FieldsNamed, FieldsUnnamed,
-    Generics, Ident, Variant,
+    Attribute, Data, DataEnum, DataStruct, DeriveInput, Field, Fields, FieldsNamed, FieldsUnnamed,
+    Generics, Ident, Member, Variant,
 };
 
-use crate::attributes;
+use crate::{
+    attributes,
+    bound::{self, Trait},
+    check, deserialize, validate,
+};
 
 /// Expand derive Update.
 pub fn derive_update(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = syn::parse_macro_input!(input as DeriveInput);
 
-    let expanded = match input.data {
-        Data::Struct(data) => expand_for_struct(input.ident, input.generics, data, &input.attrs),
-        Data::Enum(data) => expand_for_enum(input.ident, input.generics, data, &input.attrs),
-        Data::Union(_) => panic!("derive Update is not supported for union types"),
+    if let Err(err) = check::check(&input, Trait::Update) {
+        return err.to_compile_error().into();
+    }
+
+    let generics = bound::with_bound(&input, Trait::Update);
+
+    let delegated = attributes::get_from(&input.attrs).is_some()
+        || attributes::get_try_from(&input.attrs).is_some();
+
+    let expanded = if delegated {
+        expand_delegation(input.ident, generics, &input.attrs)
+    } else if let Some((member, field)) =
+        attributes::get_transparent_field(&input.attrs, &input.data)
+    {
+        expand_transparent(input.ident, generics, member, field, &input.attrs)
+    } else {
+        match input.data {
+            Data::Struct(data) => expand_for_struct(input.ident, generics, data, &input.attrs),
+            Data::Enum(data) => expand_for_enum(input.ident, generics, data, &input.attrs),
+            Data::Union(_) => panic!("derive Update is not supported for union types"),
+        }
+    };
+
+    crate::with_crate_path(&input.attrs, expanded)
+}
+
+/// Expand Update for a type that delegates its de-serialization to another
+/// type.
+///
+/// If the conversion into the other type is also available (i.e. the `into`
+/// or `try_into` attribute is present and it refers to the same type as the
+/// `from` or `try_from` attribute), the value gets converted, updated and
+/// converted back. Otherwise, the value is replaced with a de-serialized one.
+fn expand_delegation(name: Ident, generics: Generics, attrs: &[Attribute]) -> TokenStream {
+    let from_ty = attributes::get_from(attrs).or_else(|| attributes::get_try_from(attrs));
+    let into_ty = attributes::get_into(attrs).or_else(|| attributes::get_try_into(attrs));
+
+    // the value cannot be updated in the other type if it is converted into
+    // a different type than the one it is converted from
+    let same_type = match (from_ty, into_ty) {
+        (Some(from), Some(into)) => {
+            let from = TokenStream::from_str(&from).map(|ty| ty.to_string()).ok();
+            let into = TokenStream::from_str(&into).map(|ty| ty.to_string()).ok();
+
+            from.is_some() && from == into
+        }
+        _ => false,
+    };
+
+    let into = if !same_type {
+        None
+    } else if let Some(ty) = attributes::get_into(attrs) {
+        let ty = TokenStream::from_str(&ty).expect("invalid type given for the into attribute");
+
+        Some(quote! {
+            let mut __tmp: #ty = <Self as Clone>::clone(self).into();
+        })
+    } else if let Some(ty) = attributes::get_try_into(attrs) {
+        let ty = TokenStream::from_str(&ty).expect("invalid type given for the try_into attribute");
+
+        Some(quote! {
+            let mut __tmp = <Self as std::convert::TryInto<#ty>>::try_into(<Self as Clone>::clone(self))
+                .map_err(serde_lite::Error::custom)?;
+        })
+    } else {
+        None
+    };
+
+    let from = if let Some(ty) = attributes::get_from(attrs) {
+        let ty = TokenStream::from_str(&ty).expect("invalid type given for the from attribute");
+
+        quote! {
+            <Self as From<#ty>>::from(__tmp)
+        }
+    } else if let Some(ty) = attributes::get_try_from(attrs) {
+        let ty = TokenStream::from_str(&ty).expect("invalid type given for the try_from attribute");
+
+        quote! {
+            <Self as std::convert::TryFrom<#ty>>::try_from(__tmp)
+                .map_err(serde_lite::Error::custom)?
+        }
+    } else {
+        unreachable!()
+    };
+
+    let update = if let Some(into) = into {
+        quote! {
+            #into
+
+            serde_lite::Update::update_tracked(&mut __tmp, __val, __changes)?;
+
+            *self = #from;
+
+            Ok(())
+        }
+    } else {
+        quote! {
+            *self = <Self as serde_lite::Deserialize>::deserialize(__val)?;
+
+            __changes.written(__val);
+
+            Ok(())
+        }
     };
 
-    proc_macro::TokenStream::from(expanded)
+    let update = validate_container(update, attrs);
+
+    impl_update(&name, &generics, update, TokenStream::new(), attrs)
+}
+
+/// Expand Update for a transparent struct.
+fn expand_transparent(
+    name: Ident,
+    generics: Generics,
+    member: Member,
+    field: &Field,
+    attrs: &[Attribute],
+) -> TokenStream {
+    let field_name = Ident::new("__field", Span::call_site());
+
+    let update_field = keep_old_value(
+        field,
+        &field_name,
+        update_field(field, &field_name, quote!(__val)),
+    );
+
+    let old_value = declare_old_value(field, &field_name);
+    let on_update = call_on_update(field, &field_name);
+
+    let (backup, validate) = container_validation(attrs);
+
+    let update = quote! {
+        #backup
+
+        let #field_name = &mut self.#member;
+
+        #old_value
+
+        let __res = #update_field;
+
+        __res?;
+
+        #validate
+
+        #on_update
+
+        Ok(())
+    };
+
+    let check = check_field_permissions(field, quote!(__val))
+        .map(|check| quote!(#check?;))
+        .unwrap_or_default();
+
+    impl_update(&name, &generics, update, check, attrs)
 }
 
 /// Expand Update for a given struct.
@@ -27,33 +178,42 @@ fn expand_for_struct(
     name: Ident,
     generics: Generics,
     data: DataStruct,
-    _: &[Attribute],
+    attrs: &[Attribute],
 ) -> TokenStream {
-    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let check = match &data.fields {
+        Fields::Named(fields) => check_named_fields(fields, None),
+        Fields::Unnamed(fields) => check_unnamed_fields(fields),
+        Fields::Unit => TokenStream::new(),
+    };
+
+    // the container is validated before calling the field hooks, so that
+    // the hooks are not called if the updated value is rejected
+    let (backup, validate) = container_validation(attrs);
 
     let update = match data.fields {
-        Fields::Named(fields) => expand_named_fields(fields),
-        Fields::Unnamed(fields) => expand_unnamed_fields(fields),
+        Fields::Named(fields) => expand_named_fields(fields, &validate),
+        Fields::Unnamed(fields) => {
+            expand_unnamed_fields(fields, attributes::has_default(attrs), &validate)
+        }
         Fields::Unit => quote! {
+            #validate
             Ok(())
         },
     };
 
-    let expanded = quote! {
-        #[allow(unused_variables)]
-        impl #impl_generics serde_lite::Update for #name #ty_generics #where_clause {
-            fn update(&mut self, __val: &serde_lite::Intermediate) -> Result<(), serde_lite::Error> {
-                #update
-            }
-        }
+    let update = quote! {
+        #backup
+        #update
     };
 
-    expanded
+    impl_update(&name, &generics, update, check, attrs)
 }
 
 /// Expand Update for given named struct fields.
-fn expand_named_fields(fields: FieldsNamed) -> TokenStream {
-    let (deconstructor, update) = update_named_fields(&fields);
+///
+/// The container validation code is inserted before the field hooks.
+fn expand_named_fields(fields: FieldsNamed, validate: &TokenStream) -> TokenStream {
+    let (deconstructor, update) = update_named_fields(&fields, None, validate);
 
     let mut init = TokenStream::new();
 
@@ -71,8 +231,14 @@ fn expand_named_fields(fields: FieldsNamed) -> TokenStream {
 }
 
 /// Expand Update for given unnamed struct fields.
-fn expand_unnamed_fields(fields: FieldsUnnamed) -> TokenStream {
-    let (deconstructor, update) = update_unnamed_fields(&fields);
+///
+/// The container validation code is inserted before the field hooks.
+fn expand_unnamed_fields(
+    fields: FieldsUnnamed,
+    default: bool,
+    validate: &TokenStream,
+) -> TokenStream {
+    let (deconstructor, update) = update_unnamed_fields(&fields, default, validate);
 
     let mut init = TokenStream::new();
 
@@ -96,9 +262,16 @@ fn expand_for_enum(
     data: DataEnum,
     attrs: &[Attribute],
 ) -> TokenStream {
-    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let check = check_enum_permissions(&data, attrs);
 
-    let update = if let Some(tag) = attributes::get_enum_tag(attrs) {
+    let update = if attributes::get_repr(attrs).is_some() {
+        // enums represented by their discriminants have no content
+        quote! {
+            *self = Self::deserialize(__val)?;
+
+            __changes.written(__val);
+        }
+    } else if let Some(tag) = attributes::get_enum_tag(attrs) {
         let content = attributes::get_enum_content(attrs);
 
         expand_internally_tagged_enum(&tag, content.as_deref(), data)
@@ -106,14 +279,145 @@ fn expand_for_enum(
         expand_externally_tagged_enum(data)
     };
 
+    let update = validate_container(
+        quote! {
+            #update
+            Ok(())
+        },
+        attrs,
+    );
+
+    impl_update(&name, &generics, update, check, attrs)
+}
+
+/// Generate the Update implementation for a given type.
+///
+/// The generated `update_tracked` method contains a given update code and the
+/// `update` method calls it with a change set that does not record anything.
+/// The `check_permissions` method is generated only if there is a given check
+/// code.
+fn impl_update(
+    name: &Ident,
+    generics: &Generics,
+    update: TokenStream,
+    check: TokenStream,
+    attrs: &[Attribute],
+) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let update = call_after_update(update, attrs);
+
+    let check = if check.is_empty() {
+        check
+    } else {
+        quote! {
+            fn check_permissions(
+                __val: &serde_lite::Intermediate,
+                __changes: &serde_lite::ChangeSet<'_>,
+            ) -> Result<(), serde_lite::Error> {
+                #check
+                Ok(())
+            }
+        }
+    };
+
     quote! {
         #[allow(unused_variables)]
         impl #impl_generics serde_lite::Update for #name #ty_generics #where_clause {
             fn update(&mut self, __val: &serde_lite::Intermediate) -> Result<(), serde_lite::Error> {
+                serde_lite::Update::update_tracked(self, __val, &mut serde_lite::ChangeSet::disabled())
+            }
+
+            fn update_tracked(
+                &mut self,
+                __val: &serde_lite::Intermediate,
+                __changes: &mut serde_lite::ChangeSet<'_>,
+            ) -> Result<(), serde_lite::Error> {
                 #update
-                Ok(())
             }
+
+            #check
+        }
+    }
+}
+
+/// Wrap a given update code with a check of the container `validate_with`
+/// attribute (if present).
+///
+/// The container is restored to its original state if the updated value is
+/// not valid.
+fn validate_container(update: TokenStream, attrs: &[Attribute]) -> TokenStream {
+    let (backup, validate) = container_validation(attrs);
+
+    if validate.is_empty() {
+        return update;
+    }
+
+    quote! {
+        #backup
+
+        let __res: Result<(), serde_lite::Error> = { #update };
+
+        __res?;
+
+        #validate
+
+        Ok(())
+    }
+}
+
+/// Generate code making a backup of the container and code checking the
+/// container `validate_with` attribute (if present).
+///
+/// The validation code restores the container from the backup and returns
+/// the error if the updated value is not valid. Both are empty if there is
+/// no `validate_with` attribute.
+fn container_validation(attrs: &[Attribute]) -> (TokenStream, TokenStream) {
+    if let Some(path) = attributes::get_validate_with(attrs) {
+        let path = TokenStream::from_str(&path)
+            .expect("invalid path given for the validate_with attribute");
+
+        let backup = quote! {
+            let __backup = <Self as Clone>::clone(self);
+        };
+
+        let validate = quote! {
+            if let Err(err) = #path(self) {
+                *self = __backup;
+
+                return Err(err);
+            }
+        };
+
+        (backup, validate)
+    } else {
+        (TokenStream::new(), TokenStream::new())
+    }
+}
+
+/// Wrap a given update code with a call of the container `after_update` hook
+/// (if present).
+///
+/// The hook is called with the original value of the container after a
+/// successful update.
+fn call_after_update(update: TokenStream, attrs: &[Attribute]) -> TokenStream {
+    if let Some(path) = attributes::get_after_update(attrs) {
+        let path = TokenStream::from_str(&path)
+            .expect("invalid path given for the after_update attribute");
+
+        quote! {
+            let __old = <Self as Clone>::clone(self);
+
+            let __res: Result<(), serde_lite::Error> = { #update };
+
+            __res?;
+
+            #path(self, &__old);
+
+            Ok(())
         }
+    } else {
+        update
     }
 }
 
@@ -127,6 +431,10 @@ fn expand_internally_tagged_enum(
     let mut update = TokenStream::new();
 
     for variant in &data.variants {
+        if attributes::is_capturing_variant(variant) {
+            continue;
+        }
+
         let sname = attributes::get_variant_name(variant);
         let lname = Literal::string(&sname);
         let update_varaint = if content_field.is_some() {
@@ -138,7 +446,7 @@ fn expand_internally_tagged_enum(
         } else {
             // Here the enum content is a part of the currently deserialized
             // object, so we don't need to check anything.
-            update_enum_variant_with_content(variant)
+            update_enum_variant_with_content(variant, Some(tag_field), None)
         };
 
         update.extend(quote! {
@@ -160,6 +468,8 @@ fn expand_internally_tagged_enum(
 
     let current_variant = get_current_enum_variant(&data);
 
+    let other = update_other_variant(&data);
+
     let ltag = Literal::string(tag_field);
 
     quote! {
@@ -179,7 +489,7 @@ fn expand_internally_tagged_enum(
 
         match __variant {
             #update
-            _ => return Err(serde_lite::Error::UnknownEnumVariant),
+            _ => { #other }
         }
     }
 }
@@ -189,41 +499,40 @@ fn expand_externally_tagged_enum(data: DataEnum) -> TokenStream {
     let mut plain = TokenStream::new();
     let mut with_content = TokenStream::new();
 
-    for (index, variant) in data.variants.into_iter().enumerate() {
+    let other = update_other_variant(&data);
+
+    for variant in data.variants.into_iter() {
+        if attributes::is_capturing_variant(&variant) {
+            continue;
+        }
+
         let sname = attributes::get_variant_name(&variant);
         let lname = Literal::string(&sname);
-        let update_variant_with_content = update_enum_variant_with_content(&variant);
+        let update_variant_with_content =
+            update_enum_variant_with_content(&variant, None, Some(&sname));
         let update_variant_without_content = update_enum_variant_without_content(&variant, None);
 
         plain.extend(quote! {
             #lname => { #update_variant_without_content }
         });
 
-        if index == 0 {
-            with_content.extend(quote! {
-                if let Some(__content) = __obj.get(#lname) {
-                    #update_variant_with_content
-                }
-            })
-        } else {
-            with_content.extend(quote! {
-                else if let Some(__content) = __obj.get(#lname) {
-                    #update_variant_with_content
-                }
-            })
-        }
+        with_content.extend(quote! {
+            if let Some(__content) = __obj.get(#lname) {
+                #update_variant_with_content
+            } else
+        });
     }
 
     quote! {
         if let Some(__obj) = __val.as_map() {
             #with_content
-            else {
-                return Err(serde_lite::Error::UnknownEnumVariant);
+            {
+                #other
             }
         } else if let Some(__variant) = __val.as_str() {
             match __variant {
                 #plain
-                _ => return Err(serde_lite::Error::UnknownEnumVariant),
+                _ => { #other }
             }
         } else {
             return Err(serde_lite::Error::invalid_value_static("enum variant"));
@@ -231,6 +540,20 @@ fn expand_externally_tagged_enum(data: DataEnum) -> TokenStream {
     }
 }
 
+/// Generate code for updating the enum from an unknown variant.
+///
+/// The enum value is replaced if there is a variant marked with the `other`
+/// attribute.
+fn update_other_variant(data: &DataEnum) -> TokenStream {
+    if data.variants.iter().any(attributes::is_other_variant) {
+        replace_self()
+    } else {
+        quote! {
+            return Err(serde_lite::Error::UnknownEnumVariant);
+        }
+    }
+}
+
 /// Generate code to get the current enum variant name.
 fn get_current_enum_variant(data: &DataEnum) -> TokenStream {
     let mut match_arms = TokenStream::new();
@@ -262,7 +585,7 @@ fn get_current_enum_variant(data: &DataEnum) -> TokenStream {
 
 /// Generate code for updating a given enum variant.
 fn update_enum_variant(variant: &Variant, content_field: Option<&str>) -> TokenStream {
-    let with_content = update_enum_variant_with_content(variant);
+    let with_content = update_enum_variant_with_content(variant, None, content_field);
     let without_content = update_enum_variant_without_content(variant, content_field);
 
     quote! {
@@ -276,10 +599,23 @@ fn update_enum_variant(variant: &Variant, content_field: Option<&str>) -> TokenS
 
 /// Generate code for updating a given enum variant and use the available
 /// variant content.
-fn update_enum_variant_with_content(variant: &Variant) -> TokenStream {
+///
+/// The segment is a name of the field containing the variant content (if the
+/// content is not a part of the currently updated object). It is used for
+/// reporting paths of the changed values.
+fn update_enum_variant_with_content(
+    variant: &Variant,
+    tag: Option<&str>,
+    segment: Option<&str>,
+) -> TokenStream {
+    if attributes::get_variant_with(variant).is_some() {
+        // variants with custom (de)serialization are always replaced
+        return replace_self();
+    }
+
     match &variant.fields {
-        Fields::Named(fields) => update_struct_enum_variant(variant, fields),
-        Fields::Unnamed(fields) => update_tuple_enum_variant(variant, fields),
+        Fields::Named(fields) => update_struct_enum_variant(variant, fields, tag, segment),
+        Fields::Unnamed(fields) => update_tuple_enum_variant(variant, fields, segment),
         Fields::Unit => update_unit_enum_variant(variant),
     }
 }
@@ -291,10 +627,10 @@ fn update_enum_variant_without_content(
 ) -> TokenStream {
     match &variant.fields {
         Fields::Named(fields) if fields.named.is_empty() => {
-            return update_struct_enum_variant(variant, fields);
+            return update_struct_enum_variant(variant, fields, None, None);
         }
         Fields::Unnamed(fields) if fields.unnamed.is_empty() => {
-            return update_tuple_enum_variant(variant, fields);
+            return update_tuple_enum_variant(variant, fields, None);
         }
         Fields::Unit => return update_unit_enum_variant(variant),
         _ => (),
@@ -316,7 +652,12 @@ fn update_enum_variant_without_content(
 }
 
 /// Generate code for updating a given struct-like enum variant.
-fn update_struct_enum_variant(variant: &Variant, fields: &FieldsNamed) -> TokenStream {
+fn update_struct_enum_variant(
+    variant: &Variant,
+    fields: &FieldsNamed,
+    tag: Option<&str>,
+    segment: Option<&str>,
+) -> TokenStream {
     let mut init = TokenStream::new();
 
     if !fields.named.is_empty() {
@@ -325,22 +666,33 @@ fn update_struct_enum_variant(variant: &Variant, fields: &FieldsNamed) -> TokenS
         });
     }
 
-    let (deconstructor, update) = update_named_fields(fields);
+    let (deconstructor, update) = update_named_fields(fields, tag, &TokenStream::new());
+
+    let update = if fields.named.is_empty() {
+        update
+    } else {
+        enter_segment(update, segment)
+    };
 
     let ident = &variant.ident;
+    let replace = replace_self();
 
     quote! {
         if let Self::#ident { #deconstructor } = self {
             #init
             #update
         } else {
-            *self = Self::deserialize(__val)?;
+            #replace
         }
     }
 }
 
 /// Generate code for updating a given tuple-like enum variant.
-fn update_tuple_enum_variant(variant: &Variant, fields: &FieldsUnnamed) -> TokenStream {
+fn update_tuple_enum_variant(
+    variant: &Variant,
+    fields: &FieldsUnnamed,
+    segment: Option<&str>,
+) -> TokenStream {
     let mut init = TokenStream::new();
 
     if !fields.unnamed.is_empty() {
@@ -349,17 +701,64 @@ fn update_tuple_enum_variant(variant: &Variant, fields: &FieldsUnnamed) -> Token
         });
     }
 
-    let (deconstructor, update) = update_unnamed_fields(fields);
+    let (deconstructor, update) = update_unnamed_fields(fields, false, &TokenStream::new());
+
+    let update = if fields.unnamed.is_empty() {
+        update
+    } else {
+        enter_segment(update, segment)
+    };
 
     let ident = &variant.ident;
+    let replace = replace_self();
 
     quote! {
         if let Self::#ident(#deconstructor) = self {
             #init
             #update
         } else {
-            *self = Self::deserialize(__val)?;
+            #replace
+        }
+    }
+}
+
+/// Generate code replacing the currently updated value with a value
+/// de-serialized from the update value.
+///
+/// The `update_if` predicates of all fields of the new value are checked
+/// first.
+fn replace_self() -> TokenStream {
+    quote! {
+        <Self as serde_lite::Update>::check_permissions(__val, __changes)?;
+
+        *self = <Self as serde_lite::Deserialize>::deserialize(__val)?;
+
+        __changes.written(__val);
+    }
+}
+
+/// Wrap a given update code so that the changes it makes are reported under
+/// a given path segment (if any).
+fn enter_segment(update: TokenStream, segment: Option<&str>) -> TokenStream {
+    if let Some(segment) = segment {
+        let lsegment = Literal::string(segment);
+
+        // the closure makes sure that the segment is left even if the update
+        // code returns early
+        quote! {
+            __changes.enter_field(#lsegment);
+
+            let __res = (|| -> Result<(), serde_lite::Error> {
+                #update
+                Ok(())
+            })();
+
+            __changes.leave();
+
+            __res?;
         }
+    } else {
+        update
     }
 }
 
@@ -368,14 +767,26 @@ fn update_unit_enum_variant(variant: &Variant) -> TokenStream {
     let ident = &variant.ident;
 
     quote! {
-        *self = Self::#ident;
+        if !matches!(self, Self::#ident) {
+            *self = Self::#ident;
+
+            __changes.written(__val);
+        }
     }
 }
 
 /// Generate code for updating given named field.
-fn update_named_fields(fields: &FieldsNamed) -> (TokenStream, TokenStream) {
+///
+/// A given container validation code is inserted before the field hooks and
+/// it is skipped if any of the fields fails.
+fn update_named_fields(
+    fields: &FieldsNamed,
+    tag: Option<&str>,
+    validate: &TokenStream,
+) -> (TokenStream, TokenStream) {
     let mut deconstructor = TokenStream::new();
     let mut update = TokenStream::new();
+    let mut on_update = TokenStream::new();
 
     if !fields.named.is_empty() {
         update.extend(quote! {
@@ -384,36 +795,53 @@ fn update_named_fields(fields: &FieldsNamed) -> (TokenStream, TokenStream) {
                 .ok_or_else(|| serde_lite::Error::invalid_value_static("object"))?;
 
             let mut __field_errors = serde_lite::ErrorList::new();
+
+            let __removed = __changes.removed_keys(__obj).unwrap_or_default();
         });
     }
 
+    let flattened = get_flattened_fields(fields);
+
+    update.extend(split_flattened_rest(fields, tag));
+
+    let mut flattened_index = 0;
+
     for field in &fields.named {
         let name = field.ident.as_ref().unwrap();
-        let ty = &field.ty;
         let sname = attributes::get_field_name(field);
         let lname = Literal::string(&sname);
-        let updater = attributes::get_field_updater(field)
-            .map(|path| TokenStream::from_str(&path))
-            .map(|res| res.expect("invalid path given for the update_with attribute"))
-            .unwrap_or_else(|| {
-                quote! {
-                    <#ty as serde_lite::Update>::update
-                }
-            });
 
         deconstructor.extend(quote! {
             #name,
         });
 
-        if attributes::has_flag(&field.attrs, "skip")
-            || attributes::has_flag(&field.attrs, "skip_deserializing")
-        {
+        let rest = if is_skipped(field) || !attributes::has_flag(&field.attrs, "flatten") {
+            None
+        } else {
+            flattened_index += 1;
+
+            Some(flattened_rest(flattened.len(), flattened_index - 1))
+        };
+
+        if is_skipped(field) || attributes::has_flag(&field.attrs, "skip_updating") {
             continue;
         }
 
-        if attributes::has_flag(&field.attrs, "flatten") {
+        update.extend(declare_old_value(field, name));
+
+        on_update.extend(call_on_update(field, name));
+
+        if let Some(rest) = rest {
+            let update_field = keep_old_value(
+                field,
+                name,
+                update_field(field, name, quote!(&*__field_rest)),
+            );
+
             update.extend(quote! {
-                if let Err(err) = #updater(#name, __val) {
+                let __field_rest = #rest;
+
+                if let Err(err) = #update_field {
                     if let serde_lite::Error::NamedFieldErrors(errors) = err {
                         __field_errors.append(errors);
                     } else {
@@ -422,9 +850,28 @@ fn update_named_fields(fields: &FieldsNamed) -> (TokenStream, TokenStream) {
                 }
             });
         } else {
+            let update_field = keep_old_value(field, name, update_field(field, name, quote!(__v)));
+            let restore_field = keep_old_value(field, name, restore_missing_field(field, name));
+
             update.extend(quote! {
                 if let Some(__v) = __obj.get(#lname) {
-                    if let Err(err) = #updater(#name, __v) {
+                    __changes.enter_field(#lname);
+
+                    let __res = #update_field;
+
+                    __changes.leave();
+
+                    if let Err(err) = __res {
+                        __field_errors.push(serde_lite::NamedFieldError::new_static(#lname, err));
+                    }
+                } else if __removed.iter().any(|__k| __k.as_str() == Some(#lname)) {
+                    __changes.enter_field(#lname);
+
+                    let __res = #restore_field;
+
+                    __changes.leave();
+
+                    if let Err(err) = __res {
                         __field_errors.push(serde_lite::NamedFieldError::new_static(#lname, err));
                     }
                 }
@@ -432,6 +879,20 @@ fn update_named_fields(fields: &FieldsNamed) -> (TokenStream, TokenStream) {
         }
     }
 
+    if fields.named.is_empty() {
+        update.extend(validate.clone());
+    } else if !validate.is_empty() {
+        update.extend(quote! {
+            if __field_errors.is_empty() {
+                #validate
+            }
+        });
+    }
+
+    // the hooks are called even if some other fields failed because the
+    // updated fields have been changed anyway
+    update.extend(on_update);
+
     if !fields.named.is_empty() {
         update.extend(quote! {
             if !__field_errors.is_empty() {
@@ -443,50 +904,132 @@ fn update_named_fields(fields: &FieldsNamed) -> (TokenStream, TokenStream) {
     (deconstructor, update)
 }
 
+/// Get flattened fields from given named fields.
+fn get_flattened_fields(fields: &FieldsNamed) -> Vec<&Field> {
+    fields
+        .named
+        .iter()
+        .filter(|field| !is_skipped(field) && attributes::has_flag(&field.attrs, "flatten"))
+        .collect()
+}
+
+/// Generate code extracting keys not claimed by any non-flattened field (or by
+/// the tag of an internally tagged enum) from the currently updated object.
+///
+/// Nothing is generated if there are no flattened fields.
+fn split_flattened_rest(fields: &FieldsNamed, tag: Option<&str>) -> TokenStream {
+    let flattened = get_flattened_fields(fields);
+
+    if flattened.is_empty() {
+        return TokenStream::new();
+    }
+
+    let claimed = fields
+        .named
+        .iter()
+        .filter(|field| !is_skipped(field) && !attributes::has_flag(&field.attrs, "flatten"))
+        .map(attributes::get_field_name)
+        .chain(tag.map(String::from));
+
+    let mut res = quote! {
+        let __rest = serde_lite::Intermediate::Map(__obj.without_keys(&[#(#claimed),*]));
+    };
+
+    // flattened fields consuming any key (e.g. maps) do not get keys consumed
+    // by the other flattened fields
+    if flattened.len() > 1 {
+        let keys = flattened
+            .iter()
+            .map(|field| attributes::get_flattened_keys(field));
+
+        res.extend(quote! {
+            let __flattened_keys = [#(#keys),*];
+        });
+    }
+
+    res
+}
+
 /// Generate code for updating given unnamed fields.
-fn update_unnamed_fields(fields: &FieldsUnnamed) -> (TokenStream, TokenStream) {
+///
+/// If the container has a default value, all fields may be missing at the end
+/// of the array.
+fn update_unnamed_fields(
+    fields: &FieldsUnnamed,
+    container_default: bool,
+    validate: &TokenStream,
+) -> (TokenStream, TokenStream) {
     match fields.unnamed.len() {
-        0 => update_unnamed_fields_0(),
-        1 => update_unnamed_fields_1(),
-        _ => update_unnamed_fields_n(fields),
+        0 => update_unnamed_fields_0(validate),
+        1 => update_unnamed_fields_1(fields, validate),
+        _ => update_unnamed_fields_n(fields, container_default, validate),
     }
 }
 
 /// Generate code for updating given unnamed fields where the actual
 /// number of fields is zero (e.g. zero-length tuple struct).
-fn update_unnamed_fields_0() -> (TokenStream, TokenStream) {
+fn update_unnamed_fields_0(validate: &TokenStream) -> (TokenStream, TokenStream) {
     let deconstructor = TokenStream::new();
-    let update = TokenStream::new();
+    let update = validate.clone();
 
     (deconstructor, update)
 }
 
 /// Generate code for updating given unnamed fields where the actual
 /// number of fields is one (e.g. single-element tuple struct).
-fn update_unnamed_fields_1() -> (TokenStream, TokenStream) {
+fn update_unnamed_fields_1(
+    fields: &FieldsUnnamed,
+    validate: &TokenStream,
+) -> (TokenStream, TokenStream) {
     let mut deconstructor = TokenStream::new();
     let mut update = TokenStream::new();
 
+    let field = &fields.unnamed[0];
     let name = Ident::new("f0", Span::call_site());
 
     deconstructor.extend(quote! {
         #name
     });
 
-    update.extend(quote! {
-        serde_lite::Update::update(#name, __val)?;
-    });
+    if !(is_skipped(field) || attributes::has_flag(&field.attrs, "skip_updating")) {
+        let update_field = keep_old_value(field, &name, update_field(field, &name, quote!(__val)));
+
+        let old_value = declare_old_value(field, &name);
+        let on_update = call_on_update(field, &name);
+
+        update.extend(quote! {
+            #old_value
+
+            let __res = #update_field;
+
+            __res?;
+
+            #validate
+
+            #on_update
+        });
+    } else {
+        update.extend(validate.clone());
+    }
 
     (deconstructor, update)
 }
 
 /// Generate code for updating given unnamed fields where the actual
 /// number of fields is greater than one (e.g. multi-element tuple struct).
-fn update_unnamed_fields_n(fields: &FieldsUnnamed) -> (TokenStream, TokenStream) {
+///
+/// Skipped fields do not consume any array elements and fields with default
+/// values are left untouched if they are missing at the end of the array.
+fn update_unnamed_fields_n(
+    fields: &FieldsUnnamed,
+    container_default: bool,
+    validate: &TokenStream,
+) -> (TokenStream, TokenStream) {
     let mut deconstructor = TokenStream::new();
     let mut update = TokenStream::new();
+    let mut on_update = TokenStream::new();
 
-    let len = Literal::usize_unsuffixed(fields.unnamed.len());
+    let len = Literal::usize_unsuffixed(min_array_len(fields, container_default));
 
     update.extend(quote! {
         let __arr = __val
@@ -500,22 +1043,61 @@ fn update_unnamed_fields_n(fields: &FieldsUnnamed) -> (TokenStream, TokenStream)
         let mut __field_errors = serde_lite::ErrorList::new();
     });
 
-    for (index, _) in fields.unnamed.iter().enumerate() {
+    let mut position = 0;
+
+    for (index, field) in fields.unnamed.iter().enumerate() {
         let sname = format!("f{}", index);
         let name = Ident::new(&sname, Span::call_site());
-        let lindex = Literal::usize_unsuffixed(index);
 
         deconstructor.extend(quote! {
             #name,
         });
 
+        if is_skipped(field) {
+            continue;
+        }
+
+        // the element is still present in the array, it is just ignored
+        if attributes::has_flag(&field.attrs, "skip_updating") {
+            position += 1;
+
+            continue;
+        }
+
+        let lposition = Literal::usize_unsuffixed(position);
+        let update_field = keep_old_value(field, &name, update_field(field, &name, quote!(__v)));
+
+        update.extend(declare_old_value(field, &name));
+
+        on_update.extend(call_on_update(field, &name));
+
         update.extend(quote! {
-            if let Err(err) = serde_lite::Update::update(#name, &__arr[#lindex]) {
-                __field_errors.push(serde_lite::UnnamedFieldError::new(#lindex, err));
+            if let Some(__v) = __arr.get(#lposition) {
+                __changes.enter_index(#lposition);
+
+                let __res = #update_field;
+
+                __changes.leave();
+
+                if let Err(err) = __res {
+                    __field_errors.push(serde_lite::UnnamedFieldError::new(#lposition, err));
+                }
             }
         });
+
+        position += 1;
     }
 
+    if !validate.is_empty() {
+        update.extend(quote! {
+            if __field_errors.is_empty() {
+                #validate
+            }
+        });
+    }
+
+    update.extend(on_update);
+
     update.extend(quote! {
         if !__field_errors.is_empty() {
             return Err(serde_lite::Error::UnnamedFieldErrors(__field_errors));
@@ -524,3 +1106,506 @@ fn update_unnamed_fields_n(fields: &FieldsUnnamed) -> (TokenStream, TokenStream)
 
     (deconstructor, update)
 }
+
+/// Get the minimum length of an array that can be used for updating given
+/// unnamed fields.
+fn min_array_len(fields: &FieldsUnnamed, container_default: bool) -> usize {
+    if container_default {
+        return 0;
+    }
+
+    let mut position = 0;
+    let mut len = 0;
+
+    for field in &fields.unnamed {
+        if is_skipped(field) {
+            continue;
+        }
+
+        position += 1;
+
+        if !(attributes::has_default(&field.attrs) || attributes::is_patch_type(&field.ty)) {
+            len = position;
+        }
+    }
+
+    len
+}
+
+/// Check if a given field is skipped during update.
+fn is_skipped(field: &Field) -> bool {
+    attributes::has_flag(&field.attrs, "skip")
+        || attributes::has_flag(&field.attrs, "skip_deserializing")
+}
+
+/// Get expression constructing the part of the currently updated object that
+/// belongs to a flattened field with a given index.
+fn flattened_rest(count: usize, index: usize) -> TokenStream {
+    if count > 1 {
+        let index = Literal::usize_unsuffixed(index);
+
+        quote!(serde_lite::flatten::field_rest(&__rest, &__flattened_keys, #index))
+    } else {
+        quote!(std::borrow::Cow::Borrowed(&__rest))
+    }
+}
+
+/// Generate code updating a given field (a mutable reference with a given
+/// name) from a given intermediate value.
+///
+/// The generated code evaluates to `Result<(), serde_lite::Error>`. Read-only
+/// fields always fail, fields that can be updated only once fail if they are
+/// already set and fields with the `update_if` predicate fail if the predicate
+/// does not allow the update in the current update context.
+fn update_field(field: &Field, name: &Ident, val: TokenStream) -> TokenStream {
+    let ty = &field.ty;
+
+    if attributes::has_flag(&field.attrs, "read_only") {
+        return quote! {
+            Err::<(), _>(serde_lite::Error::custom_static("field is read-only"))
+        };
+    }
+
+    let update = if let Some(validate) = validate::validate_field(field, quote!(&__tmp)) {
+        let update = call_updater(field, quote!(&mut __tmp), val);
+
+        // validated fields are updated on a copy, so that they are left
+        // untouched if the new value is not valid
+        quote! {
+            {
+                let mut __tmp = <#ty as Clone>::clone(#name);
+
+                #update
+                    .and_then(|_| #validate)
+                    .map(|_| *#name = __tmp)
+            }
+        }
+    } else {
+        call_updater(field, quote!(#name), val)
+    };
+
+    let update = if attributes::has_flag(&field.attrs, "update_once") {
+        quote! {
+            if #name.is_some() {
+                Err(serde_lite::Error::custom_static("field can be set only once"))
+            } else {
+                #update
+            }
+        }
+    } else {
+        update
+    };
+
+    check_update_if(field, update)
+}
+
+/// Generate code restoring a given field that was missing in the serialized
+/// object before an update reverted by an inverse patch.
+///
+/// The field is reset to its default value (i.e. the value it would get if
+/// it was missing in de-serialized input). Fields without a default value are
+/// updated with null.
+///
+/// The generated code evaluates to `Result<(), serde_lite::Error>`.
+fn restore_missing_field(field: &Field, name: &Ident) -> TokenStream {
+    if let Some(default) = deserialize::field_default(field, None) {
+        quote! {
+            {
+                *#name = #default;
+
+                __changes.removed();
+
+                Ok::<(), serde_lite::Error>(())
+            }
+        }
+    } else {
+        update_field(field, name, quote!(&serde_lite::Intermediate::None))
+    }
+}
+
+/// Wrap a given code so that it is evaluated only if the `update_if`
+/// predicate of a given field (if any) allows updating the field in the
+/// current update context.
+///
+/// The generated code evaluates to `Result<(), serde_lite::Error>`.
+fn check_update_if(field: &Field, code: TokenStream) -> TokenStream {
+    if let Some(path) = attributes::get_update_if(field) {
+        let path =
+            TokenStream::from_str(&path).expect("invalid path given for the update_if attribute");
+
+        // fields are not updated without a context the predicate expects
+        quote! {
+            if match __changes.context() {
+                Some(__ctx) => #path(__ctx),
+                None => false,
+            } {
+                #code
+            } else {
+                Err(serde_lite::Error::custom_static("field update is not permitted"))
+            }
+        }
+    } else {
+        code
+    }
+}
+
+/// Generate code checking the `update_if` predicates of fields of a given
+/// enum in a value used for creating a new enum value.
+///
+/// Variants with custom (de)serialization and variants capturing unknown
+/// variants are not checked.
+fn check_enum_permissions(data: &DataEnum, attrs: &[Attribute]) -> TokenStream {
+    if attributes::get_repr(attrs).is_some() {
+        return TokenStream::new();
+    }
+
+    let tag = attributes::get_enum_tag(attrs);
+    let content = attributes::get_enum_content(attrs);
+
+    let mut check = TokenStream::new();
+
+    for variant in &data.variants {
+        if attributes::is_capturing_variant(variant)
+            || attributes::get_variant_with(variant).is_some()
+        {
+            continue;
+        }
+
+        let check_variant = match &variant.fields {
+            Fields::Named(fields) if content.is_some() => check_named_fields(fields, None),
+            Fields::Named(fields) => check_named_fields(fields, tag.as_deref()),
+            Fields::Unnamed(fields) => check_unnamed_fields(fields),
+            Fields::Unit => continue,
+        };
+
+        if check_variant.is_empty() {
+            continue;
+        }
+
+        let sname = attributes::get_variant_name(variant);
+        let lname = Literal::string(&sname);
+
+        if tag.is_some() {
+            check.extend(quote! {
+                Some(#lname) => {
+                    let __val = __content;
+
+                    #check_variant
+                }
+            });
+        } else {
+            check.extend(quote! {
+                if let Some(__content) = __obj.get(#lname) {
+                    let __val = __content;
+
+                    #check_variant
+                } else
+            });
+        }
+    }
+
+    if check.is_empty() {
+        return check;
+    }
+
+    let content = if let Some(content) = content {
+        let lcontent = Literal::string(&content);
+
+        quote! {
+            let __content = match __obj.get(#lcontent) {
+                Some(__content) => __content,
+                None => return Ok(()),
+            };
+        }
+    } else {
+        quote! {
+            let __content = __val;
+        }
+    };
+
+    if let Some(tag) = tag {
+        let ltag = Literal::string(&tag);
+
+        quote! {
+            if let Some(__obj) = __val.as_map() {
+                #content
+
+                match __obj.get(#ltag).and_then(|v| v.as_str()) {
+                    #check
+                    _ => (),
+                }
+            }
+        }
+    } else {
+        quote! {
+            if let Some(__obj) = __val.as_map() {
+                #check
+                {}
+            }
+        }
+    }
+}
+
+/// Generate code checking the `update_if` predicates of given named fields in
+/// a value used for creating a new object.
+///
+/// Nothing is generated if none of the fields can be checked.
+fn check_named_fields(fields: &FieldsNamed, tag: Option<&str>) -> TokenStream {
+    let flattened = get_flattened_fields(fields);
+
+    let mut check = TokenStream::new();
+
+    let mut flattened_index = 0;
+
+    for field in &fields.named {
+        if !is_skipped(field) && attributes::has_flag(&field.attrs, "flatten") {
+            flattened_index += 1;
+
+            let rest = flattened_rest(flattened.len(), flattened_index - 1);
+
+            if let Some(check_field) = check_field_permissions(field, quote!(&*__field_rest)) {
+                check.extend(quote! {
+                    let __field_rest = #rest;
+
+                    if let Err(err) = #check_field {
+                        if let serde_lite::Error::NamedFieldErrors(errors) = err {
+                            __field_errors.append(errors);
+                        } else {
+                            return Err(err);
+                        }
+                    }
+                });
+            }
+        } else if let Some(check_field) = check_field_permissions(field, quote!(__v)) {
+            let sname = attributes::get_field_name(field);
+            let lname = Literal::string(&sname);
+
+            check.extend(quote! {
+                if let Some(__v) = __obj.get(#lname) {
+                    if let Err(err) = #check_field {
+                        __field_errors.push(serde_lite::NamedFieldError::new_static(#lname, err));
+                    }
+                }
+            });
+        }
+    }
+
+    if check.is_empty() {
+        return check;
+    }
+
+    let rest = split_flattened_rest(fields, tag);
+
+    quote! {
+        if let Some(__obj) = __val.as_map() {
+            let mut __field_errors = serde_lite::ErrorList::new();
+
+            #rest
+            #check
+
+            if !__field_errors.is_empty() {
+                return Err(serde_lite::Error::NamedFieldErrors(__field_errors));
+            }
+        }
+    }
+}
+
+/// Generate code checking the `update_if` predicates of given unnamed fields
+/// in a value used for creating a new object.
+///
+/// Nothing is generated if none of the fields can be checked.
+fn check_unnamed_fields(fields: &FieldsUnnamed) -> TokenStream {
+    if fields.unnamed.len() == 1 {
+        return check_field_permissions(&fields.unnamed[0], quote!(__val))
+            .map(|check| quote!(#check?;))
+            .unwrap_or_default();
+    }
+
+    let mut check = TokenStream::new();
+
+    let mut position = 0;
+
+    for field in &fields.unnamed {
+        if is_skipped(field) {
+            continue;
+        }
+
+        if let Some(check_field) = check_field_permissions(field, quote!(__v)) {
+            let lposition = Literal::usize_unsuffixed(position);
+
+            check.extend(quote! {
+                if let Some(__v) = __arr.get(#lposition) {
+                    if let Err(err) = #check_field {
+                        __field_errors.push(serde_lite::UnnamedFieldError::new(#lposition, err));
+                    }
+                }
+            });
+        }
+
+        position += 1;
+    }
+
+    if check.is_empty() {
+        return check;
+    }
+
+    quote! {
+        if let Some(__arr) = __val.as_array() {
+            let mut __field_errors = serde_lite::ErrorList::new();
+
+            #check
+
+            if !__field_errors.is_empty() {
+                return Err(serde_lite::Error::UnnamedFieldErrors(__field_errors));
+            }
+        }
+    }
+}
+
+/// Generate code checking the `update_if` predicate of a given field and the
+/// predicates of all fields of its value (if it is a nested object).
+///
+/// The generated code evaluates to `Result<(), serde_lite::Error>`. `None` is
+/// returned if there is nothing to check (e.g. the field is never updated).
+fn check_field_permissions(field: &Field, val: TokenStream) -> Option<TokenStream> {
+    if is_skipped(field)
+        || attributes::has_flag(&field.attrs, "skip_updating")
+        || attributes::has_flag(&field.attrs, "read_only")
+    {
+        return None;
+    }
+
+    let ty = &field.ty;
+
+    // values of fields with custom (de)serialization cannot be inspected
+    let custom = attributes::get_field_deserializer(field).is_some()
+        || attributes::get_field_updater(field).is_some()
+        || attributes::get_field_with(field).is_some();
+
+    if custom && attributes::get_update_if(field).is_none() {
+        return None;
+    }
+
+    let check = if custom {
+        quote!(Ok::<(), serde_lite::Error>(()))
+    } else {
+        quote!(<#ty as serde_lite::Update>::check_permissions(#val, __changes))
+    };
+
+    Some(check_update_if(field, check))
+}
+
+/// Generate declaration of a variable for the old value of a given field
+/// (if the field has the `on_update` hook).
+fn declare_old_value(field: &Field, name: &Ident) -> TokenStream {
+    if attributes::get_on_update(field).is_none() {
+        return TokenStream::new();
+    }
+
+    let ty = &field.ty;
+    let old = format_ident!("__old_{}", name);
+
+    quote! {
+        let mut #old: Option<#ty> = None;
+    }
+}
+
+/// Wrap a given field update code so that the old value of the field is kept
+/// if the update succeeds (and if the field has the `on_update` hook).
+///
+/// The generated code evaluates to `Result<(), serde_lite::Error>`.
+fn keep_old_value(field: &Field, name: &Ident, update: TokenStream) -> TokenStream {
+    if attributes::get_on_update(field).is_none() {
+        return update;
+    }
+
+    let ty = &field.ty;
+    let old = format_ident!("__old_{}", name);
+
+    quote! {
+        {
+            let __old = <#ty as Clone>::clone(#name);
+
+            let __res = #update;
+
+            if __res.is_ok() {
+                #old = Some(__old);
+            }
+
+            __res
+        }
+    }
+}
+
+/// Generate code calling the `on_update` hook of a given field (if any) with
+/// the kept old value.
+fn call_on_update(field: &Field, name: &Ident) -> TokenStream {
+    if let Some(path) = attributes::get_on_update(field) {
+        let path =
+            TokenStream::from_str(&path).expect("invalid path given for the on_update attribute");
+
+        let old = format_ident!("__old_{}", name);
+
+        quote! {
+            if let Some(__old) = #old {
+                #path(self, &__old);
+            }
+        }
+    } else {
+        TokenStream::new()
+    }
+}
+
+/// Generate code calling updater of a given field.
+///
+/// The generated code evaluates to `Result<(), serde_lite::Error>`.
+fn call_updater(field: &Field, target: TokenStream, val: TokenStream) -> TokenStream {
+    let ty = &field.ty;
+
+    if let Some(path) = attributes::get_field_updater(field) {
+        let path =
+            TokenStream::from_str(&path).expect("invalid path given for the update_with attribute");
+
+        quote! {
+            (#path(#target, #val)).map(|_| __changes.written(#val))
+        }
+    } else if let Some(path) = attributes::get_field_with(field) {
+        let path = TokenStream::from_str(&path).expect("invalid path given for the with attribute");
+
+        // The update function of the module shadows the fallback function
+        // (if the module has any). The fallback replaces the current value
+        // with a value created by the deserialize function of the module.
+        quote! {
+            {
+                #[allow(unused_imports)]
+                use serde_lite::with::update;
+
+                {
+                    #[allow(unused_imports)]
+                    use #path::*;
+
+                    serde_lite::with::UpdateResult::<#ty>::finish(
+                        update(#target, #val),
+                        #path::deserialize,
+                    )
+                }
+            }
+            .map(|_| __changes.written(#val))
+        }
+    } else if let Some(by_key) = attributes::get_update_by_key(field) {
+        let key = Literal::string(&by_key.key);
+        let remove = by_key.remove_unmatched;
+
+        quote! {
+            serde_lite::by_key::update(#target, #val, #key, #remove, __changes)
+        }
+    } else if let Some(inner) = attributes::get_flattened_option(field) {
+        quote! {
+            serde_lite::flatten::update::<#inner>(#target, #val, __changes)
+        }
+    } else {
+        quote! {
+            <#ty as serde_lite::Update>::update_tracked(#target, #val, __changes)
+        }
+    }
+}
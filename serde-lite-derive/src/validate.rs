@@ -0,0 +1,141 @@
+use std::str::FromStr;
+
+use proc_macro2::{Literal, TokenStream};
+use quote::quote;
+use syn::{Expr, Field, Lit};
+
+use crate::attributes::{self, Validator};
+
+/// Generate code validating a given field value.
+///
+/// The generated code evaluates to `Result<(), serde_lite::Error>`. `None` is
+/// returned if there are no validators for the field.
+pub fn validate_field(field: &Field, val: TokenStream) -> Option<TokenStream> {
+    let validators = attributes::get_field_validators(field);
+
+    if validators.is_empty() {
+        return None;
+    }
+
+    let ty = &field.ty;
+
+    let mut res = quote! {
+        let __v: &#ty = #val;
+
+        Result::<(), serde_lite::Error>::Ok(())
+    };
+
+    for validator in validators {
+        let check = match validator {
+            Validator::Range { min, max } => {
+                let min = optional(min);
+                let max = optional(max);
+
+                quote! {
+                    serde_lite::validate::range(__v, #min, #max)
+                }
+            }
+            Validator::Length { min, max } => {
+                let min = optional(min);
+                let max = optional(max);
+
+                quote! {
+                    serde_lite::validate::length(__v, #min, #max)
+                }
+            }
+            Validator::NonEmpty => quote! {
+                serde_lite::validate::non_empty(__v)
+            },
+            Validator::Regex(pattern) => {
+                let lpattern = Literal::string(&pattern);
+
+                quote! {
+                    {
+                        static __PATTERN: serde_lite::validate::Pattern =
+                            serde_lite::validate::Pattern::new(#lpattern);
+
+                        serde_lite::validate::regex(__v, &__PATTERN)
+                    }
+                }
+            }
+            Validator::Custom(path) => {
+                let path = TokenStream::from_str(&path)
+                    .expect("invalid path given for the validate attribute");
+
+                quote! {
+                    #path(__v)
+                }
+            }
+        };
+
+        res.extend(quote! {
+            .and_then(|_| #check)
+        });
+    }
+
+    Some(quote! {
+        { #res }
+    })
+}
+
+/// Generate code adding JSON Schema keywords corresponding to validators of a
+/// given field into a given schema.
+pub fn describe_field(field: &Field, schema: TokenStream) -> TokenStream {
+    let mut describe = TokenStream::new();
+
+    for validator in attributes::get_field_validators(field) {
+        match validator {
+            Validator::Range { min, max } => {
+                let min = optional_intermediate(min);
+                let max = optional_intermediate(max);
+
+                describe.extend(quote! {
+                    serde_lite::validate::range_schema(&mut #schema, #min, #max);
+                });
+            }
+            Validator::Length { min, max } => {
+                let min = optional(min);
+                let max = optional(max);
+
+                describe.extend(quote! {
+                    serde_lite::validate::length_schema(&mut #schema, #min, #max);
+                });
+            }
+            Validator::NonEmpty => describe.extend(quote! {
+                serde_lite::validate::length_schema(&mut #schema, Some(1), None);
+            }),
+            Validator::Regex(pattern) => {
+                let lpattern = Literal::string(&pattern);
+
+                describe.extend(quote! {
+                    serde_lite::validate::regex_schema(&mut #schema, #lpattern);
+                });
+            }
+            Validator::Custom(_) => (),
+        }
+    }
+
+    describe
+}
+
+/// Helper function.
+fn optional(expr: Option<Expr>) -> TokenStream {
+    if let Some(expr) = expr {
+        quote! { Some(#expr) }
+    } else {
+        quote! { None }
+    }
+}
+
+/// Helper function.
+fn optional_intermediate(expr: Option<Expr>) -> TokenStream {
+    match expr {
+        Some(Expr::Lit(l)) if matches!(l.lit, Lit::Int(_) | Lit::Float(_)) => quote! {
+            Some(serde_lite::Intermediate::from(#l))
+        },
+        Some(Expr::Lit(_)) | None => quote! { None },
+        Some(expr) => quote! {
+            serde_lite::Serialize::serialize(&(#expr)).ok()
+        },
+    }
+}
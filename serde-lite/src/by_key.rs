@@ -0,0 +1,110 @@
+//! Helpers used by the derive macros to implement the `update_by_key` field
+//! attribute.
+//!
+//! The module is not a part of the public API.
+
+use crate::{
+    update, ChangeKind, ChangeSet, Error, ErrorList, Intermediate, NamedFieldError, Serialize,
+    UnnamedFieldError, Update,
+};
+
+/// Update elements of a given vector matching them by a given key.
+///
+/// Elements of the input array are matched to the existing elements by the
+/// value of the key field. Matching elements are updated in place, elements
+/// without a match are appended. Existing elements without a match in the
+/// input array are either kept or removed.
+///
+/// Inverse patches contain the whole original array, so the array is updated
+/// by position when applying an inverse patch.
+#[inline(never)]
+pub fn update<T>(
+    items: &mut Vec<T>,
+    val: &Intermediate,
+    key: &'static str,
+    remove_unmatched: bool,
+    changes: &mut ChangeSet<'_>,
+) -> Result<(), Error>
+where
+    T: Serialize + Update,
+{
+    if changes.is_inverse() {
+        return items.update_tracked(val, changes);
+    }
+
+    let val = val
+        .as_array()
+        .ok_or_else(|| Error::invalid_value_static("array"))?;
+
+    let mut keys = Vec::with_capacity(items.len());
+
+    for item in items.iter() {
+        keys.push(get_key(&item.serialize()?, key));
+    }
+
+    let mut matched = vec![false; items.len()];
+
+    let mut errors = ErrorList::new();
+
+    for (index, elem) in val.iter().enumerate() {
+        let res = match get_key(elem, key) {
+            Some(k) => {
+                if let Some(pos) = keys.iter().position(|current| current.as_ref() == Some(&k)) {
+                    matched[pos] = true;
+
+                    changes.enter_index(pos);
+
+                    let res = items[pos].update_tracked(elem, changes);
+
+                    changes.leave();
+
+                    res
+                } else {
+                    update::create(elem, changes).map(|item: T| {
+                        changes.enter_index(items.len());
+                        changes.record(ChangeKind::Inserted, None, Some(elem.clone()));
+                        changes.leave();
+
+                        items.push(item);
+                        keys.push(Some(k));
+                        matched.push(true);
+                    })
+                }
+            }
+            None => Err(Error::from(NamedFieldError::new_static(
+                key,
+                Error::MissingField,
+            ))),
+        };
+
+        if let Err(err) = res {
+            errors.push(UnnamedFieldError::new(index, err));
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(Error::UnnamedFieldErrors(errors));
+    }
+
+    if remove_unmatched {
+        for (pos, _) in matched.iter().enumerate().filter(|(_, matched)| !**matched) {
+            changes.enter_index(pos);
+            changes.removed();
+            changes.leave();
+        }
+
+        let mut matched = matched.into_iter();
+
+        items.retain(|_| matched.next().unwrap_or(true));
+    }
+
+    Ok(())
+}
+
+/// Get value of a given key field of a given element.
+fn get_key(elem: &Intermediate, key: &str) -> Option<Intermediate> {
+    elem.as_map()
+        .and_then(|map| map.get(key))
+        .filter(|k| !k.is_none())
+        .cloned()
+}
@@ -0,0 +1,495 @@
+use std::{
+    any::Any,
+    borrow::Cow,
+    fmt::{self, Debug, Display, Formatter},
+    slice,
+};
+
+use crate::{Intermediate, Map};
+
+/// Key of a map patch listing keys to be removed from the updated map.
+///
+/// Inverse patches use it for removing map keys inserted by the reverted
+/// update. The key is recognized only when applying an inverse patch (see
+/// `Update::apply_inverse`) and only if it contains an array of strings.
+pub const REMOVE_KEYS: &str = "$remove";
+
+/// Segment of a change path.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+    /// Field name or map key.
+    Key(Cow<'static, str>),
+    /// Array index.
+    Index(usize),
+}
+
+/// Path of a changed value.
+///
+/// The path is displayed as field names and map keys separated by dots and
+/// array indices in square brackets (e.g. `items[2].name`). An empty path
+/// refers to the updated object itself.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Path {
+    segments: Vec<PathSegment>,
+}
+
+impl Path {
+    /// Get the path segments.
+    #[inline]
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+
+    /// Check if the path refers to the updated object itself.
+    #[inline]
+    pub fn is_root(&self) -> bool {
+        self.segments.is_empty()
+    }
+}
+
+impl Display for Path {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for (index, segment) in self.segments.iter().enumerate() {
+            match segment {
+                PathSegment::Key(key) if index > 0 => write!(f, ".{}", key)?,
+                PathSegment::Key(key) => f.write_str(key)?,
+                PathSegment::Index(i) => write!(f, "[{}]", i)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Kind of a change.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ChangeKind {
+    /// An existing value was written.
+    Modified,
+    /// A new element was added to a collection.
+    Inserted,
+    /// An element was removed from a collection.
+    Removed,
+}
+
+/// Single change made by `Update`.
+#[derive(Debug, Clone)]
+pub struct Change {
+    path: Path,
+    kind: ChangeKind,
+    old: Option<Intermediate>,
+    new: Option<Intermediate>,
+}
+
+impl Change {
+    /// Get path of the changed value.
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get kind of the change.
+    #[inline]
+    pub fn kind(&self) -> ChangeKind {
+        self.kind
+    }
+
+    /// Get the old value (if known).
+    #[inline]
+    pub fn old_value(&self) -> Option<&Intermediate> {
+        self.old.as_ref()
+    }
+
+    /// Get the new value (if any).
+    #[inline]
+    pub fn new_value(&self) -> Option<&Intermediate> {
+        self.new.as_ref()
+    }
+}
+
+/// Set of changes made by `Update`.
+///
+/// The set is filled by `Update::update_tracked`. Implementations enter a
+/// path segment before updating a nested value and leave it afterwards, so
+/// that every recorded change gets the full path of the changed value.
+///
+/// The change set also carries an optional update context which is passed to
+/// the `update_if` predicates of all updated fields.
+///
+/// Old values of written and removed values are taken from the serialized
+/// state of the object before the update (see `set_old_state`). Written
+/// values equal to their old values are not recorded.
+#[derive(Clone)]
+pub struct ChangeSet<'a> {
+    enabled: bool,
+    inverse: bool,
+    path: Vec<PathSegment>,
+    changes: Vec<Change>,
+    context: Option<&'a dyn Any>,
+    old_state: Option<Intermediate>,
+}
+
+impl<'a> ChangeSet<'a> {
+    /// Create a new empty change set.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            inverse: false,
+            path: Vec::new(),
+            changes: Vec::new(),
+            context: None,
+            old_state: None,
+        }
+    }
+
+    /// Create a change set that does not record anything.
+    #[inline]
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            inverse: false,
+            path: Vec::new(),
+            changes: Vec::new(),
+            context: None,
+            old_state: None,
+        }
+    }
+
+    /// Set the update context.
+    #[inline]
+    pub fn set_context<C>(&mut self, context: &'a C)
+    where
+        C: Any,
+    {
+        self.context = Some(context);
+    }
+
+    /// Get the update context (if it is set and if it is of a given type).
+    #[inline]
+    pub fn context<C>(&self) -> Option<&'a C>
+    where
+        C: Any,
+    {
+        self.context.and_then(|context| context.downcast_ref())
+    }
+
+    /// Set the serialized state of the updated object before the update.
+    ///
+    /// The state is used for reporting old values of written and removed
+    /// values.
+    #[inline]
+    pub fn set_old_state(&mut self, old: Intermediate) {
+        self.old_state = Some(old);
+    }
+
+    /// Get the serialized state of the updated object before the update (if
+    /// it is set).
+    #[inline]
+    pub fn old_state(&self) -> Option<&Intermediate> {
+        self.old_state.as_ref()
+    }
+
+    /// Mark the update value as an inverse patch.
+    ///
+    /// Map updates remove keys listed under the `REMOVE_KEYS` key only if the
+    /// update value is an inverse patch. Otherwise, the key is treated as any
+    /// other key.
+    #[inline]
+    pub fn set_inverse(&mut self, inverse: bool) {
+        self.inverse = inverse;
+    }
+
+    /// Check if the update value is an inverse patch.
+    #[inline]
+    pub fn is_inverse(&self) -> bool {
+        self.inverse
+    }
+
+    /// Get keys listed for removal in a given map update value.
+    ///
+    /// The keys are listed under the `REMOVE_KEYS` key. `None` is returned if
+    /// the update value is not an inverse patch or if there is no valid list
+    /// of keys.
+    pub fn removed_keys<'b>(&self, val: &'b Map) -> Option<&'b [Intermediate]> {
+        if !self.inverse {
+            return None;
+        }
+
+        val.get(REMOVE_KEYS)
+            .and_then(|keys| keys.as_array())
+            .filter(|keys| keys.iter().all(|key| key.as_str().is_some()))
+    }
+
+    /// Check if changes are being recorded.
+    ///
+    /// Implementations can use this to avoid computing values that would
+    /// not be recorded anyway.
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Enter a given field.
+    #[inline]
+    pub fn enter_field(&mut self, name: &'static str) {
+        if self.enabled {
+            self.path.push(PathSegment::Key(Cow::Borrowed(name)));
+        }
+    }
+
+    /// Enter a given map key.
+    #[inline]
+    pub fn enter_key(&mut self, key: &str) {
+        if self.enabled {
+            self.path
+                .push(PathSegment::Key(Cow::Owned(key.to_string())));
+        }
+    }
+
+    /// Enter a given array index.
+    #[inline]
+    pub fn enter_index(&mut self, index: usize) {
+        if self.enabled {
+            self.path.push(PathSegment::Index(index));
+        }
+    }
+
+    /// Leave the last entered path segment.
+    #[inline]
+    pub fn leave(&mut self) {
+        if self.enabled {
+            self.path.pop();
+        }
+    }
+
+    /// Record a change of the value at the current path.
+    pub fn record(
+        &mut self,
+        kind: ChangeKind,
+        old: Option<Intermediate>,
+        new: Option<Intermediate>,
+    ) {
+        if self.enabled {
+            self.changes.push(Change {
+                path: Path {
+                    segments: self.path.clone(),
+                },
+                kind,
+                old,
+                new,
+            });
+        }
+    }
+
+    /// Record that the value at the current path was written with a given
+    /// value.
+    ///
+    /// Nothing is recorded if the old value at the current path is known and
+    /// it is equal to the given value.
+    pub fn written(&mut self, val: &Intermediate) {
+        if !self.enabled {
+            return;
+        }
+
+        let old = self.current_old_value();
+
+        if old != Some(val) {
+            let old = old.cloned();
+
+            self.record(ChangeKind::Modified, old, Some(val.clone()));
+        }
+    }
+
+    /// Record that the value at the current path was removed.
+    pub fn removed(&mut self) {
+        if self.enabled {
+            let old = self.current_old_value().cloned();
+
+            self.record(ChangeKind::Removed, old, None);
+        }
+    }
+
+    /// Get the old value at the current path (if known).
+    fn current_old_value(&self) -> Option<&Intermediate> {
+        self.old_state
+            .as_ref()
+            .and_then(|old| get_value(old, &self.path))
+    }
+
+    /// Create an inverse patch for the recorded changes.
+    ///
+    /// A given value must be the updated object serialized before the
+    /// update. Updating the object using the returned patch restores its
+    /// original serialized state. Touched values are set to their old values,
+    /// arrays containing any changes are restored as a whole and inserted map
+    /// keys are listed for removal under the `REMOVE_KEYS` key. Keys that are
+    /// missing in an existing map of the old serialized object (e.g. fields
+    /// skipped using `skip_serializing_if`) are listed for removal as well.
+    pub fn inverse(&self, old: &Intermediate) -> Intermediate {
+        let mut inverse = Intermediate::Map(Map::new());
+
+        let mut restored: Vec<&[PathSegment]> = Vec::new();
+        let mut removed: Vec<&[PathSegment]> = Vec::new();
+
+        for change in &self.changes {
+            let segments = change.path.segments();
+
+            // changes within arrays are reverted by restoring the whole array
+            let path = if let Some(index) = segments
+                .iter()
+                .position(|segment| matches!(segment, PathSegment::Index(_)))
+            {
+                &segments[..index]
+            } else if change.kind == ChangeKind::Inserted {
+                continue;
+            } else {
+                segments
+            };
+
+            if restored.iter().any(|r| path.starts_with(r)) {
+                continue;
+            }
+
+            if let Some(old) = get_value(old, path) {
+                set_value(&mut inverse, path, old.clone());
+            } else if let Some((PathSegment::Key(_), parent)) = path.split_last() {
+                if let Some(Intermediate::Map(_)) = get_value(old, parent) {
+                    removed.push(path);
+                } else {
+                    continue;
+                }
+            } else {
+                continue;
+            }
+
+            restored.retain(|r| !r.starts_with(path));
+            restored.push(path);
+        }
+
+        let inserted = self
+            .changes
+            .iter()
+            .filter(|change| change.kind == ChangeKind::Inserted)
+            .map(|change| change.path.segments());
+
+        for segments in inserted.chain(removed) {
+            if let Some((PathSegment::Key(key), parent)) = segments.split_last() {
+                if let Some(Intermediate::Map(map)) = get_value_mut(&mut inverse, parent) {
+                    let keys = map
+                        .entry(Cow::Borrowed(REMOVE_KEYS))
+                        .or_insert_with(|| Intermediate::Array(Vec::new()));
+
+                    if let Intermediate::Array(keys) = keys {
+                        keys.push(Intermediate::String(key.clone()));
+                    }
+                }
+            }
+        }
+
+        inverse
+    }
+
+    /// Get a change of the value at a given path.
+    pub fn get(&self, path: &str) -> Option<&Change> {
+        self.changes
+            .iter()
+            .find(|change| change.path.to_string() == path)
+    }
+
+    /// Get an iterator over the recorded changes.
+    #[inline]
+    pub fn iter(&self) -> slice::Iter<'_, Change> {
+        self.changes.iter()
+    }
+
+    /// Get the number of recorded changes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    /// Check if there are no recorded changes.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl Debug for ChangeSet<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("ChangeSet")
+            .field("enabled", &self.enabled)
+            .field("inverse", &self.inverse)
+            .field("path", &self.path)
+            .field("changes", &self.changes)
+            .field("old_state", &self.old_state)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for ChangeSet<'_> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IntoIterator for ChangeSet<'_> {
+    type Item = Change;
+    type IntoIter = std::vec::IntoIter<Change>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.changes.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a ChangeSet<'_> {
+    type Item = &'a Change;
+    type IntoIter = slice::Iter<'a, Change>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.changes.iter()
+    }
+}
+
+/// Get value at a given path.
+fn get_value<'a>(mut val: &'a Intermediate, path: &[PathSegment]) -> Option<&'a Intermediate> {
+    for segment in path {
+        val = match segment {
+            PathSegment::Key(key) => val.as_map()?.get(key),
+            PathSegment::Index(index) => val.as_array()?.get(*index),
+        }?;
+    }
+
+    Some(val)
+}
+
+/// Get a mutable reference to a value at a given path.
+///
+/// Missing map keys along the path are inserted with empty maps.
+fn get_value_mut<'a>(
+    mut val: &'a mut Intermediate,
+    path: &[PathSegment],
+) -> Option<&'a mut Intermediate> {
+    for segment in path {
+        val = match (segment, val) {
+            (PathSegment::Key(key), Intermediate::Map(map)) => map
+                .entry(key.clone())
+                .or_insert_with(|| Intermediate::Map(Map::new())),
+            (PathSegment::Index(index), Intermediate::Array(arr)) => arr.get_mut(*index)?,
+            _ => return None,
+        };
+    }
+
+    Some(val)
+}
+
+/// Set value at a given path.
+fn set_value(target: &mut Intermediate, path: &[PathSegment], val: Intermediate) {
+    if let Some(target) = get_value_mut(target, path) {
+        *target = val;
+    }
+}
@@ -1,10 +1,10 @@
 use std::{
     borrow::Cow,
     cell::{Cell, RefCell},
-    collections::{BTreeMap, HashMap, HashSet},
+    collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, LinkedList, VecDeque},
     convert::TryInto,
     hash::{BuildHasher, Hash},
-    ops::Range,
+    ops::{Bound, Range, RangeInclusive},
     rc::Rc,
     sync::{Arc, Mutex},
 };
@@ -20,6 +20,21 @@ pub trait Deserialize {
     fn deserialize(val: &Intermediate) -> Result<Self, Error>
     where
         Self: Sized;
+
+    /// Get keys consumed by the object when it is flattened into another
+    /// object.
+    ///
+    /// `None` means that the object can consume any key (e.g. a map). The
+    /// method is used by the derive macros to keep keys of flattened fields
+    /// apart and it is not a part of the public API.
+    #[doc(hidden)]
+    #[inline]
+    fn flattened_keys() -> Option<Vec<&'static str>>
+    where
+        Self: Sized,
+    {
+        None
+    }
 }
 
 impl Deserialize for bool {
@@ -143,6 +158,11 @@ where
             T::deserialize(val).map(Some)
         }
     }
+
+    #[inline]
+    fn flattened_keys() -> Option<Vec<&'static str>> {
+        T::flattened_keys()
+    }
 }
 
 impl<T> Deserialize for Vec<T>
@@ -441,6 +461,37 @@ where
     }
 }
 
+macro_rules! deserialize_sequence {
+    ( $x:ident, $insert:ident $( , $bound:path )* ) => {
+        impl<T> Deserialize for $x<T>
+        where
+            T: Deserialize $( + $bound )*,
+        {
+            fn deserialize(val: &Intermediate) -> Result<Self, Error>
+            where
+                Self: Sized,
+            {
+                let val = val
+                    .as_array()
+                    .ok_or_else(|| Error::invalid_value_static("array"))?;
+
+                let mut res = $x::new();
+
+                for value in val {
+                    res.$insert(T::deserialize(value)?);
+                }
+
+                Ok(res)
+            }
+        }
+    };
+}
+
+deserialize_sequence!(BTreeSet, insert, Ord);
+deserialize_sequence!(VecDeque, push_back);
+deserialize_sequence!(LinkedList, push_back);
+deserialize_sequence!(BinaryHeap, push, Ord);
+
 macro_rules! deserialize_wrapper {
     ( $x:ident ) => {
         impl<T> Deserialize for $x<T>
@@ -453,6 +504,11 @@ macro_rules! deserialize_wrapper {
 
                 Ok($x::new(inner))
             }
+
+            #[inline]
+            fn flattened_keys() -> Option<Vec<&'static str>> {
+                T::flattened_keys()
+            }
         }
     };
 }
@@ -472,16 +528,70 @@ where
     where
         Self: Sized,
     {
-        let val = val
-            .as_map()
-            .ok_or_else(|| Error::invalid_value_static("map"))?;
+        let (start, end) = deserialize_range(val)?;
 
-        let start = val.get("start").ok_or(Error::MissingField)?;
-        let start = T::deserialize(start)?;
+        Ok(start..end)
+    }
+}
 
-        let end = val.get("end").ok_or(Error::MissingField)?;
-        let end = T::deserialize(end)?;
+impl<T> Deserialize for RangeInclusive<T>
+where
+    T: Deserialize,
+{
+    fn deserialize(val: &Intermediate) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        let (start, end) = deserialize_range(val)?;
 
-        Ok(start..end)
+        Ok(start..=end)
     }
 }
+
+impl<T> Deserialize for Bound<T>
+where
+    T: Deserialize,
+{
+    fn deserialize(val: &Intermediate) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        if let Some(variant) = val.as_str() {
+            match variant {
+                "Unbounded" => Ok(Self::Unbounded),
+                "Included" | "Excluded" => Err(Error::MissingEnumVariantContent),
+                _ => Err(Error::UnknownEnumVariant),
+            }
+        } else if let Some(val) = val.as_map() {
+            if let Some(content) = val.get("Included") {
+                T::deserialize(content).map(Self::Included)
+            } else if let Some(content) = val.get("Excluded") {
+                T::deserialize(content).map(Self::Excluded)
+            } else if val.get("Unbounded").is_some() {
+                Ok(Self::Unbounded)
+            } else {
+                Err(Error::UnknownEnumVariant)
+            }
+        } else {
+            Err(Error::invalid_value_static("enum variant"))
+        }
+    }
+}
+
+/// Helper function.
+fn deserialize_range<T>(val: &Intermediate) -> Result<(T, T), Error>
+where
+    T: Deserialize,
+{
+    let val = val
+        .as_map()
+        .ok_or_else(|| Error::invalid_value_static("map"))?;
+
+    let start = val.get("start").ok_or(Error::MissingField)?;
+    let start = T::deserialize(start)?;
+
+    let end = val.get("end").ok_or(Error::MissingField)?;
+    let end = T::deserialize(end)?;
+
+    Ok((start, end))
+}
@@ -0,0 +1,112 @@
+//! Helpers used by the derive macros to implement flattened fields.
+//!
+//! The module is not a part of the public API.
+
+use std::borrow::Cow;
+
+use crate::{ChangeKind, ChangeSet, Deserialize, Error, Intermediate, Map, Update};
+
+/// Get the part of a given object that belongs to a flattened field with a
+/// given index.
+///
+/// The keys are given for each flattened field (`None` means that the field
+/// can consume any key). Fields consuming only known keys get the whole
+/// object. The other fields get the object without keys consumed by the
+/// other flattened fields.
+#[inline(never)]
+pub fn field_rest<'a>(
+    rest: &'a Intermediate,
+    keys: &[Option<Vec<&'static str>>],
+    index: usize,
+) -> Cow<'a, Intermediate> {
+    let rest = match rest {
+        Intermediate::Map(rest) if keys[index].is_none() => rest,
+        _ => return Cow::Borrowed(rest),
+    };
+
+    let claimed = keys
+        .iter()
+        .flatten()
+        .flat_map(|keys| keys.iter().copied())
+        .collect::<Vec<_>>();
+
+    Cow::Owned(Intermediate::Map(rest.without_keys(&claimed)))
+}
+
+/// Deserialize a flattened optional value.
+///
+/// The value is absent if none of its keys are present. If the keys are not
+/// known, the value is absent if it cannot be de-serialized only because all
+/// its required fields are missing.
+#[inline(never)]
+pub fn deserialize<T>(val: &Intermediate) -> Result<Option<T>, Error>
+where
+    T: Deserialize,
+{
+    if let (Some(keys), Some(map)) = (T::flattened_keys(), val.as_map()) {
+        if keys.iter().all(|key| map.get(key).is_none()) {
+            return Ok(None);
+        }
+
+        return T::deserialize(val).map(Some);
+    }
+
+    match T::deserialize(val) {
+        Ok(res) => Ok(Some(res)),
+        Err(err) if is_absent::<T>(&err) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Update a flattened optional value.
+///
+/// A present value is updated. Otherwise, it is de-serialized if any of its
+/// keys are present (see `deserialize`).
+#[inline(never)]
+pub fn update<T>(
+    target: &mut Option<T>,
+    val: &Intermediate,
+    changes: &mut ChangeSet<'_>,
+) -> Result<(), Error>
+where
+    T: Update,
+{
+    if let Some(inner) = target {
+        inner.update_tracked(val, changes)
+    } else {
+        T::check_permissions(val, changes)?;
+
+        *target = deserialize(val)?;
+
+        // the old value is absent, so it cannot be taken from the old state
+        if target.is_some() && changes.is_enabled() {
+            changes.record(ChangeKind::Modified, None, Some(val.clone()));
+        }
+
+        Ok(())
+    }
+}
+
+/// Check if a given de-serialization error means that none of the keys of a
+/// given type (with unknown keys) are present.
+///
+/// This is the case if the error contains exactly the same missing fields as
+/// an error returned when de-serializing the type from an empty map.
+fn is_absent<T>(err: &Error) -> bool
+where
+    T: Deserialize,
+{
+    let empty = Intermediate::Map(Map::new());
+
+    match (err, T::deserialize(&empty)) {
+        (Error::NamedFieldErrors(errors), Err(Error::NamedFieldErrors(expected))) => {
+            errors.len() == expected.len()
+                && errors.iter().zip(expected.iter()).all(|(err, expected)| {
+                    matches!(err.error(), Error::MissingField)
+                        && matches!(expected.error(), Error::MissingField)
+                        && err.field() == expected.field()
+                })
+        }
+        _ => false,
+    }
+}
@@ -1,5 +1,6 @@
 use std::{
     borrow::Cow,
+    cmp::Ordering,
     collections::HashMap,
     convert::{TryFrom, TryInto},
     fmt::{self, Formatter},
@@ -21,6 +22,28 @@ pub enum Number {
     UnsignedInt(u64),
 }
 
+impl PartialEq for Number {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Number {
+    /// Compare numbers by their value regardless of their internal
+    /// representation.
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (*self, *other) {
+            (Self::Float(a), b) => a.partial_cmp(&f64::from(b)),
+            (a, Self::Float(b)) => f64::from(a).partial_cmp(&b),
+            (Self::SignedInt(a), Self::SignedInt(b)) => Some(a.cmp(&b)),
+            (Self::UnsignedInt(a), Self::UnsignedInt(b)) => Some(a.cmp(&b)),
+            (Self::SignedInt(a), Self::UnsignedInt(b)) => Some(i128::from(a).cmp(&i128::from(b))),
+            (Self::UnsignedInt(a), Self::SignedInt(b)) => Some(i128::from(a).cmp(&i128::from(b))),
+        }
+    }
+}
+
 impl From<Number> for f64 {
     #[inline]
     fn from(n: Number) -> Self {
@@ -189,7 +212,7 @@ macro_rules! intermediate {
 ///
 /// The format is similar to JSON. It can be serialized/deserialized using
 /// serde.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Intermediate {
     None,
     Bool(bool),
@@ -454,6 +477,32 @@ impl crate::Update for Intermediate {
 
         Ok(())
     }
+
+    fn update_tracked(
+        &mut self,
+        other: &Intermediate,
+        changes: &mut crate::ChangeSet<'_>,
+    ) -> Result<(), Error> {
+        match (&mut *self, other) {
+            (Self::Array(arr), Self::Array(_)) => arr.update_tracked(other, changes)?,
+            (Self::Map(map), Self::Map(_)) => map.update_tracked(other, changes)?,
+            _ => {
+                if self != other {
+                    if changes.is_enabled() {
+                        changes.record(
+                            crate::ChangeKind::Modified,
+                            Some(self.clone()),
+                            Some(other.clone()),
+                        );
+                    }
+
+                    *self = other.clone();
+                }
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl Serialize for Intermediate {
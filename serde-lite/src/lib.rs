@@ -96,14 +96,104 @@
 //! This feature can be especially handy if you're constructing a REST API and
 //! you'd like to allow partial updates of your data.
 //!
+//! If you need to tell a missing field from a field set to `null`, use the
+//! `Patch<T>` field type. Missing fields of this type are de-serialized as
+//! `Patch::Missing` and they are left out when serialized. Fields set to `null`
+//! are de-serialized as `Patch::Null`.
+//!
+//! Deriving `Patchable` for a struct with named fields generates a companion
+//! type named after the struct with the `Patch` suffix (e.g. `MyStructPatch`).
+//! All fields that can be updated are optional in the companion type, so it can
+//! be used for describing and inspecting partial updates. The companion type
+//! implements `Serialize`, `Deserialize` and `Update`. Its `apply` method updates
+//! a given value the same way as the derived `Update` and its `diff` method
+//! creates a patch containing only the fields that differ between two values.
+//! Fields with custom (de)serializers (e.g. `with`) keep using them in the
+//! companion type, their companion fields are plain `Option`s and they cannot be
+//! flattened. Fields marked with `#[serde(patchable)]` use the companion type of
+//! their own type (e.g. `Option<InnerPatch>`), so nested structs can be patched
+//! partially. Read-only fields are kept in the companion type, so applying a
+//! patch containing them fails the same way as `update` does. Use
+//! `#[serde(patch_derive = "Debug, Clone")]` to derive additional traits for the
+//! companion type and its `is_empty` method to check if there is anything to
+//! apply.
+//!
+//! Use `update_with_changes` instead of `update` if you need to know what was
+//! actually changed. It returns a `ChangeSet` listing paths of all written values
+//! (e.g. `address.city` or `tags[2]`) together with the kind of each change and
+//! the old and new values. The object must implement `Serialize` because old
+//! values are taken from its serialized state before the update. Values are
+//! reported only if they differ from the current value. Collections report
+//! inserted and removed elements and enums switching to a different variant are
+//! reported as a whole. Fields with custom updaters (e.g. `with`) and sets are
+//! reported as a whole if they changed. If you call `update_tracked` with your
+//! own `ChangeSet`, old values of non-primitive types are known only if you
+//! provide the old state using `ChangeSet::set_old_state`.
+//!
+//! Types implementing both `Update` and `Serialize` can also be updated using
+//! `update_with_inverse` which returns an inverse patch. Applying the inverse
+//! patch using `apply_inverse` restores the original serialized state of the
+//! object, so it can be stored for undo or audit purposes. Map keys inserted by
+//! the update are listed for removal in the inverse patch under the `"$remove"`
+//! key (see `REMOVE_KEYS`). The key is interpreted this way only by
+//! `apply_inverse`; plain updates treat it as any other map key. Fields missing
+//! in the original serialized object (e.g. skipped using `skip_serializing_if`)
+//! are listed under the key as well and `apply_inverse` resets them to their
+//! default values. Arrays updated using `update_by_key` are restored by position.
+//!
+//! ## JSON Schema
+//!
+//! Types can also describe their intermediate representation using JSON Schema.
+//! Simply derive `Schema` and the schema will be generated according to the same
+//! attributes used for serialization and de-serialization. Example:
+//! ```rust
+//! use serde_lite::Schema;
+//! use serde_lite_derive::Schema;
+//!
+//! #[derive(Schema)]
+//! struct MyStruct {
+//!     field1: u32,
+//!     #[serde(default)]
+//!     field2: Option<String>,
+//! }
+//!
+//! let schema = MyStruct::schema();
+//! let json = serde_json::to_string_pretty(&schema).unwrap();
+//! ```
+//!
+//! The schema can be also used for validating intermediate values before they get
+//! de-serialized using `SchemaValidator`. Validation errors are reported the same
+//! way as de-serialization errors. Note that the `pattern` keyword is supported
+//! only if the `regex` feature is enabled.
+//!
+//! Recursive types are supported as well. The outermost schema of a recursive
+//! type gets an `$anchor` named after the type and the nested occurrences are
+//! replaced with references to it (e.g. `{"$ref": "#Tree"}`). `SchemaValidator`
+//! resolves references pointing to the schema document itself. Hand-written
+//! `Schema` implementations of recursive types can use the `named_schema`
+//! function for the same purpose.
+//!
 //! ## Supported attributes
 //!
 //! The library does not support all Serde attributes at this moment. Patches are
-//! definitely welcome. These attributes are supported:
+//! definitely welcome. Unsupported attributes are reported as compile errors.
+//! These attributes are supported:
 //!
 //! * Container attributes:
 //!     * `tag`
 //!     * `content`
+//!     * `validate_with`
+//!     * `after_update`
+//!     * `from`
+//!     * `try_from`
+//!     * `into`
+//!     * `try_into`
+//!     * `transparent`
+//!     * `default`
+//!     * `bound`
+//!     * `repr`
+//!     * `crate` (only as `#[serde_lite(crate = "...")]`)
+//!     * `patch_derive`
 //! * Field attributes:
 //!     * `default`
 //!     * `flatten`
@@ -115,8 +205,182 @@
 //!     * `serialize_with`
 //!     * `deserialize_with`
 //!     * `update_with`
+//!     * `skip_updating`
+//!     * `read_only`
+//!     * `update_once`
+//!     * `update_by_key`
+//!     * `on_update`
+//!     * `update_if`
+//!     * `with`
+//!     * `schema_with`
+//!     * `validate`
+//!     * `bound`
+//!     * `patchable`
 //! * Enum variant attributes:
 //!     * `rename`
+//!     * `with`
+//!     * `other`
+//!
+//! Field attributes can be used on fields of tuple structs and tuple enum
+//! variants as well. Skipped tuple fields are left out of the serialized array
+//! and trailing fields with default values may be missing in the input array.
+//! `skip_serializing_if` can be used only on trailing tuple fields with default
+//! values, so that the remaining elements keep their positions.
+//!
+//! A flattened field gets only the keys that are not claimed by the other fields
+//! of the struct (or by the tag of an internally tagged enum). This allows
+//! capturing all unknown keys in a map, e.g. `#[serde(flatten)] extra:
+//! BTreeMap<String, Intermediate>`. A flattened map (or any other flattened field
+//! that can consume any key) does not get keys consumed by the other flattened
+//! fields either. When serialized, keys of the other fields take precedence over
+//! keys of the flattened fields and keys of a flattened field take precedence over
+//! keys of the following flattened fields.
+//!
+//! Flattened fields can be structs, maps or enums represented as maps (e.g.
+//! internally tagged enums). A flattened `Option` is `None` if none of the keys
+//! of the inner type are present and it is left out when serialized. If only
+//! some of the keys are present, missing required fields of the inner type are
+//! reported as errors. An inner type that can consume any key (e.g. a map) is
+//! `None` only if it cannot be de-serialized because all of its required fields
+//! are missing.
+//!
+//! The `skip_updating`, `read_only` and `update_once` field attributes restrict
+//! updates of fields that are otherwise de-serialized normally. Fields with
+//! `skip_updating` ignore their keys during update, `read_only` fields report an
+//! error if their key is present and `update_once` fields (that must be of type
+//! `Option`) can be updated only while they are `None`.
+//!
+//! The `update_by_key = "id"` field attribute changes how a `Vec` field is
+//! updated. Instead of updating the elements by their position, elements of the
+//! input array are matched to the existing elements by the value of a given key
+//! field. Matching elements are updated in place and new elements are appended.
+//! Existing elements without a match are kept unless
+//! `update_by_key(key = "id", unmatched = "remove")` is used. The elements must
+//! implement `Serialize` in order to get their keys.
+//!
+//! The `on_update = "path::to::fn"` field attribute calls a given function after
+//! the field has been successfully updated. The function takes a mutable
+//! reference to the struct and a reference to the old value of the field, so
+//! the field type must implement `Clone`. The hook is called even if updating
+//! some other field fails. If the container uses `validate_with`, field hooks
+//! are called only after the updated value passes the validation, so they are
+//! not called for updates that get rolled back. The `after_update =
+//! "path::to::fn"` container attribute calls a given function after the whole
+//! object has been successfully updated. The function takes a mutable reference to the object and a reference
+//! to its old value, so the type must implement `Clone`. Field hooks cannot be
+//! used on fields of enum variants.
+//!
+//! The `update_if = "path::to::fn"` field attribute restricts updates of a field
+//! using a predicate. The predicate takes a reference to an update context given
+//! to `update_with_context` (e.g. the current user) and returns `true` if the
+//! field can be updated. The context is passed to all nested objects. Fields
+//! that are not permitted are left untouched and reported as field errors. The
+//! same happens if the object is updated without a context or with a context of
+//! a different type than the predicate expects.
+//!
+//! The predicates are checked in values created during an update as well (e.g.
+//! elements inserted into a collection, an `Option` that was `None` or an enum
+//! variant switched to), so a new value cannot contain a field that could not be
+//! updated. Values of fields with custom (de)serialization and elements of
+//! collections that are always replaced (e.g. sets) are not inspected.
+//!
+//! The `with = "module"` field attribute uses `module::serialize`,
+//! `module::deserialize` and `module::update`. If the module does not contain an
+//! `update` function, the field is updated by replacing its value with a value
+//! returned by `module::deserialize`. When used on an enum variant, all variant
+//! fields are passed to `module::serialize` and `module::deserialize` is expected
+//! to return a tuple of all variant fields (or just the field value if there is
+//! only one). Such variants are always updated by replacing the whole enum value.
+//!
+//! The `other` enum variant attribute marks a variant used for all unknown
+//! variants. It can be used either on a unit variant or on a tuple variant with a
+//! single field of type `String`, `Intermediate` or `(String, Intermediate)`. The
+//! `String` field captures only the unknown variant name, so any variant content
+//! is lost (adjacently tagged variants are serialized back with `null` content).
+//! The `Intermediate` field captures the whole enum value, so that it can be
+//! serialized back unchanged, and the `(String, Intermediate)` field captures
+//! both the variant name and the whole value. Variants capturing unknown variants
+//! are not included in the generated schema.
+//!
+//! The `repr = "u8"` container attribute makes an enum with unit variants
+//! represented by its discriminants instead of variant names. Any of the `u8`,
+//! `u16`, `u32`, `u64`, `i8`, `i16`, `i32` and `i64` types can be used. Unknown
+//! discriminants are handled by the `other` variant if there is one. A tuple
+//! variant marked with `other` captures the unknown discriminant.
+//!
+//! The `from`/`try_from` and `into`/`try_into` container attributes delegate
+//! de-serialization and serialization to another type. Errors returned by the
+//! fallible conversions are turned into custom errors, so the error types must
+//! implement `Display`. The `into` and `try_into` attributes require the type to
+//! implement `Clone`. Derived `Update` converts the value into the other type,
+//! updates it and converts it back if both conversions are available and they
+//! use the same type. Otherwise, the value is simply replaced.
+//!
+//! The `default` container attribute can be used on structs. Missing fields are
+//! then taken from a value created using `Default::default()` (or using a given
+//! function if the `default = "path"` form is used). The default value is created
+//! only once and fields with their own `default` attribute use their own default
+//! value. Skipped fields are also taken from the default value.
+//!
+//! The `transparent` container attribute can be used on structs with exactly one
+//! field that is not skipped. The struct is then serialized, de-serialized and
+//! updated exactly as the field itself. All skipped fields are initialized using
+//! their `Default` implementation.
+//!
+//! Generic type parameters used in fields that are serialized, de-serialized or
+//! updated get the corresponding trait bound automatically. The inferred bounds
+//! can be replaced using the `bound = "T: Trait"` container or field attribute.
+//! Use `bound(serialize = "...", deserialize = "...", update = "...", schema = "...")`
+//! to give different bounds for each derived trait.
+//!
+//! All attributes can be given also using `#[serde_lite(...)]` which takes
+//! precedence over `#[serde(...)]` on the same item. This is useful for types
+//! deriving both the serde and serde-lite traits. If a type uses
+//! `#[serde_lite(...)]` anywhere (on the container, a field or a variant), its
+//! `#[serde(...)]` attributes are considered to be shared with serde and the
+//! attributes supported only by serde are ignored. Unknown attributes are still
+//! reported. The `#[serde_lite(crate = "path")]` container attribute can be used
+//! if serde-lite is available under a different path.
+//!
+//! ## Field validation
+//!
+//! Field values can be validated during de-serialization and update using the
+//! `validate` attribute. Built-in validators are `range(min = .., max = ..)`,
+//! `length(min = .., max = ..)`, `non_empty` and `regex = ".."` (requires the
+//! `regex` feature). A custom validator can be used with
+//! `validate = "path::to::fn"` or `validate(custom = "path::to::fn")`. Example:
+//! ```rust
+//! use serde_lite::Error;
+//! use serde_lite_derive::{Deserialize, Update};
+//!
+//! #[derive(Clone, Deserialize, Update)]
+//! struct Config {
+//!     #[serde(validate(range(min = 1, max = 64)))]
+//!     threads: u32,
+//!     #[serde(validate(non_empty, length(max = 32)), validate = "check_name")]
+//!     name: String,
+//! }
+//!
+//! fn check_name(name: &str) -> Result<(), Error> {
+//!     if name.starts_with('_') {
+//!         Err(Error::custom_static("name must not start with underscore"))
+//!     } else {
+//!         Ok(())
+//!     }
+//! }
+//! ```
+//!
+//! Validation errors are reported like any other field errors. Validated fields
+//! are updated on a copy of the current value and the copy replaces the value
+//! only if it is valid, so the field types must implement `Clone`. The derived
+//! `Schema` includes the corresponding JSON Schema keywords for the built-in
+//! validators.
+//!
+//! Invariants spanning multiple fields can be checked using the container
+//! attribute `validate_with = "path::to::fn"`. The function takes a reference to
+//! the container and it is called after all fields have been de-serialized or
+//! updated. If the check fails during update, the container is restored to its
+//! original state, so the container type must implement `Clone`.
 //!
 //! # When to use this library
 //!
@@ -133,11 +397,26 @@
 //! transformed into the intermediate representation at first. And, finally, this
 //! library can only be used with self-describing formats like JSON.
 
+mod changes;
 mod deserialize;
 mod intermediate;
 mod map;
+mod patch;
+mod schema;
 mod serialize;
 mod update;
+mod validator;
+
+pub mod validate;
+
+#[doc(hidden)]
+pub mod by_key;
+#[doc(hidden)]
+pub mod flatten;
+#[doc(hidden)]
+pub mod other;
+#[doc(hidden)]
+pub mod with;
 
 use std::{
     borrow::Cow,
@@ -146,14 +425,18 @@ use std::{
 };
 
 #[cfg(feature = "derive")]
-pub use serde_lite_derive::{Deserialize, Serialize, Update};
+pub use serde_lite_derive::{Deserialize, Patchable, Schema, Serialize, Update};
 
 pub use crate::{
+    changes::{Change, ChangeKind, ChangeSet, Path, PathSegment, REMOVE_KEYS},
     deserialize::Deserialize,
     intermediate::{Intermediate, Number},
     map::{Map, MapImpl},
+    patch::Patch,
+    schema::{named_schema, EnumSchema, ObjectSchema, Schema, TupleSchema},
     serialize::Serialize,
     update::Update,
+    validator::SchemaValidator,
 };
 
 /// Error.
@@ -406,6 +689,13 @@ impl<T> ErrorList<T> {
     }
 }
 
+impl<T> Default for ErrorList<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<'a, T> IntoIterator for &'a ErrorList<T> {
     type Item = &'a T;
     type IntoIter = std::collections::linked_list::Iter<'a, T>;
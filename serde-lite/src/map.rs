@@ -17,7 +17,7 @@ pub type MapImpl<K, V> = std::collections::HashMap<K, V>;
 ///
 /// It wraps the underlying map implementation and prohibits inlining of some
 /// methods in order to make the generated code smaller.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Map {
     inner: MapImpl<Cow<'static, str>, Intermediate>,
 }
@@ -56,6 +56,25 @@ impl Map {
     pub fn insert_with_owned_key(&mut self, key: String, value: Intermediate) {
         self.inner.insert(Cow::Owned(key), value);
     }
+
+    /// Get a copy of the map without entries with given keys.
+    #[inline(never)]
+    pub fn without_keys(&self, keys: &[&str]) -> Self {
+        let inner = self
+            .inner
+            .iter()
+            .filter(|(key, _)| !keys.contains(&key.as_ref()))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+
+        Self { inner }
+    }
+
+    /// Remove all entries with given keys.
+    #[inline(never)]
+    pub fn remove_keys(&mut self, keys: &[&str]) {
+        self.inner.retain(|key, _| !keys.contains(&key.as_ref()));
+    }
 }
 
 impl Default for Map {
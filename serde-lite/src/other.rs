@@ -0,0 +1,92 @@
+//! Helpers used by the derive macros to implement the `other` enum variant
+//! attribute.
+//!
+//! The module is not a part of the public API.
+
+use std::borrow::Cow;
+
+use crate::{Error, Intermediate, Map};
+
+/// Value of a tuple variant capturing unknown enum variants.
+pub trait UnknownVariant: Sized {
+    /// Capture an unknown variant with a given name.
+    ///
+    /// The given value is the whole enum value including the tag.
+    fn capture(name: &str, val: &Intermediate) -> Self;
+
+    /// Serialize the captured variant back.
+    ///
+    /// The tag and content fields are given for internally and adjacently
+    /// tagged enums.
+    fn restore(
+        &self,
+        tag: Option<&'static str>,
+        content: Option<&'static str>,
+    ) -> Result<Intermediate, Error>;
+}
+
+/// The variant name is the only captured value. Any content of the unknown
+/// variant is lost and the variant is restored without it (i.e. with `null`
+/// content for adjacently tagged enums). Use `(String, Intermediate)` for
+/// capturing both the name and the whole value.
+impl UnknownVariant for String {
+    #[inline]
+    fn capture(name: &str, _: &Intermediate) -> Self {
+        String::from(name)
+    }
+
+    #[inline(never)]
+    fn restore(
+        &self,
+        tag: Option<&'static str>,
+        content: Option<&'static str>,
+    ) -> Result<Intermediate, Error> {
+        let name = Intermediate::String(Cow::Owned(self.clone()));
+
+        if let Some(tag) = tag {
+            let mut map = Map::with_capacity(2);
+
+            map.insert_with_static_key(tag, name);
+
+            if let Some(content) = content {
+                map.insert_with_static_key(content, Intermediate::None);
+            }
+
+            Ok(Intermediate::Map(map))
+        } else {
+            Ok(name)
+        }
+    }
+}
+
+impl UnknownVariant for Intermediate {
+    #[inline]
+    fn capture(_: &str, val: &Intermediate) -> Self {
+        val.clone()
+    }
+
+    #[inline]
+    fn restore(
+        &self,
+        _: Option<&'static str>,
+        _: Option<&'static str>,
+    ) -> Result<Intermediate, Error> {
+        Ok(self.clone())
+    }
+}
+
+impl UnknownVariant for (String, Intermediate) {
+    #[inline]
+    fn capture(name: &str, val: &Intermediate) -> Self {
+        (String::from(name), val.clone())
+    }
+
+    #[inline]
+    fn restore(
+        &self,
+        _: Option<&'static str>,
+        _: Option<&'static str>,
+    ) -> Result<Intermediate, Error> {
+        Ok(self.1.clone())
+    }
+}
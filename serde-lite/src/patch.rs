@@ -0,0 +1,182 @@
+use crate::{update, ChangeSet, Deserialize, Error, Intermediate, Schema, Serialize, Update};
+
+/// Field value distinguishing a missing field from a field set to `null`.
+///
+/// Derived `Deserialize` uses `Patch::Missing` for missing fields of this type
+/// and derived `Serialize` leaves the field out if it is `Patch::Missing`.
+/// This allows using a single type for expressing both "leave the value
+/// unchanged" and "clear the value".
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Patch<T> {
+    /// The field is missing.
+    #[default]
+    Missing,
+    /// The field is set to `null`.
+    Null,
+    /// The field is set to a given value.
+    Value(T),
+}
+
+impl<T> Patch<T> {
+    /// Check if the field is missing.
+    #[inline]
+    pub fn is_missing(&self) -> bool {
+        matches!(self, Self::Missing)
+    }
+
+    /// Check if the field is set to `null`.
+    #[inline]
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    /// Check if the field is set to a value.
+    #[inline]
+    pub fn is_value(&self) -> bool {
+        matches!(self, Self::Value(_))
+    }
+
+    /// Get the value (if any).
+    #[inline]
+    pub fn value(&self) -> Option<&T> {
+        if let Self::Value(v) = self {
+            Some(v)
+        } else {
+            None
+        }
+    }
+
+    /// Convert `&Patch<T>` into `Patch<&T>`.
+    #[inline]
+    pub fn as_ref(&self) -> Patch<&T> {
+        match self {
+            Self::Missing => Patch::Missing,
+            Self::Null => Patch::Null,
+            Self::Value(v) => Patch::Value(v),
+        }
+    }
+
+    /// Convert the patch into an option of an option.
+    ///
+    /// Missing fields are converted into `None`, fields set to `null` into
+    /// `Some(None)`.
+    #[inline]
+    pub fn into_option(self) -> Option<Option<T>> {
+        match self {
+            Self::Missing => None,
+            Self::Null => Some(None),
+            Self::Value(v) => Some(Some(v)),
+        }
+    }
+
+    /// Apply the patch to a given optional value.
+    ///
+    /// The value is left unchanged if the field is missing.
+    #[inline]
+    pub fn apply(self, target: &mut Option<T>) {
+        if let Some(v) = self.into_option() {
+            *target = v;
+        }
+    }
+}
+
+impl<T> From<Option<Option<T>>> for Patch<T> {
+    #[inline]
+    fn from(v: Option<Option<T>>) -> Self {
+        match v {
+            None => Self::Missing,
+            Some(None) => Self::Null,
+            Some(Some(v)) => Self::Value(v),
+        }
+    }
+}
+
+impl<T> Serialize for Patch<T>
+where
+    T: Serialize,
+{
+    #[inline]
+    fn serialize(&self) -> Result<Intermediate, Error> {
+        if let Self::Value(v) = self {
+            v.serialize()
+        } else {
+            Ok(Intermediate::None)
+        }
+    }
+}
+
+impl<T> Deserialize for Patch<T>
+where
+    T: Deserialize,
+{
+    #[inline]
+    fn deserialize(val: &Intermediate) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        if val.is_none() {
+            Ok(Self::Null)
+        } else {
+            T::deserialize(val).map(Self::Value)
+        }
+    }
+}
+
+impl<T> Update for Patch<T>
+where
+    T: Update,
+{
+    #[inline]
+    fn update(&mut self, val: &Intermediate) -> Result<(), Error> {
+        if val.is_none() {
+            *self = Self::Null;
+        } else if let Self::Value(inner) = self {
+            inner.update(val)?;
+        } else {
+            *self = update::create(val, &ChangeSet::disabled()).map(Self::Value)?;
+        }
+
+        Ok(())
+    }
+
+    fn update_tracked(
+        &mut self,
+        val: &Intermediate,
+        changes: &mut ChangeSet<'_>,
+    ) -> Result<(), Error> {
+        if val.is_none() {
+            if !self.is_null() {
+                *self = Self::Null;
+
+                changes.written(val);
+            }
+        } else if let Self::Value(inner) = self {
+            inner.update_tracked(val, changes)?;
+        } else {
+            *self = update::create(val, changes).map(Self::Value)?;
+
+            changes.written(val);
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn check_permissions(val: &Intermediate, changes: &ChangeSet<'_>) -> Result<(), Error> {
+        if val.is_none() {
+            Ok(())
+        } else {
+            T::check_permissions(val, changes)
+        }
+    }
+}
+
+impl<T> Schema for Patch<T>
+where
+    T: Schema,
+{
+    #[inline]
+    fn schema() -> Intermediate {
+        Option::<T>::schema()
+    }
+}
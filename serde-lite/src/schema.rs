@@ -0,0 +1,984 @@
+use std::{
+    any,
+    borrow::Cow,
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, LinkedList, VecDeque},
+    ops::{Bound, Range, RangeInclusive},
+    rc::Rc,
+    sync::{Arc, Mutex},
+};
+
+use crate::{Intermediate, Map, Number};
+
+/// Schema trait.
+///
+/// The trait can be implemented by objects that can describe their
+/// intermediate representation using JSON Schema. The schema itself is
+/// returned in the intermediate representation, so it can be serialized
+/// using Serde the same way as any other intermediate value.
+pub trait Schema {
+    /// Get JSON Schema of the type.
+    fn schema() -> Intermediate;
+}
+
+thread_local! {
+    /// Types whose schemas are being generated in the current thread.
+    static NAMED_SCHEMAS: RefCell<NamedSchemas> = RefCell::new(NamedSchemas::default());
+}
+
+/// Registry of named schemas.
+#[derive(Default)]
+struct NamedSchemas {
+    /// Types whose schemas are being generated.
+    in_progress: Vec<&'static str>,
+    /// Anchors of recursive types (type name and anchor name).
+    anchors: Vec<(&'static str, String)>,
+    /// Recursive types whose schemas are already complete.
+    complete: Vec<&'static str>,
+}
+
+impl NamedSchemas {
+    /// Get anchor of a given type (if the type is recursive).
+    fn anchor(&self, ty: &str) -> Option<&str> {
+        self.anchors
+            .iter()
+            .find(|(t, _)| *t == ty)
+            .map(|(_, anchor)| anchor.as_str())
+    }
+
+    /// Create a new anchor for a given type.
+    ///
+    /// The anchor name is made unique by appending a number, so that
+    /// different instances of a generic type get different anchors.
+    fn create_anchor(&mut self, ty: &'static str, name: &str) -> &str {
+        let mut anchor = name.to_string();
+        let mut index = 1;
+
+        while self.anchors.iter().any(|(_, a)| *a == anchor) {
+            index += 1;
+            anchor = format!("{}{}", name, index);
+        }
+
+        self.anchors.push((ty, anchor));
+
+        &self.anchors.last().unwrap().1
+    }
+}
+
+/// Guard removing a given type from the types in progress when dropped.
+struct InProgress;
+
+impl Drop for InProgress {
+    fn drop(&mut self) {
+        NAMED_SCHEMAS.with(|schemas| {
+            let mut schemas = schemas.borrow_mut();
+
+            schemas.in_progress.pop();
+
+            // anchors are unique only within a single schema document
+            if schemas.in_progress.is_empty() {
+                schemas.anchors.clear();
+                schemas.complete.clear();
+            }
+        });
+    }
+}
+
+/// Generate schema of a named type that may be recursive.
+///
+/// The schema is generated using a given closure. If the closure needs the
+/// schema of the type `T` again (i.e. the type is recursive), the nested
+/// schema is replaced with a reference (`{"$ref": "#Name"}`) and the
+/// outermost schema gets the corresponding `$anchor`. Any further
+/// occurrences of the recursive type within the same schema document are
+/// replaced with the reference as well.
+///
+/// The derived `Schema` uses this function for all structs and enums, so it
+/// is needed only for hand-written implementations of recursive types.
+pub fn named_schema<T, F>(name: &str, f: F) -> Intermediate
+where
+    T: ?Sized,
+    F: FnOnce() -> Intermediate,
+{
+    let ty = any::type_name::<T>();
+
+    let reference = NAMED_SCHEMAS.with(|schemas| {
+        let mut schemas = schemas.borrow_mut();
+
+        if schemas.complete.contains(&ty) {
+            schemas.anchor(ty).map(reference_schema)
+        } else if schemas.in_progress.contains(&ty) {
+            let anchor = match schemas.anchor(ty) {
+                Some(anchor) => anchor.to_string(),
+                None => schemas.create_anchor(ty, name).to_string(),
+            };
+
+            Some(reference_schema(&anchor))
+        } else {
+            schemas.in_progress.push(ty);
+
+            None
+        }
+    });
+
+    if let Some(reference) = reference {
+        return reference;
+    }
+
+    let guard = InProgress;
+
+    let schema = f();
+
+    let anchor = NAMED_SCHEMAS.with(|schemas| {
+        let mut schemas = schemas.borrow_mut();
+
+        let anchor = schemas.anchor(ty).map(String::from);
+
+        if anchor.is_some() {
+            schemas.complete.push(ty);
+        }
+
+        anchor
+    });
+
+    drop(guard);
+
+    if let Some(anchor) = anchor {
+        let mut schema = match schema {
+            Intermediate::Map(map) => map,
+            other => {
+                let mut res = Map::with_capacity(2);
+
+                res.insert_with_static_key("allOf", Intermediate::Array(vec![other]));
+
+                res
+            }
+        };
+
+        schema.insert_with_static_key("$anchor", Intermediate::String(Cow::Owned(anchor)));
+
+        Intermediate::Map(schema)
+    } else {
+        schema
+    }
+}
+
+/// Builder for JSON Schemas of objects.
+///
+/// The builder is used mainly by the derive macro but it can be used in
+/// hand-written implementations as well.
+#[derive(Debug, Clone)]
+pub struct ObjectSchema {
+    properties: Map,
+    required: Vec<Intermediate>,
+    all_of: Vec<Intermediate>,
+    additional_properties: Option<bool>,
+}
+
+impl ObjectSchema {
+    /// Create a new object schema.
+    #[inline(never)]
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Create a new object schema with a given capacity for properties.
+    #[inline(never)]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            properties: Map::with_capacity(capacity),
+            required: Vec::new(),
+            all_of: Vec::new(),
+            additional_properties: None,
+        }
+    }
+
+    /// Add a given property.
+    #[inline(never)]
+    pub fn property(&mut self, name: &'static str, schema: Intermediate, required: bool) {
+        self.properties.insert_with_static_key(name, schema);
+
+        if required {
+            self.required
+                .push(Intermediate::String(Cow::Borrowed(name)));
+        }
+    }
+
+    /// Merge a given schema of a flattened field into the object schema.
+    ///
+    /// Properties of plain object schemas are merged directly. Any other
+    /// schema is combined with the object schema using `allOf`.
+    #[inline(never)]
+    pub fn flatten(&mut self, schema: Intermediate) {
+        let map = match schema {
+            Intermediate::Map(map) if is_plain_object_schema(&map) => map,
+            other => {
+                self.all_of.push(other);
+                return;
+            }
+        };
+
+        for (keyword, value) in map {
+            match (keyword.as_ref(), value) {
+                ("properties", Intermediate::Map(properties)) => {
+                    self.properties.extend(properties);
+                }
+                ("required", Intermediate::Array(required)) => self.required.extend(required),
+                _ => (),
+            }
+        }
+    }
+
+    /// Merge a given schema of a flattened optional field into the object
+    /// schema.
+    ///
+    /// The value is either described by the schema or it is absent, i.e. none
+    /// of the properties described by the schema are present. Properties of
+    /// plain object schemas are merged directly and their required properties
+    /// are required only if the value is present.
+    #[inline(never)]
+    pub fn flatten_optional(&mut self, schema: Intermediate) {
+        let mut keys = Vec::new();
+
+        collect_property_names(&schema, &mut keys);
+
+        let present = match schema {
+            Intermediate::Map(map) if is_plain_object_schema(&map) => {
+                let mut required = Vec::new();
+
+                for (keyword, value) in map {
+                    match (keyword.as_ref(), value) {
+                        ("properties", Intermediate::Map(properties)) => {
+                            self.properties.extend(properties);
+                        }
+                        ("required", Intermediate::Array(r)) => required = r,
+                        _ => (),
+                    }
+                }
+
+                if required.is_empty() {
+                    return;
+                }
+
+                let mut res = Map::with_capacity(1);
+
+                res.insert_with_static_key("required", Intermediate::Array(required));
+
+                Intermediate::Map(res)
+            }
+            other => other,
+        };
+
+        // an absent value cannot be recognized without any known properties
+        if keys.is_empty() {
+            return;
+        }
+
+        let any_key = keys
+            .into_iter()
+            .map(|key| {
+                let mut res = Map::with_capacity(1);
+
+                res.insert_with_static_key(
+                    "required",
+                    Intermediate::Array(vec![Intermediate::String(key)]),
+                );
+
+                Intermediate::Map(res)
+            })
+            .collect();
+
+        let mut any_of = Map::with_capacity(1);
+
+        any_of.insert_with_static_key("anyOf", Intermediate::Array(any_key));
+
+        let mut absent = Map::with_capacity(1);
+
+        absent.insert_with_static_key("not", Intermediate::Map(any_of));
+
+        let mut res = Map::with_capacity(1);
+
+        res.insert_with_static_key(
+            "anyOf",
+            Intermediate::Array(vec![present, Intermediate::Map(absent)]),
+        );
+
+        self.all_of.push(Intermediate::Map(res));
+    }
+
+    /// Allow or deny properties that are not listed in the schema.
+    #[inline]
+    pub fn additional_properties(&mut self, allow: bool) {
+        self.additional_properties = Some(allow);
+    }
+
+    /// Build the schema.
+    #[inline(never)]
+    pub fn into_schema(self) -> Intermediate {
+        let mut res = Map::with_capacity(4);
+
+        res.insert_with_static_key("type", Intermediate::String(Cow::Borrowed("object")));
+        res.insert_with_static_key("properties", Intermediate::Map(self.properties));
+
+        if !self.required.is_empty() {
+            res.insert_with_static_key("required", Intermediate::Array(self.required));
+        }
+
+        if let Some(allow) = self.additional_properties {
+            res.insert_with_static_key("additionalProperties", Intermediate::Bool(allow));
+        }
+
+        if self.all_of.is_empty() {
+            Intermediate::Map(res)
+        } else {
+            let mut all_of = Vec::with_capacity(self.all_of.len() + 1);
+
+            all_of.push(Intermediate::Map(res));
+            all_of.extend(self.all_of);
+
+            let mut res = Map::with_capacity(1);
+
+            res.insert_with_static_key("allOf", Intermediate::Array(all_of));
+
+            Intermediate::Map(res)
+        }
+    }
+}
+
+impl Default for ObjectSchema {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Builder for JSON Schemas of tuples.
+#[derive(Debug, Clone)]
+pub struct TupleSchema {
+    items: Vec<Intermediate>,
+    min_items: usize,
+}
+
+impl TupleSchema {
+    /// Create a new tuple schema with a given capacity for elements.
+    #[inline(never)]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            items: Vec::with_capacity(capacity),
+            min_items: 0,
+        }
+    }
+
+    /// Add a given element.
+    #[inline(never)]
+    pub fn element(&mut self, schema: Intermediate) {
+        self.items.push(schema);
+        self.min_items = self.items.len();
+    }
+
+    /// Add a given element that may be missing if it is not followed by any
+    /// required elements.
+    #[inline(never)]
+    pub fn optional_element(&mut self, schema: Intermediate) {
+        self.items.push(schema);
+    }
+
+    /// Build the schema.
+    #[inline(never)]
+    pub fn into_schema(self) -> Intermediate {
+        let mut res = Map::with_capacity(3);
+
+        let len = Intermediate::Number(Number::UnsignedInt(self.min_items as _));
+
+        res.insert_with_static_key("type", Intermediate::String(Cow::Borrowed("array")));
+        res.insert_with_static_key("prefixItems", Intermediate::Array(self.items));
+        res.insert_with_static_key("minItems", len);
+
+        Intermediate::Map(res)
+    }
+}
+
+/// Builder for JSON Schemas of enums.
+///
+/// Unit variants are collected into a single string enumeration and all
+/// variants are combined using `oneOf`.
+#[derive(Debug, Clone)]
+pub struct EnumSchema {
+    names: Vec<Intermediate>,
+    variants: Vec<Intermediate>,
+}
+
+impl EnumSchema {
+    /// Create a new enum schema.
+    #[inline(never)]
+    pub fn new() -> Self {
+        Self {
+            names: Vec::new(),
+            variants: Vec::new(),
+        }
+    }
+
+    /// Add a variant represented by its name only.
+    #[inline(never)]
+    pub fn unit_variant(&mut self, name: &'static str) {
+        self.names.push(Intermediate::String(Cow::Borrowed(name)));
+    }
+
+    /// Add a variant with a given schema.
+    #[inline(never)]
+    pub fn variant(&mut self, schema: Intermediate) {
+        self.variants.push(schema);
+    }
+
+    /// Create a schema matching only a given constant string (e.g. an enum
+    /// tag).
+    #[inline(never)]
+    pub fn tag(name: &'static str) -> Intermediate {
+        let mut res = Map::with_capacity(2);
+
+        res.insert_with_static_key("type", Intermediate::String(Cow::Borrowed("string")));
+        res.insert_with_static_key("const", Intermediate::String(Cow::Borrowed(name)));
+
+        Intermediate::Map(res)
+    }
+
+    /// Build the schema.
+    #[inline(never)]
+    pub fn into_schema(self) -> Intermediate {
+        let mut variants = self.variants;
+
+        if !self.names.is_empty() {
+            let mut res = Map::with_capacity(2);
+
+            res.insert_with_static_key("type", Intermediate::String(Cow::Borrowed("string")));
+            res.insert_with_static_key("enum", Intermediate::Array(self.names));
+
+            variants.insert(0, Intermediate::Map(res));
+        }
+
+        if variants.len() == 1 {
+            variants.pop().unwrap()
+        } else {
+            let mut res = Map::with_capacity(1);
+
+            res.insert_with_static_key("oneOf", Intermediate::Array(variants));
+
+            Intermediate::Map(res)
+        }
+    }
+}
+
+impl Default for EnumSchema {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Schema for bool {
+    #[inline]
+    fn schema() -> Intermediate {
+        simple_schema("boolean")
+    }
+}
+
+macro_rules! schema_for_int {
+    ( $x:ty ) => {
+        impl Schema for $x {
+            #[inline]
+            fn schema() -> Intermediate {
+                integer_schema(<$x>::MIN.into(), <$x>::MAX.into())
+            }
+        }
+    };
+}
+
+schema_for_int!(i8);
+schema_for_int!(i16);
+schema_for_int!(i32);
+
+schema_for_int!(u8);
+schema_for_int!(u16);
+schema_for_int!(u32);
+
+macro_rules! schema_for_signed_int {
+    ( $x:ty ) => {
+        impl Schema for $x {
+            #[inline]
+            fn schema() -> Intermediate {
+                simple_schema("integer")
+            }
+        }
+    };
+}
+
+schema_for_signed_int!(i64);
+schema_for_signed_int!(i128);
+schema_for_signed_int!(isize);
+
+macro_rules! schema_for_unsigned_int {
+    ( $x:ty ) => {
+        impl Schema for $x {
+            #[inline]
+            fn schema() -> Intermediate {
+                let mut res = Map::with_capacity(2);
+
+                res.insert_with_static_key("type", Intermediate::String(Cow::Borrowed("integer")));
+                res.insert_with_static_key("minimum", Intermediate::Number(Number::UnsignedInt(0)));
+
+                Intermediate::Map(res)
+            }
+        }
+    };
+}
+
+schema_for_unsigned_int!(u64);
+schema_for_unsigned_int!(u128);
+schema_for_unsigned_int!(usize);
+
+impl Schema for f32 {
+    #[inline]
+    fn schema() -> Intermediate {
+        simple_schema("number")
+    }
+}
+
+impl Schema for f64 {
+    #[inline]
+    fn schema() -> Intermediate {
+        simple_schema("number")
+    }
+}
+
+impl Schema for char {
+    #[inline]
+    fn schema() -> Intermediate {
+        let mut res = Map::with_capacity(3);
+
+        res.insert_with_static_key("type", Intermediate::String(Cow::Borrowed("string")));
+        res.insert_with_static_key("minLength", Intermediate::Number(Number::UnsignedInt(1)));
+        res.insert_with_static_key("maxLength", Intermediate::Number(Number::UnsignedInt(1)));
+
+        Intermediate::Map(res)
+    }
+}
+
+impl Schema for String {
+    #[inline]
+    fn schema() -> Intermediate {
+        simple_schema("string")
+    }
+}
+
+impl Schema for str {
+    #[inline]
+    fn schema() -> Intermediate {
+        simple_schema("string")
+    }
+}
+
+impl<T> Schema for Cow<'_, T>
+where
+    T: Schema + ToOwned + ?Sized,
+{
+    #[inline]
+    fn schema() -> Intermediate {
+        T::schema()
+    }
+}
+
+impl<T> Schema for Option<T>
+where
+    T: Schema,
+{
+    fn schema() -> Intermediate {
+        let mut res = Map::with_capacity(1);
+
+        res.insert_with_static_key(
+            "anyOf",
+            Intermediate::Array(vec![T::schema(), simple_schema("null")]),
+        );
+
+        Intermediate::Map(res)
+    }
+}
+
+impl<T> Schema for [T]
+where
+    T: Schema,
+{
+    #[inline]
+    fn schema() -> Intermediate {
+        array_schema(T::schema(), None)
+    }
+}
+
+impl<T> Schema for Vec<T>
+where
+    T: Schema,
+{
+    #[inline]
+    fn schema() -> Intermediate {
+        array_schema(T::schema(), None)
+    }
+}
+
+impl<T> Schema for VecDeque<T>
+where
+    T: Schema,
+{
+    #[inline]
+    fn schema() -> Intermediate {
+        array_schema(T::schema(), None)
+    }
+}
+
+impl<T> Schema for LinkedList<T>
+where
+    T: Schema,
+{
+    #[inline]
+    fn schema() -> Intermediate {
+        array_schema(T::schema(), None)
+    }
+}
+
+impl<T> Schema for BinaryHeap<T>
+where
+    T: Schema,
+{
+    #[inline]
+    fn schema() -> Intermediate {
+        array_schema(T::schema(), None)
+    }
+}
+
+impl<T, const N: usize> Schema for [T; N]
+where
+    T: Schema,
+{
+    #[inline]
+    fn schema() -> Intermediate {
+        array_schema(T::schema(), Some(N))
+    }
+}
+
+impl Schema for () {
+    #[inline]
+    fn schema() -> Intermediate {
+        let mut res = Map::with_capacity(2);
+
+        res.insert_with_static_key("type", Intermediate::String(Cow::Borrowed("array")));
+        res.insert_with_static_key("maxItems", Intermediate::Number(Number::UnsignedInt(0)));
+
+        Intermediate::Map(res)
+    }
+}
+
+macro_rules! schema_tuple {
+    ( $len:expr => ($($ty:ident)+) ) => {
+        impl<$($ty),+> Schema for ($($ty,)+)
+        where
+            $($ty: Schema,)+
+        {
+            fn schema() -> Intermediate {
+                let mut res = TupleSchema::with_capacity($len);
+
+                $(
+                    res.element($ty::schema());
+                )+
+
+                res.into_schema()
+            }
+        }
+    };
+}
+
+schema_tuple!(1 => (T0));
+schema_tuple!(2 => (T0 T1));
+schema_tuple!(3 => (T0 T1 T2));
+schema_tuple!(4 => (T0 T1 T2 T3));
+schema_tuple!(5 => (T0 T1 T2 T3 T4));
+schema_tuple!(6 => (T0 T1 T2 T3 T4 T5));
+schema_tuple!(7 => (T0 T1 T2 T3 T4 T5 T6));
+schema_tuple!(8 => (T0 T1 T2 T3 T4 T5 T6 T7));
+schema_tuple!(9 => (T0 T1 T2 T3 T4 T5 T6 T7 T8));
+schema_tuple!(10 => (T0 T1 T2 T3 T4 T5 T6 T7 T8 T9));
+schema_tuple!(11 => (T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10));
+schema_tuple!(12 => (T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10 T11));
+schema_tuple!(13 => (T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10 T11 T12));
+schema_tuple!(14 => (T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10 T11 T12 T13));
+schema_tuple!(15 => (T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10 T11 T12 T13 T14));
+schema_tuple!(16 => (T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10 T11 T12 T13 T14 T15));
+
+impl<K, V, S> Schema for HashMap<K, V, S>
+where
+    V: Schema,
+{
+    #[inline]
+    fn schema() -> Intermediate {
+        map_schema(V::schema())
+    }
+}
+
+impl<K, V> Schema for BTreeMap<K, V>
+where
+    V: Schema,
+{
+    #[inline]
+    fn schema() -> Intermediate {
+        map_schema(V::schema())
+    }
+}
+
+#[cfg(feature = "preserve-order")]
+impl<K, V, S> Schema for indexmap::IndexMap<K, V, S>
+where
+    V: Schema,
+{
+    #[inline]
+    fn schema() -> Intermediate {
+        map_schema(V::schema())
+    }
+}
+
+impl<T, S> Schema for HashSet<T, S>
+where
+    T: Schema,
+{
+    #[inline]
+    fn schema() -> Intermediate {
+        set_schema(T::schema())
+    }
+}
+
+impl<T> Schema for BTreeSet<T>
+where
+    T: Schema,
+{
+    #[inline]
+    fn schema() -> Intermediate {
+        set_schema(T::schema())
+    }
+}
+
+#[cfg(feature = "preserve-order")]
+impl<T, S> Schema for indexmap::IndexSet<T, S>
+where
+    T: Schema,
+{
+    #[inline]
+    fn schema() -> Intermediate {
+        set_schema(T::schema())
+    }
+}
+
+impl<T> Schema for Range<T>
+where
+    T: Schema,
+{
+    #[inline]
+    fn schema() -> Intermediate {
+        range_schema(T::schema())
+    }
+}
+
+impl<T> Schema for RangeInclusive<T>
+where
+    T: Schema,
+{
+    #[inline]
+    fn schema() -> Intermediate {
+        range_schema(T::schema())
+    }
+}
+
+impl<T> Schema for Bound<T>
+where
+    T: Schema,
+{
+    fn schema() -> Intermediate {
+        let mut res = EnumSchema::new();
+
+        res.unit_variant("Unbounded");
+
+        for variant in ["Included", "Excluded"] {
+            let mut content = ObjectSchema::with_capacity(1);
+
+            content.property(variant, T::schema(), true);
+            content.additional_properties(false);
+
+            res.variant(content.into_schema());
+        }
+
+        res.into_schema()
+    }
+}
+
+impl Schema for Intermediate {
+    #[inline]
+    fn schema() -> Intermediate {
+        Intermediate::Map(Map::new())
+    }
+}
+
+impl<T> Schema for &T
+where
+    T: Schema + ?Sized,
+{
+    #[inline]
+    fn schema() -> Intermediate {
+        T::schema()
+    }
+}
+
+impl<T> Schema for &mut T
+where
+    T: Schema + ?Sized,
+{
+    #[inline]
+    fn schema() -> Intermediate {
+        T::schema()
+    }
+}
+
+macro_rules! schema_wrapper {
+    ( $x:ident ) => {
+        impl<T> Schema for $x<T>
+        where
+            T: Schema + ?Sized,
+        {
+            #[inline]
+            fn schema() -> Intermediate {
+                T::schema()
+            }
+        }
+    };
+}
+
+schema_wrapper!(Box);
+schema_wrapper!(Rc);
+schema_wrapper!(Arc);
+schema_wrapper!(Cell);
+schema_wrapper!(RefCell);
+schema_wrapper!(Mutex);
+
+/// Helper function.
+fn simple_schema(ty: &'static str) -> Intermediate {
+    let mut res = Map::with_capacity(1);
+
+    res.insert_with_static_key("type", Intermediate::String(Cow::Borrowed(ty)));
+
+    Intermediate::Map(res)
+}
+
+/// Helper function.
+fn integer_schema(min: i64, max: i64) -> Intermediate {
+    let mut res = Map::with_capacity(3);
+
+    res.insert_with_static_key("type", Intermediate::String(Cow::Borrowed("integer")));
+    res.insert_with_static_key("minimum", Intermediate::from(min));
+    res.insert_with_static_key("maximum", Intermediate::from(max));
+
+    Intermediate::Map(res)
+}
+
+/// Helper function.
+fn range_schema(bound: Intermediate) -> Intermediate {
+    let mut res = ObjectSchema::with_capacity(2);
+
+    res.property("start", bound.clone(), true);
+    res.property("end", bound, true);
+
+    res.into_schema()
+}
+
+/// Helper function.
+fn array_schema(items: Intermediate, len: Option<usize>) -> Intermediate {
+    let mut res = Map::with_capacity(4);
+
+    res.insert_with_static_key("type", Intermediate::String(Cow::Borrowed("array")));
+    res.insert_with_static_key("items", items);
+
+    if let Some(len) = len {
+        res.insert_with_static_key(
+            "minItems",
+            Intermediate::Number(Number::UnsignedInt(len as _)),
+        );
+    }
+
+    Intermediate::Map(res)
+}
+
+/// Helper function.
+fn map_schema(values: Intermediate) -> Intermediate {
+    let mut res = Map::with_capacity(2);
+
+    res.insert_with_static_key("type", Intermediate::String(Cow::Borrowed("object")));
+    res.insert_with_static_key("additionalProperties", values);
+
+    Intermediate::Map(res)
+}
+
+/// Helper function.
+fn set_schema(items: Intermediate) -> Intermediate {
+    let mut res = Map::with_capacity(3);
+
+    res.insert_with_static_key("type", Intermediate::String(Cow::Borrowed("array")));
+    res.insert_with_static_key("items", items);
+    res.insert_with_static_key("uniqueItems", Intermediate::Bool(true));
+
+    Intermediate::Map(res)
+}
+
+/// Helper function.
+fn reference_schema(anchor: &str) -> Intermediate {
+    let mut res = Map::with_capacity(1);
+
+    res.insert_with_static_key(
+        "$ref",
+        Intermediate::String(Cow::Owned(format!("#{}", anchor))),
+    );
+
+    Intermediate::Map(res)
+}
+
+/// Collect names of all properties described by a given schema including
+/// properties described by its `allOf`, `anyOf` and `oneOf` subschemas.
+fn collect_property_names(schema: &Intermediate, names: &mut Vec<Cow<'static, str>>) {
+    let schema = match schema.as_map() {
+        Some(schema) => schema,
+        None => return,
+    };
+
+    if let Some(properties) = schema.get("properties").and_then(|p| p.as_map()) {
+        for name in properties.keys() {
+            if !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+    }
+
+    for keyword in ["allOf", "anyOf", "oneOf"] {
+        if let Some(subschemas) = schema.get(keyword).and_then(|s| s.as_array()) {
+            for subschema in subschemas {
+                collect_property_names(subschema, names);
+            }
+        }
+    }
+}
+
+/// Helper function.
+fn is_plain_object_schema(schema: &Map) -> bool {
+    let is_object = schema
+        .get("type")
+        .and_then(|t| t.as_str())
+        .map(|t| t == "object")
+        .unwrap_or(false);
+
+    is_object
+        && schema.contains_key("properties")
+        && schema
+            .keys()
+            .all(|k| matches!(k.as_ref(), "type" | "properties" | "required"))
+}
@@ -1,8 +1,9 @@
 use std::{
     borrow::Cow,
     cell::RefCell,
-    collections::HashMap,
+    collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, LinkedList, VecDeque},
     convert::TryFrom,
+    ops::{Bound, Range, RangeInclusive},
     rc::Rc,
     sync::{Arc, Mutex},
 };
@@ -314,6 +315,101 @@ where
     }
 }
 
+impl<K, V> Serialize for BTreeMap<K, V>
+where
+    K: ToString,
+    V: Serialize,
+{
+    fn serialize(&self) -> Result<Intermediate, Error> {
+        let mut res = Map::with_capacity(self.len());
+
+        for (k, v) in self.iter() {
+            res.insert_with_owned_key(k.to_string(), v.serialize()?);
+        }
+
+        Ok(Intermediate::Map(res))
+    }
+}
+
+macro_rules! serialize_sequence {
+    ( $x:ty ) => {
+        impl<T> Serialize for $x
+        where
+            T: Serialize,
+        {
+            #[inline]
+            fn serialize(&self) -> Result<Intermediate, Error> {
+                serialize_iter(self.iter(), self.len())
+            }
+        }
+    };
+}
+
+serialize_sequence!(BTreeSet<T>);
+serialize_sequence!(VecDeque<T>);
+serialize_sequence!(LinkedList<T>);
+serialize_sequence!(BinaryHeap<T>);
+
+impl<T, S> Serialize for HashSet<T, S>
+where
+    T: Serialize,
+{
+    #[inline]
+    fn serialize(&self) -> Result<Intermediate, Error> {
+        serialize_iter(self.iter(), self.len())
+    }
+}
+
+#[cfg(feature = "preserve-order")]
+impl<T, S> Serialize for indexmap::IndexSet<T, S>
+where
+    T: Serialize,
+{
+    #[inline]
+    fn serialize(&self) -> Result<Intermediate, Error> {
+        serialize_iter(self.iter(), self.len())
+    }
+}
+
+impl<T> Serialize for Range<T>
+where
+    T: Serialize,
+{
+    #[inline]
+    fn serialize(&self) -> Result<Intermediate, Error> {
+        serialize_range(&self.start, &self.end)
+    }
+}
+
+impl<T> Serialize for RangeInclusive<T>
+where
+    T: Serialize,
+{
+    #[inline]
+    fn serialize(&self) -> Result<Intermediate, Error> {
+        serialize_range(self.start(), self.end())
+    }
+}
+
+impl<T> Serialize for Bound<T>
+where
+    T: Serialize,
+{
+    fn serialize(&self) -> Result<Intermediate, Error> {
+        let (variant, value) = match self {
+            Self::Included(v) => ("Included", v),
+            Self::Excluded(v) => ("Excluded", v),
+            Self::Unbounded => return Ok(Intermediate::String(Cow::Borrowed("Unbounded"))),
+        };
+
+        let mut res = Map::with_capacity(1);
+
+        res.insert_with_static_key(variant, value.serialize()?);
+
+        Ok(Intermediate::Map(res))
+    }
+}
+
 impl<'a, T> Serialize for &'a T
 where
     T: Serialize + ?Sized,
@@ -385,3 +481,31 @@ where
 
     Ok(Intermediate::Array(res))
 }
+
+/// Helper function.
+fn serialize_iter<'a, I, T>(iter: I, len: usize) -> Result<Intermediate, Error>
+where
+    I: Iterator<Item = &'a T>,
+    T: Serialize + 'a,
+{
+    let mut res = Vec::with_capacity(len);
+
+    for elem in iter {
+        res.push(elem.serialize()?);
+    }
+
+    Ok(Intermediate::Array(res))
+}
+
+/// Helper function.
+fn serialize_range<T>(start: &T, end: &T) -> Result<Intermediate, Error>
+where
+    T: Serialize,
+{
+    let mut res = Map::with_capacity(2);
+
+    res.insert_with_static_key("start", start.serialize()?);
+    res.insert_with_static_key("end", end.serialize()?);
+
+    Ok(Intermediate::Map(res))
+}
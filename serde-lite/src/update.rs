@@ -1,14 +1,15 @@
 use std::{
+    any::Any,
     borrow::Borrow,
     cell::RefCell,
-    collections::HashMap,
-    hash::Hash,
-    ops::DerefMut,
+    collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, LinkedList, VecDeque},
+    hash::{BuildHasher, Hash},
+    ops::{Bound, DerefMut, Range, RangeInclusive},
     rc::Rc,
     sync::{Arc, Mutex},
 };
 
-use crate::{Deserialize, Error, Intermediate};
+use crate::{ChangeKind, ChangeSet, Deserialize, Error, Intermediate, Serialize, REMOVE_KEYS};
 
 /// Update trait.
 ///
@@ -17,6 +18,143 @@ use crate::{Deserialize, Error, Intermediate};
 pub trait Update: Deserialize {
     /// Update the object.
     fn update(&mut self, val: &Intermediate) -> Result<(), Error>;
+
+    /// Update the object and record the changes into a given change set.
+    ///
+    /// The default implementation records the whole object as written.
+    fn update_tracked(
+        &mut self,
+        val: &Intermediate,
+        changes: &mut ChangeSet<'_>,
+    ) -> Result<(), Error> {
+        self.update(val)?;
+
+        changes.written(val);
+
+        Ok(())
+    }
+
+    /// Update the object and return the changes that were made.
+    ///
+    /// The object is serialized before the update, so that the changes
+    /// contain old values of all written and removed values.
+    fn update_with_changes(&mut self, val: &Intermediate) -> Result<ChangeSet<'static>, Error>
+    where
+        Self: Serialize,
+    {
+        let mut changes = ChangeSet::new();
+
+        changes.set_old_state(self.serialize()?);
+
+        self.update_tracked(val, &mut changes)?;
+
+        Ok(changes)
+    }
+
+    /// Update the object using a given context.
+    ///
+    /// The context is passed to the `update_if` predicates of all updated
+    /// fields including fields of nested objects. Fields with a predicate are
+    /// not updated if the context is of a different type than the predicate
+    /// expects.
+    fn update_with_context<C>(&mut self, val: &Intermediate, context: &C) -> Result<(), Error>
+    where
+        Self: Sized,
+        C: Any,
+    {
+        let mut changes = ChangeSet::disabled();
+
+        changes.set_context(context);
+
+        self.update_tracked(val, &mut changes)
+    }
+
+    /// Update the object and return an inverse patch.
+    ///
+    /// Applying the inverse patch using `apply_inverse` restores the original
+    /// serialized state of the object.
+    fn update_with_inverse(&mut self, val: &Intermediate) -> Result<Intermediate, Error>
+    where
+        Self: Serialize,
+    {
+        let old = self.serialize()?;
+
+        let mut changes = ChangeSet::new();
+
+        changes.set_old_state(old.clone());
+
+        self.update_tracked(val, &mut changes)?;
+
+        Ok(changes.inverse(&old))
+    }
+
+    /// Check if a given value can be used for creating a new object during an
+    /// update.
+    ///
+    /// New objects (e.g. inserted collection elements or enum variants
+    /// switched to) are de-serialized, so the `update_if` predicates of their
+    /// fields must be checked separately. The default implementation permits
+    /// everything.
+    #[doc(hidden)]
+    fn check_permissions(val: &Intermediate, changes: &ChangeSet<'_>) -> Result<(), Error>
+    where
+        Self: Sized,
+    {
+        let _ = (val, changes);
+
+        Ok(())
+    }
+
+    /// Apply a given inverse patch.
+    ///
+    /// Unlike `update`, map updates remove keys listed under the
+    /// `REMOVE_KEYS` key of the patch.
+    fn apply_inverse(&mut self, inverse: &Intermediate) -> Result<(), Error> {
+        let mut changes = ChangeSet::disabled();
+
+        changes.set_inverse(true);
+
+        self.update_tracked(inverse, &mut changes)
+    }
+}
+
+/// Create a new object from a given value during an update.
+///
+/// The `update_if` predicates of all fields of the new object are checked
+/// before the object is de-serialized.
+#[inline]
+pub(crate) fn create<T>(val: &Intermediate, changes: &ChangeSet<'_>) -> Result<T, Error>
+where
+    T: Update,
+{
+    T::check_permissions(val, changes)?;
+    T::deserialize(val)
+}
+
+/// Check permissions of all elements of a given array (if it is an array).
+fn check_elements<T>(val: &Intermediate, changes: &ChangeSet<'_>) -> Result<(), Error>
+where
+    T: Update,
+{
+    for elem in val.as_array().unwrap_or_default() {
+        T::check_permissions(elem, changes)?;
+    }
+
+    Ok(())
+}
+
+/// Check permissions of all values of a given map (if it is a map).
+fn check_values<T>(val: &Intermediate, changes: &ChangeSet<'_>) -> Result<(), Error>
+where
+    T: Update,
+{
+    if let Some(val) = val.as_map() {
+        for value in val.values() {
+            T::check_permissions(value, changes)?;
+        }
+    }
+
+    Ok(())
 }
 
 macro_rules! update_by_replace {
@@ -28,6 +166,26 @@ macro_rules! update_by_replace {
 
                 Ok(())
             }
+
+            fn update_tracked(
+                &mut self,
+                val: &Intermediate,
+                changes: &mut ChangeSet<'_>,
+            ) -> Result<(), Error> {
+                let new = <$x as Deserialize>::deserialize(val)?;
+
+                if changes.is_enabled() && *self != new {
+                    changes.record(
+                        ChangeKind::Modified,
+                        Some(self.serialize()?),
+                        Some(new.serialize()?),
+                    );
+                }
+
+                *self = new;
+
+                Ok(())
+            }
         }
     };
 }
@@ -66,11 +224,42 @@ where
         } else if let Some(inner) = self {
             T::update(inner, val)?;
         } else {
-            *self = T::deserialize(val).map(Some)?;
+            *self = create(val, &ChangeSet::disabled()).map(Some)?;
+        }
+
+        Ok(())
+    }
+
+    fn update_tracked(
+        &mut self,
+        val: &Intermediate,
+        changes: &mut ChangeSet<'_>,
+    ) -> Result<(), Error> {
+        if val.is_none() {
+            if self.is_some() {
+                *self = None;
+
+                changes.written(val);
+            }
+        } else if let Some(inner) = self {
+            T::update_tracked(inner, val, changes)?;
+        } else {
+            *self = create(val, changes).map(Some)?;
+
+            changes.written(val);
         }
 
         Ok(())
     }
+
+    #[inline]
+    fn check_permissions(val: &Intermediate, changes: &ChangeSet<'_>) -> Result<(), Error> {
+        if val.is_none() {
+            Ok(())
+        } else {
+            T::check_permissions(val, changes)
+        }
+    }
 }
 
 impl<T> Update for Vec<T>
@@ -89,7 +278,77 @@ where
                 if let Some(current) = self.get_mut(index) {
                     current.update(elem)?;
                 } else {
-                    self.push(T::deserialize(elem)?);
+                    self.push(create(elem, &ChangeSet::disabled())?);
+                }
+            }
+
+            Ok(())
+        } else {
+            Err(Error::invalid_value_static("array"))
+        }
+    }
+
+    fn update_tracked(
+        &mut self,
+        val: &Intermediate,
+        changes: &mut ChangeSet<'_>,
+    ) -> Result<(), Error> {
+        let val = val
+            .as_array()
+            .ok_or_else(|| Error::invalid_value_static("array"))?;
+
+        for index in val.len()..self.len() {
+            changes.enter_index(index);
+            changes.removed();
+            changes.leave();
+        }
+
+        self.truncate(val.len());
+
+        for (index, elem) in val.iter().enumerate() {
+            changes.enter_index(index);
+
+            let res = if let Some(current) = self.get_mut(index) {
+                current.update_tracked(elem, changes)
+            } else {
+                create(elem, changes).map(|item| {
+                    self.push(item);
+
+                    changes.record(ChangeKind::Inserted, None, Some(elem.clone()));
+                })
+            };
+
+            changes.leave();
+
+            res?;
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn check_permissions(val: &Intermediate, changes: &ChangeSet<'_>) -> Result<(), Error> {
+        check_elements::<T>(val, changes)
+    }
+}
+
+impl<T> Update for VecDeque<T>
+where
+    T: Update,
+{
+    fn update(&mut self, val: &Intermediate) -> Result<(), Error> {
+        if let Some(val) = val.as_array() {
+            match val.len() {
+                len if self.len() > len => self.truncate(len),
+                len if self.len() < len => self.reserve(len - self.len()),
+                _ => (),
+            }
+
+            for (index, elem) in val.iter().enumerate() {
+                if let Some(current) = self.get_mut(index) {
+                    current.update(elem)?;
+                } else {
+                    self.push_back(create(elem, &ChangeSet::disabled())?);
                 }
             }
 
@@ -98,6 +357,156 @@ where
             Err(Error::invalid_value_static("array"))
         }
     }
+
+    fn update_tracked(
+        &mut self,
+        val: &Intermediate,
+        changes: &mut ChangeSet<'_>,
+    ) -> Result<(), Error> {
+        let val = val
+            .as_array()
+            .ok_or_else(|| Error::invalid_value_static("array"))?;
+
+        for index in val.len()..self.len() {
+            changes.enter_index(index);
+            changes.removed();
+            changes.leave();
+        }
+
+        self.truncate(val.len());
+
+        for (index, elem) in val.iter().enumerate() {
+            changes.enter_index(index);
+
+            let res = if let Some(current) = self.get_mut(index) {
+                current.update_tracked(elem, changes)
+            } else {
+                create(elem, changes).map(|item| {
+                    self.push_back(item);
+
+                    changes.record(ChangeKind::Inserted, None, Some(elem.clone()));
+                })
+            };
+
+            changes.leave();
+
+            res?;
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn check_permissions(val: &Intermediate, changes: &ChangeSet<'_>) -> Result<(), Error> {
+        check_elements::<T>(val, changes)
+    }
+}
+
+macro_rules! update_sequence_by_replace {
+    ( $x:ident $( , $bound:path )* ) => {
+        impl<T> Update for $x<T>
+        where
+            T: Deserialize $( + $bound )*,
+        {
+            #[inline]
+            fn update(&mut self, val: &Intermediate) -> Result<(), Error> {
+                *self = Self::deserialize(val)?;
+
+                Ok(())
+            }
+        }
+    };
+}
+
+update_sequence_by_replace!(LinkedList);
+update_sequence_by_replace!(BinaryHeap, Ord);
+
+impl<T> Update for BTreeSet<T>
+where
+    T: Deserialize + Ord,
+{
+    #[inline]
+    fn update(&mut self, val: &Intermediate) -> Result<(), Error> {
+        *self = Self::deserialize(val)?;
+
+        Ok(())
+    }
+
+    #[inline]
+    fn update_tracked(
+        &mut self,
+        val: &Intermediate,
+        changes: &mut ChangeSet<'_>,
+    ) -> Result<(), Error> {
+        replace_set(self, val, changes)
+    }
+}
+
+impl<T, S> Update for HashSet<T, S>
+where
+    T: Deserialize + Eq + Hash,
+    S: BuildHasher + Default,
+{
+    #[inline]
+    fn update(&mut self, val: &Intermediate) -> Result<(), Error> {
+        *self = Self::deserialize(val)?;
+
+        Ok(())
+    }
+
+    #[inline]
+    fn update_tracked(
+        &mut self,
+        val: &Intermediate,
+        changes: &mut ChangeSet<'_>,
+    ) -> Result<(), Error> {
+        replace_set(self, val, changes)
+    }
+}
+
+#[cfg(feature = "preserve-order")]
+impl<T> Update for indexmap::IndexSet<T>
+where
+    T: Deserialize + Eq + Hash,
+{
+    #[inline]
+    fn update(&mut self, val: &Intermediate) -> Result<(), Error> {
+        *self = Self::deserialize(val)?;
+
+        Ok(())
+    }
+
+    #[inline]
+    fn update_tracked(
+        &mut self,
+        val: &Intermediate,
+        changes: &mut ChangeSet<'_>,
+    ) -> Result<(), Error> {
+        replace_set(self, val, changes)
+    }
+}
+
+/// Replace a given set with a de-serialized one.
+///
+/// The change is recorded only if the sets differ, regardless of the order
+/// of the elements.
+fn replace_set<T>(
+    target: &mut T,
+    val: &Intermediate,
+    changes: &mut ChangeSet<'_>,
+) -> Result<(), Error>
+where
+    T: Deserialize + PartialEq,
+{
+    let new = T::deserialize(val)?;
+
+    if *target != new {
+        *target = new;
+
+        changes.written(val);
+    }
+
+    Ok(())
 }
 
 impl<T> Update for [T; 0] {
@@ -134,6 +543,36 @@ macro_rules! update_array {
                     )))
                 }
             }
+
+            fn update_tracked(
+                &mut self,
+                val: &Intermediate,
+                changes: &mut ChangeSet<'_>,
+            ) -> Result<(), Error> {
+                let val = val
+                    .as_array()
+                    .filter(|val| val.len() >= $len)
+                    .ok_or_else(|| {
+                        Error::invalid_value_static(concat!("an array of length ", $len))
+                    })?;
+
+                for (index, elem) in val.iter().take($len).enumerate() {
+                    changes.enter_index(index);
+
+                    let res = self[index].update_tracked(elem, changes);
+
+                    changes.leave();
+
+                    res?;
+                }
+
+                Ok(())
+            }
+
+            #[inline]
+            fn check_permissions(val: &Intermediate, changes: &ChangeSet<'_>) -> Result<(), Error> {
+                check_elements::<T>(val, changes)
+            }
         }
     };
 }
@@ -199,6 +638,44 @@ macro_rules! update_tuple {
                     Err(Error::invalid_value_static(concat!("an array of length ", $len)))
                 }
             }
+
+            fn update_tracked(
+                &mut self,
+                val: &Intermediate,
+                changes: &mut ChangeSet<'_>,
+            ) -> Result<(), Error> {
+                let val = val
+                    .as_array()
+                    .filter(|val| val.len() >= $len)
+                    .ok_or_else(|| {
+                        Error::invalid_value_static(concat!("an array of length ", $len))
+                    })?;
+
+                $(
+                    changes.enter_index($n);
+
+                    let res = self.$n.update_tracked(&val[$n], changes);
+
+                    changes.leave();
+
+                    res?;
+                )+
+
+                Ok(())
+            }
+
+            fn check_permissions(
+                val: &Intermediate,
+                changes: &ChangeSet<'_>,
+            ) -> Result<(), Error> {
+                if let Some(val) = val.as_array().filter(|val| val.len() >= $len) {
+                    $(
+                        $ty::check_permissions(&val[$n], changes)?;
+                    )+
+                }
+
+                Ok(())
+            }
         }
     };
 }
@@ -225,24 +702,131 @@ where
     K: Deserialize + Borrow<str> + Eq + Hash,
     V: Update,
 {
+    #[inline]
     fn update(&mut self, val: &Intermediate) -> Result<(), Error> {
+        self.update_tracked(val, &mut ChangeSet::disabled())
+    }
+
+    fn update_tracked(
+        &mut self,
+        val: &Intermediate,
+        changes: &mut ChangeSet<'_>,
+    ) -> Result<(), Error> {
         let val = val
             .as_map()
             .ok_or_else(|| Error::invalid_value_static("map"))?;
 
+        let removed = changes.removed_keys(val);
+
         for (name, value) in val {
-            if let Some(inner) = self.get_mut(name) {
-                V::update(inner, value)?;
+            if removed.is_some() && *name == REMOVE_KEYS {
+                continue;
+            }
+
+            changes.enter_key(name);
+
+            let res = if let Some(inner) = self.get_mut(name) {
+                V::update_tracked(inner, value, changes)
             } else {
-                let k = Deserialize::deserialize(&Intermediate::String(name.clone()))?;
-                let v = V::deserialize(value)?;
+                Deserialize::deserialize(&Intermediate::String(name.clone())).and_then(|k| {
+                    self.insert(k, create(value, changes)?);
+
+                    changes.record(ChangeKind::Inserted, None, Some(value.clone()));
+
+                    Ok(())
+                })
+            };
+
+            changes.leave();
 
-                self.insert(k, v);
+            res?;
+        }
+
+        for key in removed
+            .unwrap_or_default()
+            .iter()
+            .filter_map(Intermediate::as_str)
+        {
+            if self.remove(key).is_some() {
+                changes.enter_key(key);
+                changes.removed();
+                changes.leave();
             }
         }
 
         Ok(())
     }
+
+    #[inline]
+    fn check_permissions(val: &Intermediate, changes: &ChangeSet<'_>) -> Result<(), Error> {
+        check_values::<V>(val, changes)
+    }
+}
+
+impl<K, V> Update for BTreeMap<K, V>
+where
+    K: Deserialize + Borrow<str> + Ord,
+    V: Update,
+{
+    #[inline]
+    fn update(&mut self, val: &Intermediate) -> Result<(), Error> {
+        self.update_tracked(val, &mut ChangeSet::disabled())
+    }
+
+    fn update_tracked(
+        &mut self,
+        val: &Intermediate,
+        changes: &mut ChangeSet<'_>,
+    ) -> Result<(), Error> {
+        let val = val
+            .as_map()
+            .ok_or_else(|| Error::invalid_value_static("map"))?;
+
+        let removed = changes.removed_keys(val);
+
+        for (name, value) in val {
+            if removed.is_some() && *name == REMOVE_KEYS {
+                continue;
+            }
+
+            changes.enter_key(name);
+
+            let res = if let Some(inner) = self.get_mut(name as &str) {
+                V::update_tracked(inner, value, changes)
+            } else {
+                Deserialize::deserialize(&Intermediate::String(name.clone())).and_then(|k| {
+                    self.insert(k, create(value, changes)?);
+
+                    changes.record(ChangeKind::Inserted, None, Some(value.clone()));
+
+                    Ok(())
+                })
+            };
+
+            changes.leave();
+
+            res?;
+        }
+
+        for key in removed
+            .unwrap_or_default()
+            .iter()
+            .filter_map(Intermediate::as_str)
+        {
+            if self.remove(key).is_some() {
+                changes.enter_key(key);
+                changes.removed();
+                changes.leave();
+            }
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn check_permissions(val: &Intermediate, changes: &ChangeSet<'_>) -> Result<(), Error> {
+        check_values::<V>(val, changes)
+    }
 }
 
 #[cfg(feature = "preserve-order")]
@@ -251,19 +835,174 @@ where
     K: Deserialize + Borrow<str> + Eq + Hash,
     V: Update,
 {
+    #[inline]
     fn update(&mut self, val: &Intermediate) -> Result<(), Error> {
+        self.update_tracked(val, &mut ChangeSet::disabled())
+    }
+
+    fn update_tracked(
+        &mut self,
+        val: &Intermediate,
+        changes: &mut ChangeSet<'_>,
+    ) -> Result<(), Error> {
         let val = val
             .as_map()
             .ok_or_else(|| Error::invalid_value_static("map"))?;
 
+        let removed = changes.removed_keys(val);
+
         for (name, value) in val {
-            if let Some(inner) = self.get_mut(name as &str) {
-                V::update(inner, value)?;
+            if removed.is_some() && *name == REMOVE_KEYS {
+                continue;
+            }
+
+            changes.enter_key(name);
+
+            let res = if let Some(inner) = self.get_mut(name as &str) {
+                V::update_tracked(inner, value, changes)
             } else {
-                let k = Deserialize::deserialize(&Intermediate::String(name.clone()))?;
-                let v = V::deserialize(value)?;
+                Deserialize::deserialize(&Intermediate::String(name.clone())).and_then(|k| {
+                    self.insert(k, create(value, changes)?);
 
-                self.insert(k, v);
+                    changes.record(ChangeKind::Inserted, None, Some(value.clone()));
+
+                    Ok(())
+                })
+            };
+
+            changes.leave();
+
+            res?;
+        }
+
+        for key in removed
+            .unwrap_or_default()
+            .iter()
+            .filter_map(Intermediate::as_str)
+        {
+            if self.shift_remove(key).is_some() {
+                changes.enter_key(key);
+                changes.removed();
+                changes.leave();
+            }
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn check_permissions(val: &Intermediate, changes: &ChangeSet<'_>) -> Result<(), Error> {
+        check_values::<V>(val, changes)
+    }
+}
+
+impl<T> Update for Range<T>
+where
+    T: Update,
+{
+    fn update(&mut self, val: &Intermediate) -> Result<(), Error> {
+        let val = val
+            .as_map()
+            .ok_or_else(|| Error::invalid_value_static("map"))?;
+
+        if let Some(start) = val.get("start") {
+            self.start.update(start)?;
+        }
+
+        if let Some(end) = val.get("end") {
+            self.end.update(end)?;
+        }
+
+        Ok(())
+    }
+
+    fn update_tracked(
+        &mut self,
+        val: &Intermediate,
+        changes: &mut ChangeSet<'_>,
+    ) -> Result<(), Error> {
+        let val = val
+            .as_map()
+            .ok_or_else(|| Error::invalid_value_static("map"))?;
+
+        if let Some(start) = val.get("start") {
+            changes.enter_field("start");
+
+            let res = self.start.update_tracked(start, changes);
+
+            changes.leave();
+
+            res?;
+        }
+
+        if let Some(end) = val.get("end") {
+            changes.enter_field("end");
+
+            let res = self.end.update_tracked(end, changes);
+
+            changes.leave();
+
+            res?;
+        }
+
+        Ok(())
+    }
+
+    fn check_permissions(val: &Intermediate, changes: &ChangeSet<'_>) -> Result<(), Error> {
+        if let Some(val) = val.as_map() {
+            for bound in ["start", "end"] {
+                if let Some(bound) = val.get(bound) {
+                    T::check_permissions(bound, changes)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<T> Update for RangeInclusive<T>
+where
+    T: Deserialize,
+{
+    #[inline]
+    fn update(&mut self, val: &Intermediate) -> Result<(), Error> {
+        *self = Self::deserialize(val)?;
+
+        Ok(())
+    }
+}
+
+impl<T> Update for Bound<T>
+where
+    T: Update,
+{
+    fn update(&mut self, val: &Intermediate) -> Result<(), Error> {
+        let current = match self {
+            Self::Included(current) => val
+                .as_map()
+                .and_then(|val| val.get("Included").map(|content| (current, content))),
+            Self::Excluded(current) => val
+                .as_map()
+                .and_then(|val| val.get("Excluded").map(|content| (current, content))),
+            Self::Unbounded => None,
+        };
+
+        if let Some((current, content)) = current {
+            current.update(content)
+        } else {
+            *self = create(val, &ChangeSet::disabled())?;
+
+            Ok(())
+        }
+    }
+
+    fn check_permissions(val: &Intermediate, changes: &ChangeSet<'_>) -> Result<(), Error> {
+        if let Some(val) = val.as_map() {
+            for bound in ["Included", "Excluded"] {
+                if let Some(content) = val.get(bound) {
+                    T::check_permissions(content, changes)?;
+                }
             }
         }
 
@@ -279,6 +1018,20 @@ where
     fn update(&mut self, val: &Intermediate) -> Result<(), Error> {
         self.deref_mut().update(val)
     }
+
+    #[inline]
+    fn update_tracked(
+        &mut self,
+        val: &Intermediate,
+        changes: &mut ChangeSet<'_>,
+    ) -> Result<(), Error> {
+        self.deref_mut().update_tracked(val, changes)
+    }
+
+    #[inline]
+    fn check_permissions(val: &Intermediate, changes: &ChangeSet<'_>) -> Result<(), Error> {
+        T::check_permissions(val, changes)
+    }
 }
 
 impl<T> Update for Mutex<T>
@@ -289,6 +1042,20 @@ where
     fn update(&mut self, val: &Intermediate) -> Result<(), Error> {
         self.get_mut().unwrap().update(val)
     }
+
+    #[inline]
+    fn update_tracked(
+        &mut self,
+        val: &Intermediate,
+        changes: &mut ChangeSet<'_>,
+    ) -> Result<(), Error> {
+        self.get_mut().unwrap().update_tracked(val, changes)
+    }
+
+    #[inline]
+    fn check_permissions(val: &Intermediate, changes: &ChangeSet<'_>) -> Result<(), Error> {
+        T::check_permissions(val, changes)
+    }
 }
 
 impl<T> Update for Arc<Mutex<T>>
@@ -299,6 +1066,20 @@ where
     fn update(&mut self, val: &Intermediate) -> Result<(), Error> {
         self.lock().unwrap().update(val)
     }
+
+    #[inline]
+    fn update_tracked(
+        &mut self,
+        val: &Intermediate,
+        changes: &mut ChangeSet<'_>,
+    ) -> Result<(), Error> {
+        self.lock().unwrap().update_tracked(val, changes)
+    }
+
+    #[inline]
+    fn check_permissions(val: &Intermediate, changes: &ChangeSet<'_>) -> Result<(), Error> {
+        T::check_permissions(val, changes)
+    }
 }
 
 impl<T> Update for RefCell<T>
@@ -309,6 +1090,20 @@ where
     fn update(&mut self, val: &Intermediate) -> Result<(), Error> {
         self.borrow_mut().update(val)
     }
+
+    #[inline]
+    fn update_tracked(
+        &mut self,
+        val: &Intermediate,
+        changes: &mut ChangeSet<'_>,
+    ) -> Result<(), Error> {
+        self.borrow_mut().update_tracked(val, changes)
+    }
+
+    #[inline]
+    fn check_permissions(val: &Intermediate, changes: &ChangeSet<'_>) -> Result<(), Error> {
+        T::check_permissions(val, changes)
+    }
 }
 
 impl<T> Update for Rc<RefCell<T>>
@@ -319,4 +1114,18 @@ where
     fn update(&mut self, val: &Intermediate) -> Result<(), Error> {
         self.borrow_mut().update(val)
     }
+
+    #[inline]
+    fn update_tracked(
+        &mut self,
+        val: &Intermediate,
+        changes: &mut ChangeSet<'_>,
+    ) -> Result<(), Error> {
+        self.borrow_mut().update_tracked(val, changes)
+    }
+
+    #[inline]
+    fn check_permissions(val: &Intermediate, changes: &ChangeSet<'_>) -> Result<(), Error> {
+        T::check_permissions(val, changes)
+    }
 }
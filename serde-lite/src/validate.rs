@@ -0,0 +1,413 @@
+//! Field validators.
+//!
+//! The functions in this module are used by the derive macros to implement
+//! the `validate` field attribute, e.g.:
+//! ```rust
+//! use serde_lite::{Error, Deserialize};
+//! use serde_lite_derive::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct User {
+//!     #[serde(validate(length(min = 1, max = 32)))]
+//!     name: String,
+//!     #[serde(validate(range(max = 150)))]
+//!     age: Option<u8>,
+//!     #[serde(validate = "validate_email")]
+//!     email: String,
+//! }
+//!
+//! fn validate_email(email: &str) -> Result<(), Error> {
+//!     if email.contains('@') {
+//!         Ok(())
+//!     } else {
+//!         Err(Error::custom_static("invalid email address"))
+//!     }
+//! }
+//! ```
+//!
+//! Absent optional values (i.e. `None`) are always considered valid.
+
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, LinkedList, VecDeque},
+    rc::Rc,
+    sync::Arc,
+};
+
+use crate::{Error, Intermediate, Map};
+
+/// Values that can be validated using the `range` validator.
+pub trait Bounded {
+    /// Type of the bounds.
+    type Bound: PartialOrd;
+
+    /// Get the value (if present).
+    fn value(&self) -> Option<&Self::Bound>;
+}
+
+/// Values that can be validated using the `length` and `non_empty`
+/// validators.
+pub trait Length {
+    /// Get length of the value (if present).
+    ///
+    /// Length of strings is measured in characters.
+    fn length(&self) -> Option<usize>;
+}
+
+/// Values that can be validated using the `regex` validator.
+pub trait Text {
+    /// Get the text (if present).
+    fn text(&self) -> Option<&str>;
+}
+
+/// Check that a given value is within given bounds (inclusive).
+pub fn range<T>(val: &T, min: Option<T::Bound>, max: Option<T::Bound>) -> Result<(), Error>
+where
+    T: Bounded + ?Sized,
+{
+    if let Some(val) = val.value() {
+        let out_of_bounds = min.map(|min| *val < min).unwrap_or(false)
+            || max.map(|max| *val > max).unwrap_or(false);
+
+        if out_of_bounds {
+            return Err(Error::OutOfBounds);
+        }
+    }
+
+    Ok(())
+}
+
+/// Check that length of a given value is within given bounds (inclusive).
+pub fn length<T>(val: &T, min: Option<usize>, max: Option<usize>) -> Result<(), Error>
+where
+    T: Length + ?Sized,
+{
+    if let Some(len) = val.length() {
+        let out_of_bounds =
+            min.map(|min| len < min).unwrap_or(false) || max.map(|max| len > max).unwrap_or(false);
+
+        if out_of_bounds {
+            return Err(Error::custom_static("length is out of bounds"));
+        }
+    }
+
+    Ok(())
+}
+
+/// Check that a given value is not empty.
+pub fn non_empty<T>(val: &T) -> Result<(), Error>
+where
+    T: Length + ?Sized,
+{
+    if val.length() == Some(0) {
+        Err(Error::custom_static("value must not be empty"))
+    } else {
+        Ok(())
+    }
+}
+
+/// Lazily compiled regular expression.
+///
+/// The type is meant to be used in statics, so that the regular expression
+/// gets compiled only once.
+#[cfg(feature = "regex")]
+pub struct Pattern {
+    pattern: &'static str,
+    regex: std::sync::OnceLock<Result<regex::Regex, regex::Error>>,
+}
+
+#[cfg(feature = "regex")]
+impl Pattern {
+    /// Create a new pattern.
+    #[inline]
+    pub const fn new(pattern: &'static str) -> Self {
+        Self {
+            pattern,
+            regex: std::sync::OnceLock::new(),
+        }
+    }
+
+    /// Check if a given string matches the pattern.
+    pub fn is_match(&self, s: &str) -> Result<bool, Error> {
+        self.regex
+            .get_or_init(|| regex::Regex::new(self.pattern))
+            .as_ref()
+            .map(|regex| regex.is_match(s))
+            .map_err(Error::custom)
+    }
+}
+
+/// Check that a given value matches a given pattern.
+#[cfg(feature = "regex")]
+pub fn regex<T>(val: &T, pattern: &Pattern) -> Result<(), Error>
+where
+    T: Text + ?Sized,
+{
+    if let Some(text) = val.text() {
+        if !pattern.is_match(text)? {
+            return Err(Error::custom_static("value does not match the pattern"));
+        }
+    }
+
+    Ok(())
+}
+
+/// Add keywords corresponding to the `range` validator into a given schema.
+pub fn range_schema(
+    schema: &mut Intermediate,
+    min: Option<Intermediate>,
+    max: Option<Intermediate>,
+) {
+    if let Intermediate::Map(schema) = schema {
+        if let Some(min) = min {
+            schema.insert_with_static_key("minimum", min);
+        }
+
+        if let Some(max) = max {
+            schema.insert_with_static_key("maximum", max);
+        }
+    }
+}
+
+/// Add keywords corresponding to the `length` validator into a given schema.
+///
+/// Length keywords for strings, arrays and objects are added because JSON
+/// Schema applies them only to the corresponding types.
+pub fn length_schema(schema: &mut Intermediate, min: Option<usize>, max: Option<usize>) {
+    if let Intermediate::Map(schema) = schema {
+        if let Some(min) = min {
+            insert_length_keywords(schema, ["minLength", "minItems", "minProperties"], min);
+        }
+
+        if let Some(max) = max {
+            insert_length_keywords(schema, ["maxLength", "maxItems", "maxProperties"], max);
+        }
+    }
+}
+
+/// Add keywords corresponding to the `regex` validator into a given schema.
+pub fn regex_schema(schema: &mut Intermediate, pattern: &'static str) {
+    if let Intermediate::Map(schema) = schema {
+        schema.insert_with_static_key("pattern", Intermediate::String(Cow::Borrowed(pattern)));
+    }
+}
+
+/// Helper function.
+fn insert_length_keywords(schema: &mut Map, keywords: [&'static str; 3], len: usize) {
+    for keyword in keywords {
+        schema.insert_with_static_key(keyword, Intermediate::from(len as u64));
+    }
+}
+
+macro_rules! bounded {
+    ( $x:ty ) => {
+        impl Bounded for $x {
+            type Bound = $x;
+
+            #[inline]
+            fn value(&self) -> Option<&Self::Bound> {
+                Some(self)
+            }
+        }
+    };
+}
+
+bounded!(i8);
+bounded!(i16);
+bounded!(i32);
+bounded!(i64);
+bounded!(i128);
+bounded!(isize);
+
+bounded!(u8);
+bounded!(u16);
+bounded!(u32);
+bounded!(u64);
+bounded!(u128);
+bounded!(usize);
+
+bounded!(f32);
+bounded!(f64);
+
+bounded!(char);
+
+impl<T> Length for [T] {
+    #[inline]
+    fn length(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+impl<T> Length for Vec<T> {
+    #[inline]
+    fn length(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+impl Length for str {
+    #[inline]
+    fn length(&self) -> Option<usize> {
+        Some(self.chars().count())
+    }
+}
+
+impl Length for String {
+    #[inline]
+    fn length(&self) -> Option<usize> {
+        Some(self.chars().count())
+    }
+}
+
+impl<K, V, S> Length for HashMap<K, V, S> {
+    #[inline]
+    fn length(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+impl<K, V> Length for BTreeMap<K, V> {
+    #[inline]
+    fn length(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+impl<T, S> Length for HashSet<T, S> {
+    #[inline]
+    fn length(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+macro_rules! sequence_length {
+    ( $x:ident ) => {
+        impl<T> Length for $x<T> {
+            #[inline]
+            fn length(&self) -> Option<usize> {
+                Some(self.len())
+            }
+        }
+    };
+}
+
+sequence_length!(BTreeSet);
+sequence_length!(VecDeque);
+sequence_length!(LinkedList);
+sequence_length!(BinaryHeap);
+
+#[cfg(feature = "preserve-order")]
+impl<K, V, S> Length for indexmap::IndexMap<K, V, S> {
+    #[inline]
+    fn length(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+#[cfg(feature = "preserve-order")]
+impl<T, S> Length for indexmap::IndexSet<T, S> {
+    #[inline]
+    fn length(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+impl Text for str {
+    #[inline]
+    fn text(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl Text for String {
+    #[inline]
+    fn text(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl Text for Cow<'_, str> {
+    #[inline]
+    fn text(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl Length for Cow<'_, str> {
+    #[inline]
+    fn length(&self) -> Option<usize> {
+        Some(self.chars().count())
+    }
+}
+
+impl<T> Bounded for Option<T>
+where
+    T: Bounded,
+{
+    type Bound = T::Bound;
+
+    #[inline]
+    fn value(&self) -> Option<&Self::Bound> {
+        self.as_ref()?.value()
+    }
+}
+
+impl<T> Length for Option<T>
+where
+    T: Length,
+{
+    #[inline]
+    fn length(&self) -> Option<usize> {
+        self.as_ref()?.length()
+    }
+}
+
+impl<T> Text for Option<T>
+where
+    T: Text,
+{
+    #[inline]
+    fn text(&self) -> Option<&str> {
+        self.as_ref()?.text()
+    }
+}
+
+macro_rules! validate_pointer {
+    ( $x:ident ) => {
+        impl<T> Bounded for $x<T>
+        where
+            T: Bounded + ?Sized,
+        {
+            type Bound = T::Bound;
+
+            #[inline]
+            fn value(&self) -> Option<&Self::Bound> {
+                T::value(self)
+            }
+        }
+
+        impl<T> Length for $x<T>
+        where
+            T: Length + ?Sized,
+        {
+            #[inline]
+            fn length(&self) -> Option<usize> {
+                T::length(self)
+            }
+        }
+
+        impl<T> Text for $x<T>
+        where
+            T: Text + ?Sized,
+        {
+            #[inline]
+            fn text(&self) -> Option<&str> {
+                T::text(self)
+            }
+        }
+    };
+}
+
+validate_pointer!(Box);
+validate_pointer!(Rc);
+validate_pointer!(Arc);
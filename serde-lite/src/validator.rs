@@ -0,0 +1,732 @@
+use std::borrow::Cow;
+
+use crate::{Error, ErrorList, Intermediate, NamedFieldError, Number, UnnamedFieldError};
+
+/// JSON Schema validator.
+///
+/// The validator is constructed from a JSON Schema document in the
+/// intermediate representation (e.g. the one returned by
+/// [`Schema::schema`](crate::Schema::schema)) and it can be used for checking
+/// intermediate values before they are deserialized. Validation errors are
+/// reported the same way as deserialization errors, i.e. errors of object
+/// properties are reported as `NamedFieldError`s and errors of array elements
+/// are reported as `UnnamedFieldError`s.
+///
+/// The following keywords are supported: `type`, `enum`, `const`, `minimum`,
+/// `maximum`, `exclusiveMinimum`, `exclusiveMaximum`, `minLength`,
+/// `maxLength`, `pattern` (requires the `regex` feature), `items`,
+/// `prefixItems`, `minItems`, `maxItems`, `uniqueItems`, `properties`,
+/// `required`, `additionalProperties`, `minProperties`, `maxProperties`,
+/// `allOf`, `anyOf`, `oneOf`, `not` and `$ref`. Other keywords are ignored.
+/// References can point only to the schema document itself, i.e. they can
+/// be either `#`, a JSON pointer (e.g. `#/$defs/foo`) or an anchor defined
+/// using `$anchor` (e.g. `#foo`).
+///
+/// Note that the `integer` type matches only integer numbers (i.e. not floats
+/// with zero fractional part) in order to be consistent with deserialization.
+///
+/// # Example
+/// ```rust
+/// use serde_lite::{intermediate, SchemaValidator};
+///
+/// let schema = intermediate!({
+///     "type": "object",
+///     "properties": {
+///         "name": { "type": "string", "minLength": 1 },
+///     },
+///     "required": ["name"],
+/// });
+///
+/// let validator = SchemaValidator::new(&schema).unwrap();
+///
+/// assert!(validator.validate(&intermediate!({ "name": "foo" })).is_ok());
+/// assert!(validator.validate(&intermediate!({ "name": "" })).is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct SchemaValidator {
+    root: Node,
+    references: Vec<Node>,
+}
+
+impl SchemaValidator {
+    /// Create a new validator from a given JSON Schema.
+    ///
+    /// An error is returned if the schema is not valid or if it uses
+    /// unsupported features.
+    pub fn new(schema: &Intermediate) -> Result<Self, Error> {
+        let mut compiler = Compiler {
+            document: schema,
+            references: Vec::new(),
+        };
+
+        let root = Node::new(schema, &mut compiler)?;
+
+        let mut references = Vec::new();
+
+        // compiling a referenced schema may add new references
+        while let Some(reference) = compiler.references.get(references.len()) {
+            let node = resolve_reference(schema, reference)
+                .ok_or_else(|| Error::custom(format!("unresolvable reference: {}", reference)))
+                .and_then(|target| Node::new(target, &mut compiler))
+                .map_err(|err| Error::from(NamedFieldError::new_static("$ref", err)))?;
+
+            references.push(node);
+        }
+
+        let res = Self { root, references };
+
+        Ok(res)
+    }
+
+    /// Validate a given value.
+    pub fn validate(&self, val: &Intermediate) -> Result<(), Error> {
+        self.root.validate(&self.references, val)
+    }
+}
+
+/// Schema compiler state.
+struct Compiler<'a> {
+    /// The whole schema document.
+    document: &'a Intermediate,
+    /// References found in the schema document.
+    references: Vec<String>,
+}
+
+impl Compiler<'_> {
+    /// Get index of a given reference.
+    fn reference(&mut self, reference: &str) -> Result<usize, Error> {
+        if !reference.starts_with('#') {
+            return Err(Error::custom(format!(
+                "unsupported reference: {}",
+                reference
+            )));
+        }
+
+        if let Some(index) = self.references.iter().position(|r| r == reference) {
+            return Ok(index);
+        }
+
+        if resolve_reference(self.document, reference).is_none() {
+            return Err(Error::custom(format!(
+                "unresolvable reference: {}",
+                reference
+            )));
+        }
+
+        self.references.push(reference.to_string());
+
+        Ok(self.references.len() - 1)
+    }
+}
+
+/// JSON types.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Type {
+    Null,
+    Boolean,
+    Integer,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+impl Type {
+    /// Parse a given type name.
+    fn parse(name: &str) -> Result<Self, Error> {
+        let res = match name {
+            "null" => Self::Null,
+            "boolean" => Self::Boolean,
+            "integer" => Self::Integer,
+            "number" => Self::Number,
+            "string" => Self::String,
+            "array" => Self::Array,
+            "object" => Self::Object,
+            _ => return Err(Error::custom(format!("unknown type: {}", name))),
+        };
+
+        Ok(res)
+    }
+
+    /// Get the type name.
+    fn name(self) -> &'static str {
+        match self {
+            Self::Null => "null",
+            Self::Boolean => "boolean",
+            Self::Integer => "integer",
+            Self::Number => "number",
+            Self::String => "string",
+            Self::Array => "array",
+            Self::Object => "object",
+        }
+    }
+
+    /// Check if a given value is of this type.
+    fn matches(self, val: &Intermediate) -> bool {
+        match (self, val) {
+            (Self::Null, Intermediate::None) => true,
+            (Self::Boolean, Intermediate::Bool(_)) => true,
+            (Self::Integer, Intermediate::Number(n)) => !matches!(n, Number::Float(_)),
+            (Self::Number, Intermediate::Number(_)) => true,
+            (Self::String, Intermediate::String(_)) => true,
+            (Self::Array, Intermediate::Array(_)) => true,
+            (Self::Object, Intermediate::Map(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Compiled schema.
+#[derive(Debug, Clone, Default)]
+struct Node {
+    reject: bool,
+    types: Vec<Type>,
+    enum_values: Option<Vec<Intermediate>>,
+    const_value: Option<Intermediate>,
+    minimum: Option<Number>,
+    maximum: Option<Number>,
+    exclusive_minimum: Option<Number>,
+    exclusive_maximum: Option<Number>,
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+    #[cfg(feature = "regex")]
+    pattern: Option<regex::Regex>,
+    items: Option<Box<Node>>,
+    prefix_items: Vec<Node>,
+    min_items: Option<usize>,
+    max_items: Option<usize>,
+    unique_items: bool,
+    properties: Vec<(Cow<'static, str>, Node)>,
+    required: Vec<Cow<'static, str>>,
+    additional_properties: Option<Box<Node>>,
+    min_properties: Option<usize>,
+    max_properties: Option<usize>,
+    all_of: Vec<Node>,
+    any_of: Vec<Node>,
+    one_of: Vec<Node>,
+    not: Option<Box<Node>>,
+    reference: Option<usize>,
+}
+
+impl Node {
+    /// Compile a given schema.
+    fn new(schema: &Intermediate, compiler: &mut Compiler) -> Result<Self, Error> {
+        let schema = match schema {
+            Intermediate::Bool(accept) => {
+                let res = Self {
+                    reject: !accept,
+                    ..Default::default()
+                };
+
+                return Ok(res);
+            }
+            Intermediate::Map(map) => map,
+            _ => return Err(Error::invalid_value_static("schema")),
+        };
+
+        let mut res = Self::default();
+
+        for (keyword, value) in schema {
+            match keyword.as_ref() {
+                "type" => res.types = parse_types(value)?,
+                "enum" => {
+                    let values = value
+                        .as_array()
+                        .ok_or_else(|| invalid_keyword("enum", "array"))?;
+
+                    res.enum_values = Some(values.to_vec());
+                }
+                "const" => res.const_value = Some(value.clone()),
+                "minimum" => res.minimum = Some(parse_number("minimum", value)?),
+                "maximum" => res.maximum = Some(parse_number("maximum", value)?),
+                "exclusiveMinimum" => {
+                    res.exclusive_minimum = Some(parse_number("exclusiveMinimum", value)?);
+                }
+                "exclusiveMaximum" => {
+                    res.exclusive_maximum = Some(parse_number("exclusiveMaximum", value)?);
+                }
+                "minLength" => res.min_length = Some(parse_usize("minLength", value)?),
+                "maxLength" => res.max_length = Some(parse_usize("maxLength", value)?),
+                "pattern" => res.set_pattern(value)?,
+                "items" => res.items = Some(Box::new(parse_subschema("items", value, compiler)?)),
+                "prefixItems" => {
+                    res.prefix_items = parse_subschemas("prefixItems", value, compiler)?
+                }
+                "minItems" => res.min_items = Some(parse_usize("minItems", value)?),
+                "maxItems" => res.max_items = Some(parse_usize("maxItems", value)?),
+                "uniqueItems" => {
+                    res.unique_items = value
+                        .as_bool()
+                        .ok_or_else(|| invalid_keyword("uniqueItems", "boolean"))?;
+                }
+                "properties" => {
+                    let properties = value
+                        .as_map()
+                        .ok_or_else(|| invalid_keyword("properties", "object"))?;
+
+                    for (name, schema) in properties {
+                        let node = Self::new(schema, compiler).map_err(|err| {
+                            Error::from(NamedFieldError::new_static(
+                                "properties",
+                                Error::from(NamedFieldError::new(name, err)),
+                            ))
+                        })?;
+
+                        res.properties.push((name.clone(), node));
+                    }
+                }
+                "required" => {
+                    let required = value
+                        .as_array()
+                        .ok_or_else(|| invalid_keyword("required", "array"))?;
+
+                    for name in required {
+                        let name = name
+                            .as_str()
+                            .ok_or_else(|| invalid_keyword("required", "array of strings"))?;
+
+                        res.required.push(Cow::Owned(name.to_string()));
+                    }
+                }
+                "additionalProperties" => {
+                    let node = parse_subschema("additionalProperties", value, compiler)?;
+
+                    res.additional_properties = Some(Box::new(node));
+                }
+                "minProperties" => {
+                    res.min_properties = Some(parse_usize("minProperties", value)?);
+                }
+                "maxProperties" => {
+                    res.max_properties = Some(parse_usize("maxProperties", value)?);
+                }
+                "allOf" => res.all_of = parse_subschemas("allOf", value, compiler)?,
+                "anyOf" => res.any_of = parse_subschemas("anyOf", value, compiler)?,
+                "oneOf" => res.one_of = parse_subschemas("oneOf", value, compiler)?,
+                "not" => res.not = Some(Box::new(parse_subschema("not", value, compiler)?)),
+                "$ref" => {
+                    let reference = value
+                        .as_str()
+                        .ok_or_else(|| invalid_keyword("$ref", "string"))?;
+
+                    let index = compiler
+                        .reference(reference)
+                        .map_err(|err| Error::from(NamedFieldError::new_static("$ref", err)))?;
+
+                    res.reference = Some(index);
+                }
+                _ => (),
+            }
+        }
+
+        Ok(res)
+    }
+
+    /// Set the pattern.
+    #[cfg(feature = "regex")]
+    fn set_pattern(&mut self, pattern: &Intermediate) -> Result<(), Error> {
+        let pattern = pattern
+            .as_str()
+            .ok_or_else(|| invalid_keyword("pattern", "string"))?;
+
+        let pattern = regex::Regex::new(pattern).map_err(|err| {
+            Error::from(NamedFieldError::new_static("pattern", Error::custom(err)))
+        })?;
+
+        self.pattern = Some(pattern);
+
+        Ok(())
+    }
+
+    /// Set the pattern.
+    #[cfg(not(feature = "regex"))]
+    fn set_pattern(&mut self, _: &Intermediate) -> Result<(), Error> {
+        Err(Error::custom_static(
+            "the pattern keyword requires the regex feature",
+        ))
+    }
+
+    /// Validate a given value.
+    fn validate(&self, references: &[Node], val: &Intermediate) -> Result<(), Error> {
+        if self.reject {
+            return Err(Error::custom_static("value is not allowed"));
+        }
+
+        self.validate_type(val)?;
+        self.validate_value(val)?;
+
+        match val {
+            Intermediate::Number(n) => self.validate_number(*n)?,
+            Intermediate::String(s) => self.validate_string(s)?,
+            Intermediate::Array(arr) => self.validate_array(references, arr)?,
+            Intermediate::Map(_) => self.validate_object(references, val)?,
+            _ => (),
+        }
+
+        self.validate_combinators(references, val)?;
+
+        if let Some(index) = self.reference {
+            references[index].validate(references, val)?;
+        }
+
+        Ok(())
+    }
+
+    /// Validate type of a given value.
+    fn validate_type(&self, val: &Intermediate) -> Result<(), Error> {
+        if self.types.is_empty() || self.types.iter().any(|t| t.matches(val)) {
+            Ok(())
+        } else if self.types.len() == 1 {
+            Err(Error::invalid_value_static(self.types[0].name()))
+        } else {
+            let names = self
+                .types
+                .iter()
+                .map(|t| t.name())
+                .collect::<Vec<_>>()
+                .join(" or ");
+
+            Err(Error::invalid_value(names))
+        }
+    }
+
+    /// Validate a given value against the enum and const keywords.
+    fn validate_value(&self, val: &Intermediate) -> Result<(), Error> {
+        if let Some(values) = self.enum_values.as_ref() {
+            if !values.contains(val) {
+                return Err(Error::custom_static(
+                    "value is not one of the allowed values",
+                ));
+            }
+        }
+
+        if let Some(expected) = self.const_value.as_ref() {
+            if expected != val {
+                return Err(Error::custom_static("value is not the expected constant"));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validate a given number.
+    fn validate_number(&self, n: Number) -> Result<(), Error> {
+        let out_of_bounds = self.minimum.map(|min| n < min).unwrap_or(false)
+            || self.maximum.map(|max| n > max).unwrap_or(false)
+            || self.exclusive_minimum.map(|min| n <= min).unwrap_or(false)
+            || self.exclusive_maximum.map(|max| n >= max).unwrap_or(false);
+
+        if out_of_bounds {
+            Err(Error::OutOfBounds)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Validate a given string.
+    fn validate_string(&self, s: &str) -> Result<(), Error> {
+        if self.min_length.is_some() || self.max_length.is_some() {
+            let len = s.chars().count();
+
+            let out_of_bounds = self.min_length.map(|min| len < min).unwrap_or(false)
+                || self.max_length.map(|max| len > max).unwrap_or(false);
+
+            if out_of_bounds {
+                return Err(Error::custom_static("string length is out of bounds"));
+            }
+        }
+
+        #[cfg(feature = "regex")]
+        if let Some(pattern) = self.pattern.as_ref() {
+            if !pattern.is_match(s) {
+                return Err(Error::custom_static("string does not match the pattern"));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validate a given array.
+    fn validate_array(&self, references: &[Node], arr: &[Intermediate]) -> Result<(), Error> {
+        let out_of_bounds = self.min_items.map(|min| arr.len() < min).unwrap_or(false)
+            || self.max_items.map(|max| arr.len() > max).unwrap_or(false);
+
+        if out_of_bounds {
+            return Err(Error::custom_static("number of items is out of bounds"));
+        }
+
+        if self.unique_items {
+            for (index, item) in arr.iter().enumerate() {
+                if arr[..index].contains(item) {
+                    return Err(Error::custom_static("array items are not unique"));
+                }
+            }
+        }
+
+        let mut field_errors = ErrorList::new();
+
+        for (index, item) in arr.iter().enumerate() {
+            let schema = self.prefix_items.get(index).or(self.items.as_deref());
+
+            if let Some(schema) = schema {
+                if let Err(err) = schema.validate(references, item) {
+                    field_errors.push(UnnamedFieldError::new(index, err));
+                }
+            }
+        }
+
+        if field_errors.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::UnnamedFieldErrors(field_errors))
+        }
+    }
+
+    /// Validate a given object.
+    fn validate_object(&self, references: &[Node], val: &Intermediate) -> Result<(), Error> {
+        let obj = val.as_map().unwrap();
+
+        let out_of_bounds = self
+            .min_properties
+            .map(|min| obj.len() < min)
+            .unwrap_or(false)
+            || self
+                .max_properties
+                .map(|max| obj.len() > max)
+                .unwrap_or(false);
+
+        if out_of_bounds {
+            return Err(Error::custom_static(
+                "number of properties is out of bounds",
+            ));
+        }
+
+        let mut field_errors = ErrorList::new();
+
+        for name in &self.required {
+            if !obj.contains_key(name) {
+                field_errors.push(NamedFieldError::new(name, Error::MissingField));
+            }
+        }
+
+        for (name, value) in obj {
+            let schema = self
+                .properties
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, schema)| schema);
+
+            let res = if let Some(schema) = schema {
+                schema.validate(references, value)
+            } else if let Some(schema) = self.additional_properties.as_ref() {
+                if schema.reject {
+                    Err(Error::custom_static("unknown field"))
+                } else {
+                    schema.validate(references, value)
+                }
+            } else {
+                Ok(())
+            };
+
+            if let Err(err) = res {
+                field_errors.push(NamedFieldError::new(name, err));
+            }
+        }
+
+        if field_errors.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::NamedFieldErrors(field_errors))
+        }
+    }
+
+    /// Validate a given value against the schema combinators.
+    fn validate_combinators(&self, references: &[Node], val: &Intermediate) -> Result<(), Error> {
+        let mut named_errors = ErrorList::new();
+        let mut unnamed_errors = ErrorList::new();
+
+        for schema in &self.all_of {
+            match schema.validate(references, val) {
+                Ok(()) => (),
+                Err(Error::NamedFieldErrors(errors)) => named_errors.append(errors),
+                Err(Error::UnnamedFieldErrors(errors)) => unnamed_errors.append(errors),
+                Err(err) => return Err(err),
+            }
+        }
+
+        if !named_errors.is_empty() {
+            return Err(Error::NamedFieldErrors(named_errors));
+        } else if !unnamed_errors.is_empty() {
+            return Err(Error::UnnamedFieldErrors(unnamed_errors));
+        }
+
+        if !self.any_of.is_empty() {
+            validate_alternatives(references, &self.any_of, val, false)?;
+        }
+
+        if !self.one_of.is_empty() {
+            validate_alternatives(references, &self.one_of, val, true)?;
+        }
+
+        if let Some(schema) = self.not.as_ref() {
+            if schema.validate(references, val).is_ok() {
+                return Err(Error::custom_static("value is not allowed"));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Validate a given value against a given list of alternatives.
+///
+/// If none of the alternatives matches, the error of the first alternative
+/// that failed only because of its fields is returned as it is most likely
+/// the one that was intended.
+fn validate_alternatives(
+    references: &[Node],
+    alternatives: &[Node],
+    val: &Intermediate,
+    exactly_one: bool,
+) -> Result<(), Error> {
+    let mut matches = 0;
+    let mut field_error = None;
+
+    for schema in alternatives {
+        match schema.validate(references, val) {
+            Ok(()) => matches += 1,
+            Err(err @ Error::NamedFieldErrors(_)) | Err(err @ Error::UnnamedFieldErrors(_)) => {
+                if field_error.is_none() {
+                    field_error = Some(err);
+                }
+            }
+            Err(_) => (),
+        }
+    }
+
+    if matches == 0 {
+        Err(field_error.unwrap_or_else(|| {
+            Error::custom_static("value does not match any of the allowed schemas")
+        }))
+    } else if exactly_one && matches > 1 {
+        Err(Error::custom_static(
+            "value matches more than one of the allowed schemas",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Helper function.
+fn invalid_keyword(keyword: &'static str, expected: &'static str) -> Error {
+    Error::from(NamedFieldError::new_static(
+        keyword,
+        Error::invalid_value_static(expected),
+    ))
+}
+
+/// Helper function.
+fn parse_types(value: &Intermediate) -> Result<Vec<Type>, Error> {
+    let parse = |name: &Intermediate| {
+        name.as_str()
+            .ok_or_else(|| invalid_keyword("type", "string"))
+            .and_then(|name| {
+                Type::parse(name)
+                    .map_err(|err| Error::from(NamedFieldError::new_static("type", err)))
+            })
+    };
+
+    if let Some(names) = value.as_array() {
+        names.iter().map(parse).collect()
+    } else {
+        parse(value).map(|t| vec![t])
+    }
+}
+
+/// Helper function.
+fn parse_number(keyword: &'static str, value: &Intermediate) -> Result<Number, Error> {
+    value
+        .as_number()
+        .ok_or_else(|| invalid_keyword(keyword, "number"))
+}
+
+/// Helper function.
+fn parse_usize(keyword: &'static str, value: &Intermediate) -> Result<usize, Error> {
+    value
+        .as_number()
+        .ok_or_else(|| Error::invalid_value_static("unsigned integer"))
+        .and_then(|n| n.try_into())
+        .map_err(|err| Error::from(NamedFieldError::new_static(keyword, err)))
+}
+
+/// Helper function.
+fn parse_subschema(
+    keyword: &'static str,
+    value: &Intermediate,
+    compiler: &mut Compiler,
+) -> Result<Node, Error> {
+    Node::new(value, compiler).map_err(|err| Error::from(NamedFieldError::new_static(keyword, err)))
+}
+
+/// Helper function.
+fn parse_subschemas(
+    keyword: &'static str,
+    value: &Intermediate,
+    compiler: &mut Compiler,
+) -> Result<Vec<Node>, Error> {
+    let schemas = value
+        .as_array()
+        .ok_or_else(|| invalid_keyword(keyword, "array"))?;
+
+    let mut res = Vec::with_capacity(schemas.len());
+
+    for (index, schema) in schemas.iter().enumerate() {
+        let node = Node::new(schema, compiler).map_err(|err| {
+            Error::from(NamedFieldError::new_static(
+                keyword,
+                Error::from(UnnamedFieldError::new(index, err)),
+            ))
+        })?;
+
+        res.push(node);
+    }
+
+    Ok(res)
+}
+
+/// Find the schema a given reference points to.
+///
+/// The reference can be either `#`, a JSON pointer or an anchor.
+fn resolve_reference<'a>(document: &'a Intermediate, reference: &str) -> Option<&'a Intermediate> {
+    let fragment = reference.strip_prefix('#')?;
+
+    if fragment.is_empty() {
+        Some(document)
+    } else if let Some(pointer) = fragment.strip_prefix('/') {
+        pointer.split('/').try_fold(document, |val, token| {
+            let token = token.replace("~1", "/").replace("~0", "~");
+
+            match val {
+                Intermediate::Map(map) => map.get(token.as_str()),
+                Intermediate::Array(arr) => token.parse::<usize>().ok().and_then(|i| arr.get(i)),
+                _ => None,
+            }
+        })
+    } else {
+        find_anchor(document, fragment)
+    }
+}
+
+/// Find a subschema with a given anchor.
+fn find_anchor<'a>(val: &'a Intermediate, anchor: &str) -> Option<&'a Intermediate> {
+    match val {
+        Intermediate::Map(map) => {
+            if map.get("$anchor").and_then(|a| a.as_str()) == Some(anchor) {
+                Some(val)
+            } else {
+                map.values().find_map(|val| find_anchor(val, anchor))
+            }
+        }
+        Intermediate::Array(arr) => arr.iter().find_map(|val| find_anchor(val, anchor)),
+        _ => None,
+    }
+}
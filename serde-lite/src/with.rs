@@ -0,0 +1,54 @@
+//! Helpers used by the derive macros to implement the `with` attribute.
+//!
+//! The module is not a part of the public API.
+
+use crate::{Error, Intermediate};
+
+/// Fallback update function used if the module given in the `with` attribute
+/// does not provide its own `update` function.
+///
+/// The derive macros import this function into an outer scope and the
+/// content of the module into an inner scope, so that the `update` function
+/// of the module takes precedence.
+#[inline]
+pub fn update<'a, T>(val: &'a mut T, input: &'a Intermediate) -> Fallback<'a, T> {
+    Fallback { val, input }
+}
+
+/// Pending update that will be done by replacing the current value with a
+/// de-serialized one.
+pub struct Fallback<'a, T> {
+    val: &'a mut T,
+    input: &'a Intermediate,
+}
+
+/// Common interface for results of custom `update` functions and the
+/// fallback update.
+pub trait UpdateResult<T> {
+    /// Finish the update using a given deserialize function if needed.
+    fn finish<F>(self, deserialize: F) -> Result<(), Error>
+    where
+        F: FnOnce(&Intermediate) -> Result<T, Error>;
+}
+
+impl<T> UpdateResult<T> for Result<(), Error> {
+    #[inline]
+    fn finish<F>(self, _: F) -> Result<(), Error>
+    where
+        F: FnOnce(&Intermediate) -> Result<T, Error>,
+    {
+        self
+    }
+}
+
+impl<T> UpdateResult<T> for Fallback<'_, T> {
+    #[inline]
+    fn finish<F>(self, deserialize: F) -> Result<(), Error>
+    where
+        F: FnOnce(&Intermediate) -> Result<T, Error>,
+    {
+        *self.val = deserialize(self.input)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,120 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, BinaryHeap, HashSet, LinkedList, VecDeque},
+    fmt::Debug,
+    ops::{Bound, Range, RangeInclusive},
+};
+
+use serde_lite::{intermediate, Deserialize, Error, Serialize, Update};
+
+#[test]
+fn test_collections_round_trip() {
+    let map = BTreeMap::from([(1, "foo".to_string()), (2, "bar".to_string())]);
+
+    assert_eq!(
+        map.serialize().unwrap(),
+        intermediate!({ "1": "foo", "2": "bar" })
+    );
+
+    round_trip(map);
+    round_trip(BTreeSet::from([3, 1, 2]));
+    round_trip(HashSet::<_>::from([3, 1, 2]));
+    round_trip(VecDeque::from([3, 1, 2]));
+    round_trip(LinkedList::from([3, 1, 2]));
+
+    let heap = BinaryHeap::from([3, 1, 2]);
+    let res = BinaryHeap::<u32>::deserialize(&heap.serialize().unwrap()).unwrap();
+
+    assert_eq!(res.into_sorted_vec(), [1, 2, 3]);
+}
+
+#[test]
+fn test_ranges_round_trip() {
+    assert_eq!(
+        (1..5).serialize().unwrap(),
+        intermediate!({ "start": 1, "end": 5 })
+    );
+    assert_eq!(
+        (1..=5).serialize().unwrap(),
+        intermediate!({ "start": 1, "end": 5 })
+    );
+
+    round_trip::<Range<u32>>(1..5);
+    round_trip::<RangeInclusive<u32>>(1..=5);
+
+    assert_eq!(
+        Bound::Included(1).serialize().unwrap(),
+        intermediate!({ "Included": 1 })
+    );
+    assert_eq!(
+        Bound::<u32>::Unbounded.serialize().unwrap(),
+        intermediate!("Unbounded")
+    );
+
+    round_trip(Bound::Included(1));
+    round_trip(Bound::Excluded(1));
+    round_trip(Bound::<u32>::Unbounded);
+
+    assert!(matches!(
+        Bound::<u32>::deserialize(&intermediate!("Included")),
+        Err(Error::MissingEnumVariantContent)
+    ));
+    assert!(matches!(
+        Bound::<u32>::deserialize(&intermediate!({ "Foo": 1 })),
+        Err(Error::UnknownEnumVariant)
+    ));
+}
+
+#[test]
+fn test_collections_update() {
+    let mut map = BTreeMap::from([("a".to_string(), 1), ("b".to_string(), 2)]);
+
+    map.update(&intermediate!({ "b": 3, "c": 4 })).unwrap();
+
+    assert_eq!(
+        map,
+        BTreeMap::from([
+            ("a".to_string(), 1),
+            ("b".to_string(), 3),
+            ("c".to_string(), 4),
+        ])
+    );
+
+    let mut deque = VecDeque::from([1, 2, 3]);
+
+    deque.update(&intermediate!([4, 5])).unwrap();
+
+    assert_eq!(deque, [4, 5]);
+
+    let mut set = BTreeSet::from([1, 2]);
+
+    set.update(&intermediate!([3])).unwrap();
+
+    assert_eq!(set, BTreeSet::from([3]));
+
+    let mut range = 1..5;
+
+    range.update(&intermediate!({ "end": 10 })).unwrap();
+
+    assert_eq!(range, 1..10);
+
+    let mut bound = Bound::Included(1);
+
+    bound.update(&intermediate!({ "Included": 2 })).unwrap();
+
+    assert_eq!(bound, Bound::Included(2));
+
+    bound.update(&intermediate!("Unbounded")).unwrap();
+
+    assert_eq!(bound, Bound::Unbounded);
+}
+
+/// Helper.
+fn round_trip<T>(val: T)
+where
+    T: Serialize + Deserialize + PartialEq + Debug,
+{
+    let intermediate = val.serialize().unwrap();
+    let res = T::deserialize(&intermediate).unwrap();
+
+    assert_eq!(res, val);
+}
@@ -1,4 +1,4 @@
-use std::convert::TryInto;
+use std::convert::{TryFrom, TryInto};
 
 use serde_lite::{intermediate, Deserialize, Error, Intermediate, Map, Number, Serialize, Update};
 
@@ -73,6 +73,336 @@ fn test_struct_update() {
     assert!(instance.update(&Intermediate::None).is_err());
 }
 
+#[test]
+fn test_flattened_map() {
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize, Update)]
+    struct Config {
+        name: String,
+        #[serde(flatten)]
+        extra: BTreeMap<String, Intermediate>,
+    }
+
+    let input = intermediate!({
+        "name": "foo",
+        "color": "red",
+        "size": 3,
+    });
+
+    let mut config = Config::deserialize(&input).unwrap();
+
+    assert_eq!(config.name, "foo");
+    assert_eq!(config.extra.len(), 2);
+    assert_eq!(config.extra["color"], intermediate!("red"));
+    assert_eq!(config.extra["size"], intermediate!(3));
+
+    assert_eq!(config.serialize().unwrap(), input);
+
+    config
+        .update(&intermediate!({
+            "name": "bar",
+            "shape": "round",
+        }))
+        .unwrap();
+
+    assert_eq!(config.name, "bar");
+    assert_eq!(config.extra.len(), 3);
+    assert_eq!(config.extra["shape"], intermediate!("round"));
+
+    // named fields take precedence over the flattened ones
+    config
+        .extra
+        .insert(String::from("name"), intermediate!("baz"));
+
+    assert_eq!(
+        config.serialize().unwrap(),
+        intermediate!({
+            "name": "bar",
+            "color": "red",
+            "shape": "round",
+            "size": 3,
+        })
+    );
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize, Update)]
+    #[serde(tag = "type")]
+    enum Message {
+        Request {
+            id: u32,
+            #[serde(flatten)]
+            extra: BTreeMap<String, Intermediate>,
+        },
+    }
+
+    let input = intermediate!({
+        "type": "Request",
+        "id": 1,
+        "method": "get",
+    });
+
+    let mut message = Message::deserialize(&input).unwrap();
+
+    let Message::Request { id, extra } = &message;
+
+    assert_eq!(*id, 1);
+    assert_eq!(extra.len(), 1);
+    assert_eq!(extra["method"], intermediate!("get"));
+
+    assert_eq!(message.serialize().unwrap(), input);
+
+    message
+        .update(&intermediate!({
+            "type": "Request",
+            "path": "/",
+        }))
+        .unwrap();
+
+    let Message::Request { extra, .. } = &message;
+
+    assert_eq!(extra.len(), 2);
+    assert!(!extra.contains_key("type"));
+}
+
+#[test]
+fn test_flattened_siblings() {
+    use std::collections::HashMap;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize, Update)]
+    struct Settings {
+        name: String,
+        #[serde(flatten)]
+        common: Common,
+        #[serde(flatten)]
+        extra: HashMap<String, Intermediate>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize, Update)]
+    struct Common {
+        a: u32,
+    }
+
+    let input = intermediate!({
+        "name": "x",
+        "a": 1,
+        "zzz": true,
+    });
+
+    let mut settings = Settings::deserialize(&input).unwrap();
+
+    assert_eq!(settings.common, Common { a: 1 });
+    assert_eq!(settings.extra.len(), 1);
+    assert_eq!(settings.extra["zzz"], intermediate!(true));
+
+    assert_eq!(settings.serialize().unwrap(), input);
+
+    assert!(settings.update(&intermediate!({ "a": "str" })).is_err());
+    assert!(!settings.extra.contains_key("a"));
+
+    settings
+        .update(&intermediate!({ "a": 2, "yyy": 3 }))
+        .unwrap();
+
+    assert_eq!(settings.common, Common { a: 2 });
+    assert_eq!(settings.extra.len(), 2);
+    assert!(!settings.extra.contains_key("a"));
+
+    // earlier flattened fields take precedence over the following ones
+    settings
+        .extra
+        .insert(String::from("a"), intermediate!("str"));
+
+    assert_eq!(
+        settings.serialize().unwrap(),
+        intermediate!({
+            "name": "x",
+            "a": 2,
+            "yyy": 3,
+            "zzz": true,
+        })
+    );
+
+    #[derive(Debug, PartialEq, Deserialize, Update)]
+    struct Tagged {
+        #[serde(flatten)]
+        extra: HashMap<String, Intermediate>,
+        #[serde(flatten)]
+        payload: Payload,
+        #[serde(flatten)]
+        location: Option<Location>,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize, Update)]
+    #[serde(tag = "kind")]
+    enum Payload {
+        Key { code: u32 },
+        Click(Location),
+    }
+
+    #[derive(Debug, PartialEq, Deserialize, Update)]
+    struct Location {
+        #[serde(rename = "latitude")]
+        lat: f64,
+        lon: f64,
+    }
+
+    let val = Tagged::deserialize(&intermediate!({
+        "kind": "Key",
+        "code": 1,
+        "latitude": 1.5,
+        "lon": 2.5,
+        "other": 3,
+    }))
+    .unwrap();
+
+    assert_eq!(val.extra.len(), 1);
+    assert_eq!(val.extra["other"], intermediate!(3));
+    assert_eq!(val.payload, Payload::Key { code: 1 });
+    assert_eq!(val.location, Some(Location { lat: 1.5, lon: 2.5 }));
+}
+
+#[test]
+fn test_flattened_enum_and_option() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize, Update)]
+    struct Event {
+        id: u32,
+        #[serde(flatten)]
+        payload: Payload,
+        #[serde(flatten)]
+        location: Option<Location>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize, Update)]
+    #[serde(tag = "kind")]
+    enum Payload {
+        Click { x: i32, y: i32 },
+        Key { code: u32 },
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize, Update)]
+    struct Location {
+        lat: f64,
+        lon: f64,
+    }
+
+    let input = intermediate!({
+        "id": 1,
+        "kind": "Click",
+        "x": 10,
+        "y": 20,
+    });
+
+    let mut event = Event::deserialize(&input).unwrap();
+
+    assert_eq!(
+        event,
+        Event {
+            id: 1,
+            payload: Payload::Click { x: 10, y: 20 },
+            location: None,
+        }
+    );
+
+    assert_eq!(event.serialize().unwrap(), input);
+
+    let input = intermediate!({
+        "id": 2,
+        "kind": "Key",
+        "code": 13,
+        "lat": 1.5,
+        "lon": 2.5,
+    });
+
+    let output = Event::deserialize(&input).unwrap();
+
+    assert_eq!(output.payload, Payload::Key { code: 13 });
+    assert_eq!(output.location, Some(Location { lat: 1.5, lon: 2.5 }));
+    assert_eq!(output.serialize().unwrap(), input);
+
+    // the optional struct is present if any of its keys is present, so a
+    // partially present struct reports its missing fields
+    let err = Event::deserialize(&intermediate!({
+        "id": 3,
+        "kind": "Key",
+        "code": 13,
+        "lat": 1.5,
+    }))
+    .unwrap_err();
+
+    if let Error::NamedFieldErrors(errors) = err {
+        assert_eq!(errors.len(), 1);
+
+        let err = errors.iter().next().unwrap();
+
+        assert_eq!(err.field(), "lon");
+        assert!(matches!(err.error(), Error::MissingField));
+    } else {
+        panic!("unexpected error: {:?}", err);
+    }
+
+    // a struct with default values for all its fields is absent if none of
+    // its keys are present
+    #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+    struct Paging {
+        #[serde(default)]
+        page: u32,
+        #[serde(default)]
+        size: u32,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Query {
+        id: u32,
+        #[serde(flatten)]
+        paging: Option<Paging>,
+    }
+
+    let input = intermediate!({ "id": 1 });
+
+    let query = Query::deserialize(&input).unwrap();
+
+    assert_eq!(query.paging, None);
+    assert_eq!(query.serialize().unwrap(), input);
+
+    let query = Query::deserialize(&intermediate!({ "id": 1, "size": 10 })).unwrap();
+
+    assert_eq!(query.paging, Some(Paging { page: 0, size: 10 }));
+
+    event
+        .update(&intermediate!({
+            "y": 30,
+            "lat": 0.5,
+            "lon": 0.25,
+        }))
+        .unwrap();
+
+    assert_eq!(event.payload, Payload::Click { x: 10, y: 30 });
+    assert_eq!(
+        event.location,
+        Some(Location {
+            lat: 0.5,
+            lon: 0.25
+        })
+    );
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Wrapper {
+        #[serde(flatten)]
+        payload: Option<Payload>,
+    }
+
+    assert_eq!(
+        Wrapper::deserialize(&intermediate!({})).unwrap(),
+        Wrapper { payload: None }
+    );
+    assert_eq!(
+        Wrapper::deserialize(&intermediate!({ "kind": "Key", "code": 1 })).unwrap(),
+        Wrapper {
+            payload: Some(Payload::Key { code: 1 })
+        }
+    );
+}
+
 #[test]
 fn test_tuple_struct_deserialize() {
     let input1 = intermediate!(10);
@@ -914,56 +1244,2059 @@ fn test_update_with() {
     assert_eq!(val.field.0, 15);
 }
 
-#[test]
-fn test_deserialize_from() {
-    #[derive(Deserialize)]
-    #[serde(from = "Delegation")]
-    struct Custom(u32);
+mod as_string {
+    use serde_lite::{Error, Intermediate};
 
-    #[derive(Deserialize)]
-    struct Delegation(u32);
+    pub fn serialize(val: &u32) -> Result<Intermediate, Error> {
+        Ok(Intermediate::from(val.to_string()))
+    }
 
-    impl From<Delegation> for Custom {
-        fn from(d: Delegation) -> Self {
-            Self(d.0 + 1)
-        }
+    pub fn deserialize(val: &Intermediate) -> Result<u32, Error> {
+        val.as_str()
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| Error::invalid_value_static("number string"))
     }
+}
 
-    let input = intermediate!(12);
+mod as_pair {
+    use serde_lite::{Deserialize, Error, Intermediate, Serialize};
 
-    let val = Custom::deserialize(&input).unwrap();
-    assert_eq!(val.0, 13);
+    pub fn serialize(a: &u32, b: &u32) -> Result<Intermediate, Error> {
+        [*a, *b].serialize()
+    }
+
+    pub fn deserialize(val: &Intermediate) -> Result<(u32, u32), Error> {
+        let [a, b] = <[u32; 2]>::deserialize(val)?;
+
+        Ok((a, b))
+    }
 }
 
 #[test]
-fn test_default_function() {
-    #[derive(Deserialize, PartialEq, Debug)]
-    enum A {
-        Variant {
-            #[serde(default)]
-            foo: usize,
+fn test_restricted_updates() {
+    #[derive(Debug, PartialEq, Deserialize, Update)]
+    struct Resource {
+        #[serde(read_only)]
+        id: u32,
+        #[serde(skip_updating)]
+        created_at: u64,
+        #[serde(update_once)]
+        owner: Option<String>,
+        name: String,
+    }
 
-            #[serde(default = "default_bar")]
-            bar: String,
-        },
+    let mut resource = Resource::deserialize(&intermediate!({
+        "id": 1,
+        "created_at": 1000,
+        "owner": null,
+        "name": "foo",
+    }))
+    .unwrap();
+
+    assert_eq!(resource.id, 1);
+    assert_eq!(resource.created_at, 1000);
+
+    resource
+        .update(&intermediate!({
+            "created_at": 2000,
+            "owner": "alice",
+            "name": "bar",
+        }))
+        .unwrap();
+
+    assert_eq!(resource.created_at, 1000);
+    assert_eq!(resource.owner.as_deref(), Some("alice"));
+    assert_eq!(resource.name, "bar");
+
+    let err = resource
+        .update(&intermediate!({
+            "id": 2,
+            "owner": "bob",
+            "name": "baz",
+        }))
+        .unwrap_err();
+
+    if let Error::NamedFieldErrors(errors) = err {
+        let fields = errors.iter().map(|err| err.field()).collect::<Vec<_>>();
+
+        assert_eq!(fields, ["id", "owner"]);
+    } else {
+        panic!("unexpected error");
     }
 
-    fn default_bar() -> String {
-        "hello".to_string()
+    assert_eq!(resource.id, 1);
+    assert_eq!(resource.owner.as_deref(), Some("alice"));
+
+    #[derive(Debug, PartialEq, Deserialize, Update)]
+    struct Tuple(#[serde(skip_updating)] u32, u32);
+
+    let mut tuple = Tuple(1, 2);
+
+    tuple.update(&intermediate!([3, 4])).unwrap();
+
+    assert_eq!(tuple, Tuple(1, 4));
+}
+
+#[test]
+fn test_update_by_key() {
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Update)]
+    struct Item {
+        id: u32,
+        name: String,
+        #[serde(default)]
+        count: u32,
     }
 
-    let input = intermediate!({
-        "Variant": {},
-    });
+    #[derive(Debug, PartialEq, Serialize, Deserialize, Update)]
+    struct Order {
+        #[serde(update_by_key = "id")]
+        items: Vec<Item>,
+        #[serde(update_by_key(key = "id", unmatched = "remove"))]
+        replaced: Vec<Item>,
+    }
+
+    let items = intermediate!([
+        { "id": 1, "name": "foo", "count": 1 },
+        { "id": 2, "name": "bar", "count": 2 },
+    ]);
+
+    let mut input = Map::new();
+
+    input.insert_with_static_key("items", items.clone());
+    input.insert_with_static_key("replaced", items);
+
+    let mut order = Order::deserialize(&Intermediate::Map(input)).unwrap();
+
+    let changes = intermediate!([
+        { "id": 2, "count": 5 },
+        { "id": 3, "name": "baz" },
+    ]);
+
+    let mut input = Map::new();
+
+    input.insert_with_static_key("items", changes.clone());
+    input.insert_with_static_key("replaced", changes);
+
+    order.update(&Intermediate::Map(input)).unwrap();
+
+    let names = |items: &[Item]| {
+        items
+            .iter()
+            .map(|item| (item.id, item.name.clone(), item.count))
+            .collect::<Vec<_>>()
+    };
 
-    let val = A::deserialize(&input).unwrap();
     assert_eq!(
-        val,
-        A::Variant {
-            foo: 0,
-            bar: "hello".to_string()
+        names(&order.items),
+        [
+            (1, String::from("foo"), 1),
+            (2, String::from("bar"), 5),
+            (3, String::from("baz"), 0),
+        ]
+    );
+    assert_eq!(
+        names(&order.replaced),
+        [(2, String::from("bar"), 5), (3, String::from("baz"), 0)]
+    );
+
+    let err = order
+        .update(&intermediate!({
+            "items": [{ "name": "qux" }],
+        }))
+        .unwrap_err();
+
+    assert!(matches!(err, Error::NamedFieldErrors(_)));
+    assert_eq!(order.items.len(), 3);
+}
+
+#[test]
+fn test_patch_fields() {
+    use serde_lite::Patch;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize, Update)]
+    struct UserPatch {
+        name: Patch<String>,
+        email: Patch<String>,
+        age: serde_lite::Patch<u32>,
+    }
+
+    let patch = UserPatch::deserialize(&intermediate!({
+        "name": "alice",
+        "email": null,
+    }))
+    .unwrap();
+
+    assert_eq!(
+        patch,
+        UserPatch {
+            name: Patch::Value(String::from("alice")),
+            email: Patch::Null,
+            age: Patch::Missing,
         }
     );
+
+    assert_eq!(
+        patch.serialize().unwrap(),
+        intermediate!({
+            "name": "alice",
+            "email": null,
+        })
+    );
+
+    let mut email = Some(String::from("alice@example.com"));
+    let mut age = Some(30);
+
+    patch.email.clone().apply(&mut email);
+    patch.age.apply(&mut age);
+
+    assert_eq!(email, None);
+    assert_eq!(age, Some(30));
+
+    let mut patch = patch;
+
+    patch
+        .update(&intermediate!({
+            "name": null,
+            "age": 31,
+        }))
+        .unwrap();
+
+    assert_eq!(patch.name, Patch::Null);
+    assert_eq!(patch.email, Patch::Null);
+    assert_eq!(patch.age, Patch::Value(31));
+}
+
+#[test]
+fn test_patchable() {
+    use serde_lite::Patch;
+    use serde_lite_derive::Patchable;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Update, Patchable)]
+    struct User {
+        #[serde(read_only)]
+        id: u32,
+        #[serde(rename = "userName")]
+        name: String,
+        email: Option<String>,
+        tags: Vec<String>,
+        #[serde(skip)]
+        cache: usize,
+    }
+
+    let patch = UserPatch::deserialize(&intermediate!({
+        "userName": "bob",
+        "email": null,
+    }))
+    .unwrap();
+
+    assert_eq!(patch.name.as_deref(), Some("bob"));
+    assert_eq!(patch.email, Patch::Null);
+    assert!(patch.tags.is_none());
+
+    let mut user = User {
+        id: 1,
+        name: String::from("alice"),
+        email: Some(String::from("alice@example.com")),
+        tags: vec![String::from("admin")],
+        cache: 10,
+    };
+
+    patch.apply(&mut user).unwrap();
+
+    assert_eq!(user.name, "bob");
+    assert_eq!(user.email, None);
+    assert_eq!(user.tags, ["admin"]);
+
+    let mut updated = user.clone();
+
+    updated.email = Some(String::from("bob@example.com"));
+    updated.tags.clear();
+
+    let patch = UserPatch::diff(&user, &updated);
+
+    assert!(patch.name.is_none());
+    assert_eq!(patch.email, Patch::Value(String::from("bob@example.com")));
+    assert_eq!(patch.tags, Some(Vec::new()));
+
+    assert_eq!(
+        patch.serialize().unwrap(),
+        intermediate!({
+            "email": "bob@example.com",
+            "tags": [],
+        })
+    );
+
+    patch.apply(&mut user).unwrap();
+
+    assert_eq!(user, updated);
+
+    mod hex {
+        use serde_lite::{Error, Intermediate};
+
+        pub fn serialize(val: &u32) -> Result<Intermediate, Error> {
+            Ok(Intermediate::String(format!("{:x}", val).into()))
+        }
+
+        pub fn deserialize(val: &Intermediate) -> Result<u32, Error> {
+            val.as_str()
+                .and_then(|s| u32::from_str_radix(s, 16).ok())
+                .ok_or_else(|| Error::invalid_value_static("hex"))
+        }
+    }
+
+    fn to_string(val: &Option<u32>) -> Result<Intermediate, Error> {
+        Ok(val.map(|v| v.to_string()).serialize().unwrap())
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Update, Patchable)]
+    struct Style {
+        #[serde(with = "hex")]
+        color: u32,
+        #[serde(serialize_with = "to_string")]
+        width: Option<u32>,
+    }
+
+    let mut style = Style {
+        color: 0xff,
+        width: None,
+    };
+
+    let updated = Style {
+        color: 0xabc,
+        width: Some(3),
+    };
+
+    let patch = StylePatch::diff(&style, &updated);
+
+    assert_eq!(
+        patch.serialize().unwrap(),
+        intermediate!({ "color": "abc", "width": "3" })
+    );
+
+    let patch = StylePatch::diff(
+        &style,
+        &Style {
+            width: None,
+            ..updated.clone()
+        },
+    );
+
+    assert_eq!(
+        patch.serialize().unwrap(),
+        intermediate!({ "color": "abc" })
+    );
+
+    patch.apply(&mut style).unwrap();
+
+    assert_eq!(style.color, 0xabc);
+
+    let patch = StylePatch::deserialize(&intermediate!({ "color": "12" })).unwrap();
+
+    assert_eq!(patch.color, Some(0x12));
+    assert_eq!(patch.width, None);
+
+    let patch = StylePatch::deserialize(&intermediate!({ "width": null })).unwrap();
+
+    assert_eq!(patch.width, Some(None));
+
+    style.width = Some(5);
+
+    patch.apply(&mut style).unwrap();
+
+    assert_eq!(style.width, None);
+
+    let patch = UserPatch::deserialize(&intermediate!({ "id": 2, "userName": "carol" })).unwrap();
+
+    assert_eq!(patch.id, Some(2));
+
+    let mut copy = user.clone();
+
+    assert!(patch.apply(&mut copy).is_err());
+    assert!(user
+        .update(&intermediate!({ "id": 2, "userName": "carol" }))
+        .is_err());
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Update, Patchable)]
+    #[serde(patch_derive = "Debug, Clone, PartialEq")]
+    struct Dimensions {
+        width: u32,
+        height: u32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Update, Patchable)]
+    #[serde(patch_derive = "Debug, Clone, PartialEq")]
+    struct Image {
+        url: String,
+        #[serde(patchable)]
+        size: Dimensions,
+    }
+
+    let mut image = Image {
+        url: String::from("a.png"),
+        size: Dimensions {
+            width: 10,
+            height: 20,
+        },
+    };
+
+    let patch = ImagePatch::deserialize(&intermediate!({ "size": { "height": 30 } })).unwrap();
+
+    assert_eq!(
+        patch,
+        ImagePatch {
+            url: None,
+            size: Some(DimensionsPatch {
+                width: None,
+                height: Some(30),
+            }),
+        }
+    );
+
+    assert_eq!(format!("{:?}", patch.clone()), format!("{:?}", patch));
+
+    patch.apply(&mut image).unwrap();
+
+    assert_eq!(
+        image.size,
+        Dimensions {
+            width: 10,
+            height: 30,
+        }
+    );
+
+    let mut updated = image.clone();
+
+    updated.size.width = 15;
+
+    let patch = ImagePatch::diff(&image, &updated);
+
+    assert_eq!(
+        patch.serialize().unwrap(),
+        intermediate!({ "size": { "width": 15 } })
+    );
+
+    let patch = ImagePatch::diff(&image, &image);
+
+    assert!(patch.is_empty());
+    assert_eq!(patch.serialize().unwrap(), intermediate!({}));
+}
+
+#[test]
+fn test_update_changes() {
+    use std::collections::{HashMap, HashSet};
+
+    use serde_lite::ChangeKind;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize, Update)]
+    struct Address {
+        city: String,
+        zip: String,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize, Update)]
+    struct Meta {
+        version: u32,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize, Update)]
+    #[serde(tag = "type")]
+    enum Contact {
+        Email { address: String },
+        Phone { number: String },
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize, Update)]
+    struct Person {
+        name: String,
+        age: u32,
+        address: Address,
+        contact: Contact,
+        tags: Vec<String>,
+        labels: HashMap<String, u32>,
+        #[serde(flatten)]
+        meta: Meta,
+        roles: HashSet<String>,
+        #[serde(with = "upper")]
+        code: String,
+    }
+
+    mod upper {
+        use serde_lite::{Deserialize, Error, Intermediate};
+
+        pub fn serialize(val: &str) -> Result<Intermediate, Error> {
+            Ok(Intermediate::String(val.to_uppercase().into()))
+        }
+
+        pub fn deserialize(val: &Intermediate) -> Result<String, Error> {
+            String::deserialize(val).map(|s| s.to_lowercase())
+        }
+    }
+
+    let mut person = Person {
+        name: String::from("alice"),
+        age: 30,
+        address: Address {
+            city: String::from("Prague"),
+            zip: String::from("11000"),
+        },
+        contact: Contact::Email {
+            address: String::from("alice@example.com"),
+        },
+        tags: vec![String::from("a"), String::from("b")],
+        labels: HashMap::new(),
+        meta: Meta { version: 1 },
+        roles: HashSet::from([String::from("admin"), String::from("dev")]),
+        code: String::from("abc"),
+    };
+
+    let changes = person
+        .update_with_changes(&intermediate!({
+            "name": "alice",
+            "age": 31,
+            "address": {
+                "city": "Brno",
+            },
+            "contact": {
+                "type": "Phone",
+                "number": "123",
+            },
+            "tags": ["a"],
+            "labels": {
+                "x": 1,
+            },
+            "version": 2,
+            "roles": ["dev", "admin"],
+            "code": "ABC",
+        }))
+        .unwrap();
+
+    let paths = changes
+        .iter()
+        .map(|change| change.path().to_string())
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        paths,
+        [
+            "age",
+            "address.city",
+            "contact",
+            "tags[1]",
+            "labels.x",
+            "version"
+        ]
+    );
+
+    let age = changes.get("age").unwrap();
+
+    assert_eq!(age.kind(), ChangeKind::Modified);
+    assert_eq!(age.old_value(), Some(&intermediate!(30)));
+    assert_eq!(age.new_value(), Some(&intermediate!(31)));
+
+    let contact = changes.get("contact").unwrap();
+
+    assert_eq!(contact.kind(), ChangeKind::Modified);
+    assert_eq!(
+        contact.old_value(),
+        Some(&intermediate!({
+            "type": "Email",
+            "address": "alice@example.com",
+        }))
+    );
+    assert_eq!(
+        contact.new_value(),
+        Some(&intermediate!({
+            "type": "Phone",
+            "number": "123",
+        }))
+    );
+
+    let removed = changes.get("tags[1]").unwrap();
+
+    assert_eq!(removed.kind(), ChangeKind::Removed);
+    assert_eq!(removed.old_value(), Some(&intermediate!("b")));
+    assert_eq!(removed.new_value(), None);
+    assert_eq!(
+        changes.get("labels.x").unwrap().kind(),
+        ChangeKind::Inserted
+    );
+
+    assert_eq!(person.address.city, "Brno");
+    assert_eq!(
+        person.contact,
+        Contact::Phone {
+            number: String::from("123"),
+        }
+    );
+    assert_eq!(person.meta.version, 2);
+
+    let changes = person
+        .update_with_changes(&intermediate!({
+            "name": "alice",
+            "tags": ["a"],
+            "roles": ["admin"],
+            "code": "XYZ",
+        }))
+        .unwrap();
+
+    let roles = changes.get("roles").unwrap();
+
+    assert_eq!(roles.kind(), ChangeKind::Modified);
+    assert!(roles.old_value().is_some());
+    assert_eq!(roles.new_value(), Some(&intermediate!(["admin"])));
+
+    let code = changes.get("code").unwrap();
+
+    assert_eq!(code.old_value(), Some(&intermediate!("ABC")));
+    assert_eq!(code.new_value(), Some(&intermediate!("XYZ")));
+    assert_eq!(person.code, "xyz");
+
+    let changes = person
+        .update_with_changes(&intermediate!({ "name": "alice", "tags": ["a"] }))
+        .unwrap();
+
+    assert!(changes.is_empty());
+
+    let mut items = vec![1u32, 2];
+
+    let changes = items
+        .update_with_changes(&intermediate!([1, 5, 7]))
+        .unwrap();
+
+    assert_eq!(changes.len(), 2);
+    assert_eq!(changes.get("[1]").unwrap().kind(), ChangeKind::Modified);
+    assert_eq!(changes.get("[2]").unwrap().kind(), ChangeKind::Inserted);
+}
+
+#[test]
+fn test_update_inverse() {
+    use std::collections::BTreeMap;
+
+    use serde_lite::{ChangeKind, ChangeSet, Patch};
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Update)]
+    enum Shape {
+        Circle { radius: u32 },
+        Square(u32),
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Update)]
+    struct Document {
+        title: String,
+        note: Option<String>,
+        shape: Shape,
+        outline: Shape,
+        tags: Vec<String>,
+        labels: BTreeMap<String, u32>,
+    }
+
+    let original = Document {
+        title: String::from("draft"),
+        note: None,
+        shape: Shape::Circle { radius: 1 },
+        outline: Shape::Square(2),
+        tags: vec![String::from("a"), String::from("b"), String::from("c")],
+        labels: BTreeMap::from([(String::from("x"), 1)]),
+    };
+
+    let mut document = original.clone();
+
+    let inverse = document
+        .update_with_inverse(&intermediate!({
+            "title": "final",
+            "note": "reviewed",
+            "shape": {
+                "Circle": {
+                    "radius": 5,
+                },
+            },
+            "outline": {
+                "Circle": {
+                    "radius": 3,
+                },
+            },
+            "tags": ["a"],
+            "labels": {
+                "x": 2,
+                "y": 3,
+            },
+        }))
+        .unwrap();
+
+    assert_eq!(
+        inverse,
+        intermediate!({
+            "title": "draft",
+            "note": null,
+            "shape": {
+                "Circle": {
+                    "radius": 1,
+                },
+            },
+            "outline": {
+                "Square": 2,
+            },
+            "tags": ["a", "b", "c"],
+            "labels": {
+                "x": 1,
+                "$remove": ["y"],
+            },
+        })
+    );
+
+    document.apply_inverse(&inverse).unwrap();
+
+    assert_eq!(document, original);
+
+    let mut labels = BTreeMap::from([(String::from("x"), 1u32)]);
+
+    let inverse = labels
+        .update_with_inverse(&intermediate!({ "y": 2 }))
+        .unwrap();
+
+    assert_eq!(inverse, intermediate!({ "$remove": ["y"] }));
+
+    let mut changes = ChangeSet::new();
+
+    changes.set_inverse(true);
+
+    labels.update_tracked(&inverse, &mut changes).unwrap();
+
+    assert_eq!(changes.get("y").unwrap().kind(), ChangeKind::Removed);
+    assert_eq!(labels, BTreeMap::from([(String::from("x"), 1)]));
+
+    // the removal key is an ordinary key in plain updates
+    let mut extra = BTreeMap::<String, Intermediate>::new();
+
+    extra.update(&intermediate!({ "$remove": ["y"] })).unwrap();
+
+    assert_eq!(extra["$remove"], intermediate!(["y"]));
+
+    // fields missing in the old serialized object are removed again
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Update)]
+    struct Sparse {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        x: Option<u32>,
+        p: Patch<u32>,
+    }
+
+    let mut sparse = Sparse {
+        x: None,
+        p: Patch::Missing,
+    };
+
+    let original = sparse.serialize().unwrap();
+
+    let inverse = sparse
+        .update_with_inverse(&intermediate!({ "x": 5, "p": 5 }))
+        .unwrap();
+
+    assert_eq!(sparse.x, Some(5));
+    assert_eq!(sparse.p, Patch::Value(5));
+    assert_eq!(inverse, intermediate!({ "$remove": ["x", "p"] }));
+
+    sparse.apply_inverse(&inverse).unwrap();
+
+    assert_eq!(sparse.x, None);
+    assert_eq!(sparse.p, Patch::Missing);
+    assert_eq!(sparse.serialize().unwrap(), original);
+
+    // arrays updated by key are restored by position
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Update)]
+    struct Item {
+        id: u32,
+        name: String,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Update)]
+    struct Catalog {
+        #[serde(update_by_key = "id")]
+        items: Vec<Item>,
+    }
+
+    let mut catalog = Catalog {
+        items: vec![Item {
+            id: 1,
+            name: String::from("a"),
+        }],
+    };
+
+    let original = catalog.clone();
+
+    let inverse = catalog
+        .update_with_inverse(&intermediate!({
+            "items": [
+                { "id": 2, "name": "b" },
+                { "id": 1, "name": "c" },
+            ],
+        }))
+        .unwrap();
+
+    assert_eq!(catalog.items.len(), 2);
+
+    catalog.apply_inverse(&inverse).unwrap();
+
+    assert_eq!(catalog, original);
+}
+
+#[test]
+fn test_update_hooks() {
+    #[derive(Debug, Clone, Deserialize, Update)]
+    #[serde(after_update = "Item::touch")]
+    struct Item {
+        #[serde(on_update = "Item::name_updated")]
+        name: String,
+        #[serde(on_update = "Item::price_updated")]
+        price: u32,
+        #[serde(skip)]
+        renames: Vec<String>,
+        #[serde(skip)]
+        price_changed: bool,
+        #[serde(skip)]
+        revision: u32,
+    }
+
+    impl Item {
+        fn name_updated(&mut self, old: &str) {
+            self.renames.push(old.to_string());
+        }
+
+        fn price_updated(&mut self, old: &u32) {
+            self.price_changed = self.price != *old;
+        }
+
+        fn touch(&mut self, old: &Self) {
+            self.revision = old.revision + 1;
+        }
+    }
+
+    #[derive(Debug, Clone, Deserialize, Update)]
+    struct Pair(#[serde(on_update = "Pair::first_updated")] u32, u32);
+
+    impl Pair {
+        fn first_updated(&mut self, old: &u32) {
+            self.1 = *old;
+        }
+    }
+
+    let mut item = Item {
+        name: String::from("foo"),
+        price: 10,
+        renames: Vec::new(),
+        price_changed: false,
+        revision: 0,
+    };
+
+    item.update(&intermediate!({ "name": "bar", "price": 10 }))
+        .unwrap();
+
+    assert_eq!(item.name, "bar");
+    assert_eq!(item.renames, ["foo"]);
+    assert!(!item.price_changed);
+    assert_eq!(item.revision, 1);
+
+    // the hook of a successfully updated field is called even if another
+    // field fails, the container hook is not
+    assert!(item
+        .update(&intermediate!({ "name": "baz", "price": "x" }))
+        .is_err());
+
+    assert_eq!(item.renames, ["foo", "bar"]);
+    assert_eq!(item.revision, 1);
+
+    item.update(&intermediate!({ "price": 20 })).unwrap();
+
+    assert_eq!(item.renames, ["foo", "bar"]);
+    assert!(item.price_changed);
+    assert_eq!(item.revision, 2);
+
+    let mut pair = Pair(1, 0);
+
+    pair.update(&intermediate!([5, 7])).unwrap();
+
+    assert_eq!(pair.0, 5);
+    assert_eq!(pair.1, 1);
+
+    use std::cell::RefCell;
+
+    thread_local! {
+        static RENAMES: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+
+    #[derive(Debug, Clone, Deserialize, Update)]
+    #[serde(validate_with = "Tag::validate")]
+    struct Tag {
+        #[serde(on_update = "Tag::renamed")]
+        name: String,
+        color: String,
+    }
+
+    impl Tag {
+        fn validate(&self) -> Result<(), Error> {
+            if self.name.is_empty() {
+                Err(Error::custom_static("empty name"))
+            } else {
+                Ok(())
+            }
+        }
+
+        fn renamed(&mut self, old: &str) {
+            RENAMES.with(|renames| renames.borrow_mut().push(old.to_string()));
+        }
+    }
+
+    let mut tag = Tag {
+        name: String::from("foo"),
+        color: String::from("red"),
+    };
+
+    // the hook is not called if the updated value is not valid
+    assert!(tag
+        .update(&intermediate!({ "name": "", "color": "blue" }))
+        .is_err());
+
+    assert_eq!(tag.name, "foo");
+    assert_eq!(tag.color, "red");
+
+    RENAMES.with(|renames| assert!(renames.borrow().is_empty()));
+
+    tag.update(&intermediate!({ "name": "bar" })).unwrap();
+
+    assert_eq!(tag.name, "bar");
+
+    RENAMES.with(|renames| assert_eq!(*renames.borrow(), ["foo"]));
+
+    // the hook is still called if another field fails
+    assert!(tag
+        .update(&intermediate!({ "name": "baz", "color": 1 }))
+        .is_err());
+
+    assert_eq!(tag.name, "baz");
+
+    RENAMES.with(|renames| assert_eq!(*renames.borrow(), ["foo", "bar"]));
+}
+
+#[test]
+fn test_update_if() {
+    use std::collections::BTreeMap;
+
+    struct User {
+        admin: bool,
+    }
+
+    fn is_admin(user: &User) -> bool {
+        user.admin
+    }
+
+    #[derive(Debug, Deserialize, Update)]
+    struct Profile {
+        bio: String,
+        #[serde(update_if = "is_admin")]
+        verified: bool,
+    }
+
+    #[derive(Debug, Deserialize, Update)]
+    struct Account {
+        name: String,
+        #[serde(update_if = "is_admin")]
+        role: String,
+        profile: Profile,
+    }
+
+    let mut account = Account {
+        name: String::from("alice"),
+        role: String::from("user"),
+        profile: Profile {
+            bio: String::new(),
+            verified: false,
+        },
+    };
+
+    let input = intermediate!({
+        "name": "bob",
+        "role": "admin",
+        "profile": {
+            "bio": "hello",
+            "verified": true,
+        },
+    });
+
+    let err = account
+        .update_with_context(&input, &User { admin: false })
+        .unwrap_err();
+
+    let errors = if let Error::NamedFieldErrors(errors) = err {
+        errors
+    } else {
+        panic!("unexpected error");
+    };
+
+    let fields = errors.iter().map(|err| err.field()).collect::<Vec<_>>();
+
+    assert_eq!(fields, ["role", "profile"]);
+
+    assert_eq!(account.name, "bob");
+    assert_eq!(account.role, "user");
+    assert_eq!(account.profile.bio, "hello");
+    assert!(!account.profile.verified);
+
+    // fields with a predicate cannot be updated without a context
+    assert!(account.update(&input).is_err());
+    assert_eq!(account.role, "user");
+
+    account
+        .update_with_context(&input, &User { admin: true })
+        .unwrap();
+
+    assert_eq!(account.role, "admin");
+    assert!(account.profile.verified);
+
+    // predicates are checked in newly created values as well
+    #[derive(Debug, Deserialize, Update)]
+    struct Member {
+        name: String,
+        #[serde(default, update_if = "is_admin")]
+        role: String,
+    }
+
+    #[derive(Debug, Deserialize, Update)]
+    enum Seat {
+        Vacant,
+        Taken { member: Member },
+    }
+
+    #[derive(Debug, Deserialize, Update)]
+    struct Org {
+        owner: Option<Member>,
+        members: Vec<Member>,
+        teams: BTreeMap<String, Member>,
+        seat: Seat,
+    }
+
+    let mut org = Org {
+        owner: None,
+        members: Vec::new(),
+        teams: BTreeMap::new(),
+        seat: Seat::Vacant,
+    };
+
+    let inputs = [
+        intermediate!({ "owner": { "name": "x", "role": "root" } }),
+        intermediate!({ "members": [{ "name": "x", "role": "root" }] }),
+        intermediate!({ "teams": { "core": { "name": "x", "role": "root" } } }),
+        intermediate!({ "seat": { "Taken": { "member": { "name": "x", "role": "root" } } } }),
+    ];
+
+    for input in &inputs {
+        assert!(org
+            .update_with_context(input, &User { admin: false })
+            .is_err());
+    }
+
+    assert!(org.owner.is_none());
+    assert!(org.members.is_empty());
+    assert!(org.teams.is_empty());
+    assert!(matches!(org.seat, Seat::Vacant));
+
+    for input in &inputs {
+        org.update_with_context(input, &User { admin: true })
+            .unwrap();
+    }
+
+    assert_eq!(org.owner.unwrap().role, "root");
+    assert_eq!(org.members[0].role, "root");
+    assert_eq!(org.teams["core"].role, "root");
+    assert!(matches!(org.seat, Seat::Taken { member } if member.role == "root"));
+
+    // values without the restricted keys can be created without permissions
+    let mut members = Vec::<Member>::new();
+
+    members
+        .update_with_context(&intermediate!([{ "name": "y" }]), &User { admin: false })
+        .unwrap();
+
+    assert_eq!(members[0].role, "");
+}
+
+#[test]
+fn test_with_module() {
+    #[derive(Serialize, Deserialize, Update, Debug, PartialEq)]
+    struct TestStruct {
+        #[serde(with = "as_string")]
+        number: u32,
+        #[serde(with = "as_string_appending")]
+        numbers: Vec<u32>,
+    }
+
+    mod as_string_appending {
+        use serde_lite::{Error, Intermediate};
+
+        pub fn serialize(val: &[u32]) -> Result<Intermediate, Error> {
+            let res = val.iter().map(u32::to_string).collect::<Vec<_>>();
+
+            Ok(Intermediate::from(res.join(",")))
+        }
+
+        pub fn deserialize(val: &Intermediate) -> Result<Vec<u32>, Error> {
+            val.as_str()
+                .ok_or_else(|| Error::invalid_value_static("string"))?
+                .split(',')
+                .map(|s| s.parse().map_err(Error::custom))
+                .collect()
+        }
+
+        pub fn update(val: &mut Vec<u32>, input: &Intermediate) -> Result<(), Error> {
+            val.extend(deserialize(input)?);
+
+            Ok(())
+        }
+    }
+
+    let mut val = TestStruct {
+        number: 1,
+        numbers: vec![2, 3],
+    };
+
+    assert_eq!(
+        val.serialize().unwrap(),
+        intermediate!({ "number": "1", "numbers": "2,3" })
+    );
+    assert_eq!(
+        TestStruct::deserialize(&intermediate!({ "number": "1", "numbers": "2,3" })).unwrap(),
+        val
+    );
+
+    // the first field is replaced, the second one uses the custom update
+    val.update(&intermediate!({ "number": "4", "numbers": "5" }))
+        .unwrap();
+
+    assert_eq!(
+        val,
+        TestStruct {
+            number: 4,
+            numbers: vec![2, 3, 5],
+        }
+    );
+
+    #[derive(Serialize, Deserialize, Update, Debug, PartialEq)]
+    enum TestEnum {
+        #[serde(with = "as_pair")]
+        Pair(u32, u32),
+        #[serde(with = "as_string")]
+        Number { value: u32 },
+    }
+
+    let val = TestEnum::Pair(1, 2);
+
+    assert_eq!(val.serialize().unwrap(), intermediate!({ "Pair": [1, 2] }));
+    assert_eq!(
+        TestEnum::deserialize(&intermediate!({ "Pair": [1, 2] })).unwrap(),
+        val
+    );
+
+    let mut val = TestEnum::Number { value: 1 };
+
+    assert_eq!(val.serialize().unwrap(), intermediate!({ "Number": "1" }));
+
+    val.update(&intermediate!({ "Number": "5" })).unwrap();
+
+    assert_eq!(val, TestEnum::Number { value: 5 });
+
+    val.update(&intermediate!({ "Pair": [3, 4] })).unwrap();
+
+    assert_eq!(val, TestEnum::Pair(3, 4));
+}
+
+#[test]
+fn test_deserialize_from() {
+    #[derive(Deserialize)]
+    #[serde(from = "Delegation")]
+    struct Custom(u32);
+
+    #[derive(Deserialize)]
+    struct Delegation(u32);
+
+    impl From<Delegation> for Custom {
+        fn from(d: Delegation) -> Self {
+            Self(d.0 + 1)
+        }
+    }
+
+    let input = intermediate!(12);
+
+    let val = Custom::deserialize(&input).unwrap();
+    assert_eq!(val.0, 13);
+}
+
+#[test]
+fn test_conversions() {
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Update)]
+    #[serde(try_from = "String", into = "String")]
+    struct Email(String);
+
+    impl TryFrom<String> for Email {
+        type Error = &'static str;
+
+        fn try_from(s: String) -> Result<Self, Self::Error> {
+            if s.contains('@') {
+                Ok(Self(s))
+            } else {
+                Err("invalid email address")
+            }
+        }
+    }
+
+    impl From<Email> for String {
+        fn from(email: Email) -> Self {
+            email.0
+        }
+    }
+
+    let mut email = Email::deserialize(&intermediate!("foo@bar.com")).unwrap();
+
+    assert_eq!(email.serialize().unwrap(), intermediate!("foo@bar.com"));
+
+    assert!(matches!(
+        Email::deserialize(&intermediate!("foo")),
+        Err(Error::Custom(_))
+    ));
+    assert!(email.update(&intermediate!("foo")).is_err());
+    assert_eq!(email, Email(String::from("foo@bar.com")));
+
+    email.update(&intermediate!("bar@foo.com")).unwrap();
+
+    assert_eq!(email, Email(String::from("bar@foo.com")));
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Update)]
+    #[serde(try_into = "u8", from = "u8")]
+    struct Small(u32);
+
+    impl TryFrom<Small> for u8 {
+        type Error = std::num::TryFromIntError;
+
+        fn try_from(v: Small) -> Result<Self, Self::Error> {
+            v.0.try_into()
+        }
+    }
+
+    impl From<u8> for Small {
+        fn from(v: u8) -> Self {
+            Self(v.into())
+        }
+    }
+
+    assert_eq!(Small(3).serialize().unwrap(), intermediate!(3));
+    assert!(Small(300).serialize().is_err());
+
+    let mut small = Small(3);
+
+    small.update(&intermediate!(5)).unwrap();
+
+    assert_eq!(small, Small(5));
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Update)]
+    #[serde(from = "u32", into = "String")]
+    struct Id(u32);
+
+    impl From<u32> for Id {
+        fn from(v: u32) -> Self {
+            Self(v)
+        }
+    }
+
+    impl From<Id> for String {
+        fn from(id: Id) -> Self {
+            id.0.to_string()
+        }
+    }
+
+    let mut id = Id(1);
+
+    assert_eq!(id.serialize().unwrap(), intermediate!("1"));
+
+    id.update(&intermediate!(2)).unwrap();
+
+    assert_eq!(id, Id(2));
+}
+
+#[test]
+fn test_transparent() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize, Update)]
+    #[serde(transparent)]
+    struct Wrapper<T> {
+        inner: T,
+        #[serde(skip)]
+        cache: Option<usize>,
+    }
+
+    let mut val = Wrapper {
+        inner: vec![1, 2],
+        cache: Some(2),
+    };
+
+    assert_eq!(val.serialize().unwrap(), intermediate!([1, 2]));
+    assert_eq!(
+        Wrapper::<Vec<u32>>::deserialize(&intermediate!([1, 2])).unwrap(),
+        Wrapper {
+            inner: vec![1, 2],
+            cache: None,
+        }
+    );
+
+    val.update(&intermediate!([3])).unwrap();
+
+    assert_eq!(
+        val,
+        Wrapper {
+            inner: vec![3],
+            cache: Some(2),
+        }
+    );
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize, Update)]
+    #[serde(transparent)]
+    struct Name(#[serde(skip)] u32, #[serde(validate(non_empty))] String);
+
+    assert_eq!(
+        Name(1, String::from("foo")).serialize().unwrap(),
+        intermediate!("foo")
+    );
+    assert_eq!(
+        Name::deserialize(&intermediate!("foo")).unwrap(),
+        Name(0, String::from("foo"))
+    );
+    assert!(Name::deserialize(&intermediate!("")).is_err());
+
+    let mut name = Name(1, String::from("foo"));
+
+    assert!(name.update(&intermediate!("")).is_err());
+    assert_eq!(name, Name(1, String::from("foo")));
+}
+
+#[test]
+fn test_generic_bounds() {
+    #[derive(Debug, PartialEq)]
+    struct Opaque;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize, Update)]
+    struct Page<T, C> {
+        items: Vec<T>,
+        #[serde(skip)]
+        cache: Option<C>,
+    }
+
+    let mut page = Page::<u32, Opaque> {
+        items: vec![1, 2],
+        cache: Some(Opaque),
+    };
+
+    assert_eq!(
+        page.serialize().unwrap(),
+        intermediate!({ "items": [1, 2] })
+    );
+
+    page.update(&intermediate!({ "items": [3] })).unwrap();
+
+    assert_eq!(
+        page,
+        Page {
+            items: vec![3],
+            cache: Some(Opaque),
+        }
+    );
+
+    assert_eq!(
+        Page::<u32, Opaque>::deserialize(&intermediate!({ "items": [] })).unwrap(),
+        Page {
+            items: Vec::new(),
+            cache: None,
+        }
+    );
+
+    trait Kind {
+        type Value;
+    }
+
+    impl Kind for Opaque {
+        type Value = u32;
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize, Update)]
+    struct Typed<K: Kind> {
+        #[serde(bound = "K::Value: Serialize + Update")]
+        value: K::Value,
+    }
+
+    let mut typed = Typed::<Opaque> { value: 1 };
+
+    assert_eq!(typed.serialize().unwrap(), intermediate!({ "value": 1 }));
+
+    typed.update(&intermediate!({ "value": 2 })).unwrap();
+
+    assert_eq!(typed, Typed { value: 2 });
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize, Update)]
+    #[serde(bound(
+        serialize = "K::Value: Serialize",
+        deserialize = "K::Value: Deserialize",
+        update = "K::Value: Update"
+    ))]
+    enum Either<K: Kind> {
+        Value(K::Value),
+        Nothing,
+    }
+
+    assert_eq!(
+        Either::<Opaque>::deserialize(&intermediate!({ "Value": 3 })).unwrap(),
+        Either::Value(3)
+    );
+    assert_eq!(
+        Either::<Opaque>::Nothing.serialize().unwrap(),
+        intermediate!("Nothing")
+    );
+
+    // fields that are not updated still need to be de-serialized
+    #[derive(Debug, PartialEq, Deserialize, Update)]
+    struct Record<I, V> {
+        #[serde(skip_updating)]
+        id: I,
+        #[serde(read_only)]
+        version: V,
+        name: String,
+    }
+
+    let mut record = Record::<u32, u8>::deserialize(&intermediate!({
+        "id": 1,
+        "version": 2,
+        "name": "foo",
+    }))
+    .unwrap();
+
+    record
+        .update(&intermediate!({ "id": 3, "name": "bar" }))
+        .unwrap();
+
+    assert_eq!(
+        record,
+        Record {
+            id: 1,
+            version: 2,
+            name: String::from("bar"),
+        }
+    );
+}
+
+#[test]
+fn test_serde_lite_attributes() {
+    mod reexport {
+        pub use serde_lite as lite;
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize, Update)]
+    #[serde(rename_all = "camelCase", deny_unknown_fields)]
+    #[serde_lite(crate = "reexport::lite")]
+    struct TestStruct {
+        #[serde(rename = "serdeName")]
+        #[serde_lite(rename = "liteName")]
+        renamed: u32,
+        #[serde(alias = "other", default)]
+        defaulted: u32,
+        #[serde_lite(skip)]
+        skipped: u32,
+    }
+
+    let val = TestStruct::deserialize(&intermediate!({ "liteName": 1 })).unwrap();
+
+    assert_eq!(
+        val,
+        TestStruct {
+            renamed: 1,
+            defaulted: 0,
+            skipped: 0,
+        }
+    );
+
+    assert_eq!(
+        val.serialize().unwrap(),
+        intermediate!({ "liteName": 1, "defaulted": 0 })
+    );
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde_lite(tag = "kind")]
+    enum Tagged {
+        #[serde(rename = "a")]
+        A { value: u32 },
+    }
+
+    assert_eq!(
+        Tagged::A { value: 1 }.serialize().unwrap(),
+        intermediate!({ "kind": "a", "value": 1 })
+    );
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct FieldOnly {
+        #[serde(alias = "v", borrow)]
+        #[serde_lite(rename = "val")]
+        value: u32,
+    }
+
+    assert_eq!(
+        FieldOnly::deserialize(&intermediate!({ "val": 1 })).unwrap(),
+        FieldOnly { value: 1 }
+    );
+}
+
+#[test]
+fn test_other_variant() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize, Update)]
+    enum Fallback {
+        Known,
+        #[serde(other)]
+        Unknown,
+    }
+
+    assert_eq!(
+        Fallback::deserialize(&intermediate!("New")).unwrap(),
+        Fallback::Unknown
+    );
+    assert_eq!(
+        Fallback::deserialize(&intermediate!({ "New": 1 })).unwrap(),
+        Fallback::Unknown
+    );
+    assert_eq!(
+        Fallback::Unknown.serialize().unwrap(),
+        intermediate!("Unknown")
+    );
+
+    let mut val = Fallback::Known;
+
+    val.update(&intermediate!("New")).unwrap();
+
+    assert_eq!(val, Fallback::Unknown);
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize, Update)]
+    enum CapturedName {
+        Known(u32),
+        #[serde(other)]
+        Unknown(String),
+    }
+
+    let val = CapturedName::deserialize(&intermediate!("New")).unwrap();
+
+    assert_eq!(val, CapturedName::Unknown(String::from("New")));
+    assert_eq!(val.serialize().unwrap(), intermediate!("New"));
+
+    assert_eq!(
+        CapturedName::deserialize(&intermediate!({ "Known": 1 })).unwrap(),
+        CapturedName::Known(1)
+    );
+    assert!(matches!(
+        CapturedName::deserialize(&intermediate!({ "A": 1, "B": 2 })),
+        Err(Error::UnknownEnumVariant)
+    ));
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize, Update)]
+    #[serde(tag = "type")]
+    enum CapturedValue {
+        Known {
+            value: u32,
+        },
+        #[serde(other)]
+        Unknown(Intermediate),
+    }
+
+    let input = intermediate!({ "type": "New", "value": "foo" });
+
+    let val = CapturedValue::deserialize(&input).unwrap();
+
+    assert_eq!(val, CapturedValue::Unknown(input.clone()));
+    assert_eq!(val.serialize().unwrap(), input);
+
+    let mut val = CapturedValue::Known { value: 1 };
+
+    val.update(&input).unwrap();
+
+    assert_eq!(val, CapturedValue::Unknown(input.clone()));
+
+    val.update(&intermediate!({ "type": "Known", "value": 2 }))
+        .unwrap();
+
+    assert_eq!(val, CapturedValue::Known { value: 2 });
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize, Update)]
+    #[serde(tag = "t", content = "c")]
+    enum CapturedBoth {
+        Known(u32),
+        #[serde(other)]
+        Unknown((String, Intermediate)),
+    }
+
+    let input = intermediate!({ "t": "New", "c": [1, 2] });
+
+    let val = CapturedBoth::deserialize(&input).unwrap();
+
+    assert_eq!(
+        val,
+        CapturedBoth::Unknown((String::from("New"), input.clone()))
+    );
+    assert_eq!(val.serialize().unwrap(), input);
+}
+
+#[test]
+fn test_repr_enum() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize, Update)]
+    #[serde(repr = "u16")]
+    enum Status {
+        Ok = 200,
+        NotFound = 404,
+        Gone,
+    }
+
+    assert_eq!(Status::Ok.serialize().unwrap(), intermediate!(200));
+    assert_eq!(Status::Gone.serialize().unwrap(), intermediate!(405));
+    assert_eq!(
+        Status::deserialize(&intermediate!(404)).unwrap(),
+        Status::NotFound
+    );
+    assert!(matches!(
+        Status::deserialize(&intermediate!(500)),
+        Err(Error::UnknownEnumVariant)
+    ));
+    assert!(Status::deserialize(&intermediate!("Ok")).is_err());
+
+    let mut status = Status::Ok;
+
+    status.update(&intermediate!(405)).unwrap();
+
+    assert_eq!(status, Status::Gone);
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize, Update)]
+    #[serde(repr = "i32")]
+    enum Level {
+        Low,
+        High,
+        #[serde(other)]
+        Unknown,
+    }
+
+    assert_eq!(Level::deserialize(&intermediate!(1)).unwrap(), Level::High);
+    assert_eq!(
+        Level::deserialize(&intermediate!(7)).unwrap(),
+        Level::Unknown
+    );
+    assert_eq!(Level::Unknown.serialize().unwrap(), intermediate!(2));
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize, Update)]
+    #[serde(repr = "u8")]
+    #[repr(u8)]
+    enum Code {
+        A = 1,
+        B,
+        #[serde(other)]
+        Unknown(u8),
+    }
+
+    assert_eq!(Code::deserialize(&intermediate!(2)).unwrap(), Code::B);
+
+    let code = Code::deserialize(&intermediate!(9)).unwrap();
+
+    assert_eq!(code, Code::Unknown(9));
+    assert_eq!(code.serialize().unwrap(), intermediate!(9));
+    assert_eq!(Code::A.serialize().unwrap(), intermediate!(1));
+}
+
+#[test]
+fn test_default_function() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    enum A {
+        Variant {
+            #[serde(default)]
+            foo: usize,
+
+            #[serde(default = "default_bar")]
+            bar: String,
+        },
+    }
+
+    fn default_bar() -> String {
+        "hello".to_string()
+    }
+
+    let input = intermediate!({
+        "Variant": {},
+    });
+
+    let val = A::deserialize(&input).unwrap();
+    assert_eq!(
+        val,
+        A::Variant {
+            foo: 0,
+            bar: "hello".to_string()
+        }
+    );
+}
+
+#[test]
+fn test_container_default() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Debug, PartialEq, Deserialize, Update)]
+    #[serde(default)]
+    struct Options {
+        threads: usize,
+        name: String,
+        #[serde(default = "default_verbose")]
+        verbose: bool,
+        #[serde(skip)]
+        cache: usize,
+    }
+
+    impl Default for Options {
+        fn default() -> Self {
+            Self {
+                threads: 4,
+                name: String::from("worker"),
+                verbose: false,
+                cache: 64,
+            }
+        }
+    }
+
+    fn default_verbose() -> bool {
+        true
+    }
+
+    assert_eq!(
+        Options::deserialize(&intermediate!({ "threads": 8 })).unwrap(),
+        Options {
+            threads: 8,
+            name: String::from("worker"),
+            verbose: true,
+            cache: 64,
+        }
+    );
+
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(default = "Point::origin")]
+    struct Point {
+        x: i32,
+        y: i32,
+        z: i32,
+    }
+
+    impl Point {
+        fn origin() -> Self {
+            CALLS.fetch_add(1, Ordering::Relaxed);
+
+            Self { x: 1, y: 2, z: 3 }
+        }
+    }
+
+    assert_eq!(
+        Point::deserialize(&intermediate!({ "y": 5 })).unwrap(),
+        Point { x: 1, y: 5, z: 3 }
+    );
+    assert_eq!(CALLS.load(Ordering::Relaxed), 1);
+
+    #[derive(Debug, PartialEq, Deserialize, Update)]
+    #[serde(default)]
+    struct Tuple(u32, String, #[serde(skip)] bool);
+
+    impl Default for Tuple {
+        fn default() -> Self {
+            Self(7, String::from("seven"), true)
+        }
+    }
+
+    assert_eq!(
+        Tuple::deserialize(&intermediate!([1])).unwrap(),
+        Tuple(1, String::from("seven"), true)
+    );
+    assert_eq!(
+        Tuple::deserialize(&intermediate!([])).unwrap(),
+        Tuple::default()
+    );
+
+    let mut tuple = Tuple(1, String::from("one"), false);
+
+    tuple.update(&intermediate!([2])).unwrap();
+
+    assert_eq!(tuple, Tuple(2, String::from("one"), false));
+}
+
+#[test]
+fn test_validate_deserialize() {
+    #[derive(Deserialize)]
+    struct User {
+        #[serde(validate(non_empty, length(max = 8)))]
+        name: String,
+        #[serde(default, validate(range(min = 18, max = 150)))]
+        age: Option<u8>,
+        #[serde(validate = "validate_email")]
+        email: String,
+    }
+
+    fn validate_email(email: &str) -> Result<(), Error> {
+        if email.contains('@') {
+            Ok(())
+        } else {
+            Err(Error::custom_static("invalid email address"))
+        }
+    }
+
+    let input = intermediate!({
+        "name": "foo",
+        "email": "foo@bar.com",
+    });
+
+    let user = User::deserialize(&input).unwrap();
+
+    assert_eq!(user.name, "foo");
+    assert_eq!(user.age, None);
+    assert_eq!(user.email, "foo@bar.com");
+
+    let input = intermediate!({
+        "name": "",
+        "age": 10,
+        "email": "foo",
+    });
+
+    let errors = match User::deserialize(&input) {
+        Err(Error::NamedFieldErrors(errors)) => errors,
+        _ => panic!("unexpected result"),
+    };
+
+    let fields = errors.iter().map(|err| err.field()).collect::<Vec<_>>();
+
+    assert_eq!(fields, ["name", "age", "email"]);
+
+    let input = intermediate!({
+        "name": "foo bar baz",
+        "age": 20,
+        "email": "foo@bar.com",
+    });
+
+    assert!(matches!(
+        User::deserialize(&input),
+        Err(Error::NamedFieldErrors(_))
+    ));
+}
+
+#[test]
+fn test_validate_update() {
+    #[derive(Deserialize, Update)]
+    struct Config {
+        #[serde(validate(range(min = 1, max = 10)))]
+        threads: u32,
+        name: String,
+    }
+
+    let mut config = Config {
+        threads: 4,
+        name: String::from("foo"),
+    };
+
+    let input = intermediate!({
+        "threads": 8,
+    });
+
+    config.update(&input).unwrap();
+
+    assert_eq!(config.threads, 8);
+
+    let input = intermediate!({
+        "threads": 20,
+        "name": "bar",
+    });
+
+    let errors = match config.update(&input) {
+        Err(Error::NamedFieldErrors(errors)) => errors,
+        _ => panic!("unexpected result"),
+    };
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors.iter().next().unwrap().field(), "threads");
+    assert_eq!(config.threads, 8);
+    assert_eq!(config.name, "bar");
+}
+
+#[test]
+fn test_validate_container() {
+    #[derive(Clone, Debug, PartialEq, Deserialize, Update)]
+    #[serde(validate_with = "Self::check")]
+    struct Interval {
+        start: u32,
+        end: u32,
+    }
+
+    impl Interval {
+        fn check(&self) -> Result<(), Error> {
+            if self.start <= self.end {
+                Ok(())
+            } else {
+                Err(Error::custom_static("start must not be greater than end"))
+            }
+        }
+    }
+
+    let mut interval = Interval::deserialize(&intermediate!({ "start": 1, "end": 5 })).unwrap();
+
+    assert!(matches!(
+        Interval::deserialize(&intermediate!({ "start": 6, "end": 5 })),
+        Err(Error::Custom(_))
+    ));
+
+    interval.update(&intermediate!({ "end": 3 })).unwrap();
+
+    assert_eq!(interval, Interval { start: 1, end: 3 });
+
+    assert!(matches!(
+        interval.update(&intermediate!({ "start": 4 })),
+        Err(Error::Custom(_))
+    ));
+
+    assert_eq!(interval, Interval { start: 1, end: 3 });
+
+    #[derive(Clone, Deserialize, Update)]
+    #[serde(validate_with = "check_shape")]
+    enum Shape {
+        Circle { radius: f64 },
+        Square { side: f64 },
+    }
+
+    fn check_shape(shape: &Shape) -> Result<(), Error> {
+        let size = match shape {
+            Shape::Circle { radius } => *radius,
+            Shape::Square { side } => *side,
+        };
+
+        if size > 0.0 {
+            Ok(())
+        } else {
+            Err(Error::custom_static("size must be positive"))
+        }
+    }
+
+    let mut shape = Shape::deserialize(&intermediate!({ "Circle": { "radius": 1.0 } })).unwrap();
+
+    assert!(Shape::deserialize(&intermediate!({ "Square": { "side": 0.0 } })).is_err());
+    assert!(shape
+        .update(&intermediate!({ "Circle": { "radius": 0.0 } }))
+        .is_err());
+    assert!(matches!(shape, Shape::Circle { radius } if radius == 1.0));
+}
+
+#[test]
+fn test_tuple_field_attributes() {
+    #[derive(Serialize, Deserialize, Update, Debug, PartialEq)]
+    struct Tuple(
+        u32,
+        #[serde(skip)] u32,
+        #[serde(
+            serialize_with = "to_string",
+            deserialize_with = "from_string",
+            update_with = "update_from_string"
+        )]
+        u32,
+        #[serde(default = "default_name")] String,
+        #[serde(default, skip_serializing_if = "Option::is_none")] Option<bool>,
+    );
+
+    fn to_string(val: &u32) -> Result<Intermediate, Error> {
+        Ok(Intermediate::from(val.to_string()))
+    }
+
+    fn from_string(val: &Intermediate) -> Result<u32, Error> {
+        val.as_str()
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| Error::invalid_value_static("number string"))
+    }
+
+    fn update_from_string(val: &mut u32, input: &Intermediate) -> Result<(), Error> {
+        *val = from_string(input)?;
+
+        Ok(())
+    }
+
+    fn default_name() -> String {
+        String::from("foo")
+    }
+
+    let val = Tuple(1, 2, 3, String::from("bar"), None);
+
+    let serialized = val.serialize().unwrap();
+
+    assert_eq!(serialized, intermediate!([1, "3", "bar"]));
+    assert_eq!(
+        Tuple::deserialize(&serialized).unwrap(),
+        Tuple(1, 0, 3, String::from("bar"), None)
+    );
+
+    let val = Tuple::deserialize(&intermediate!([1, "3"])).unwrap();
+
+    assert_eq!(val, Tuple(1, 0, 3, String::from("foo"), None));
+
+    let val = Tuple::deserialize(&intermediate!([1, "3", "bar", true])).unwrap();
+
+    assert_eq!(val, Tuple(1, 0, 3, String::from("bar"), Some(true)));
+
+    assert!(Tuple::deserialize(&intermediate!([1])).is_err());
+
+    let errors = match Tuple::deserialize(&intermediate!([1, 3])) {
+        Err(Error::UnnamedFieldErrors(errors)) => errors,
+        _ => panic!("unexpected result"),
+    };
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors.iter().next().unwrap().field_index(), 1);
+
+    let mut val = Tuple(1, 2, 3, String::from("bar"), None);
+
+    val.update(&intermediate!([4, "5", "baz", false])).unwrap();
+
+    assert_eq!(val, Tuple(4, 2, 5, String::from("baz"), Some(false)));
+
+    #[derive(Serialize, Deserialize, Update, Debug, PartialEq)]
+    enum Enum {
+        Newtype(#[serde(serialize_with = "to_string", deserialize_with = "from_string")] u32),
+        Tuple(u32, #[serde(default, validate(range(max = 10)))] u32),
+    }
+
+    let val = Enum::Newtype(3);
+
+    assert_eq!(val.serialize().unwrap(), intermediate!({ "Newtype": "3" }));
+    assert_eq!(
+        Enum::deserialize(&intermediate!({ "Newtype": "3" })).unwrap(),
+        val
+    );
+    assert_eq!(
+        Enum::deserialize(&intermediate!({ "Tuple": [1] })).unwrap(),
+        Enum::Tuple(1, 0)
+    );
+    assert!(Enum::deserialize(&intermediate!({ "Tuple": [1, 11] })).is_err());
+
+    let mut val = Enum::Tuple(1, 2);
+
+    assert!(val.update(&intermediate!({ "Tuple": [3, 11] })).is_err());
+    assert_eq!(val, Enum::Tuple(3, 2));
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn test_validate_regex() {
+    #[derive(Deserialize)]
+    struct Ident {
+        #[serde(validate(regex = "^[a-z_][a-z0-9_]*$"))]
+        name: String,
+    }
+
+    let ident = Ident::deserialize(&intermediate!({ "name": "foo_1" })).unwrap();
+
+    assert_eq!(ident.name, "foo_1");
+
+    assert!(Ident::deserialize(&intermediate!({ "name": "1foo" })).is_err());
 }
 
 /// Helper.
@@ -0,0 +1,525 @@
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+use serde_json::json;
+use serde_lite::{intermediate, Intermediate, Schema, SchemaValidator};
+use serde_lite_derive::Schema;
+
+#[test]
+fn test_primitive_schema() {
+    assert_eq!(to_json(bool::schema()), json!({"type": "boolean"}));
+    assert_eq!(
+        to_json(u8::schema()),
+        json!({"type": "integer", "minimum": 0, "maximum": 255})
+    );
+    assert_eq!(to_json(String::schema()), json!({"type": "string"}));
+    assert_eq!(
+        to_json(<Option<f64>>::schema()),
+        json!({"anyOf": [{"type": "number"}, {"type": "null"}]})
+    );
+    assert_eq!(
+        to_json(<Vec<bool>>::schema()),
+        json!({"type": "array", "items": {"type": "boolean"}})
+    );
+    assert_eq!(
+        to_json(<HashMap<String, bool>>::schema()),
+        json!({"type": "object", "additionalProperties": {"type": "boolean"}})
+    );
+    assert_eq!(
+        to_json(<(bool, String)>::schema()),
+        json!({
+            "type": "array",
+            "prefixItems": [{"type": "boolean"}, {"type": "string"}],
+            "minItems": 2,
+        })
+    );
+}
+
+#[test]
+fn test_struct_schema() {
+    #[derive(Schema)]
+    struct OuterStruct {
+        #[serde(rename = "id")]
+        identifier: bool,
+        #[serde(default)]
+        note: Option<String>,
+        #[serde(skip)]
+        cache: Vec<u8>,
+        #[serde(flatten)]
+        inner: InnerStruct,
+    }
+
+    #[derive(Schema)]
+    struct InnerStruct {
+        flag: bool,
+        #[serde(schema_with = "custom_schema")]
+        custom: u32,
+    }
+
+    fn custom_schema() -> Intermediate {
+        intermediate!({
+            "type": "string",
+        })
+    }
+
+    assert_eq!(
+        to_json(OuterStruct::schema()),
+        json!({
+            "type": "object",
+            "properties": {
+                "id": {"type": "boolean"},
+                "note": {"anyOf": [{"type": "string"}, {"type": "null"}]},
+                "flag": {"type": "boolean"},
+                "custom": {"type": "string"},
+            },
+            "required": ["id", "flag", "custom"],
+        })
+    );
+
+    #[derive(Schema)]
+    struct TupleStruct(bool, String);
+
+    #[derive(Schema)]
+    struct NewType(bool);
+
+    #[derive(Schema)]
+    struct UnitStruct;
+
+    assert_eq!(
+        to_json(TupleStruct::schema()),
+        to_json(<(bool, String)>::schema())
+    );
+    assert_eq!(to_json(NewType::schema()), json!({"type": "boolean"}));
+    assert_eq!(to_json(UnitStruct::schema()), json!({"type": "null"}));
+}
+
+#[test]
+fn test_externally_tagged_enum_schema() {
+    #[derive(Schema)]
+    enum TestEnum {
+        Variant1,
+        #[serde(rename = "v2")]
+        Variant2,
+        Variant3(bool),
+        Variant4 {
+            field: String,
+        },
+    }
+
+    assert_eq!(
+        to_json(TestEnum::schema()),
+        json!({
+            "oneOf": [
+                {"type": "string", "enum": ["Variant1", "v2"]},
+                {
+                    "type": "object",
+                    "properties": {"Variant3": {"type": "boolean"}},
+                    "required": ["Variant3"],
+                    "additionalProperties": false,
+                },
+                {
+                    "type": "object",
+                    "properties": {
+                        "Variant4": {
+                            "type": "object",
+                            "properties": {"field": {"type": "string"}},
+                            "required": ["field"],
+                        },
+                    },
+                    "required": ["Variant4"],
+                    "additionalProperties": false,
+                },
+            ],
+        })
+    );
+}
+
+#[test]
+fn test_tagged_enum_schema() {
+    #[derive(Schema)]
+    #[serde(tag = "type")]
+    enum InternallyTagged {
+        Variant1,
+        Variant2 { field: bool },
+    }
+
+    assert_eq!(
+        to_json(InternallyTagged::schema()),
+        json!({
+            "oneOf": [
+                {
+                    "type": "object",
+                    "properties": {"type": {"type": "string", "const": "Variant1"}},
+                    "required": ["type"],
+                },
+                {
+                    "type": "object",
+                    "properties": {
+                        "type": {"type": "string", "const": "Variant2"},
+                        "field": {"type": "boolean"},
+                    },
+                    "required": ["type", "field"],
+                },
+            ],
+        })
+    );
+
+    #[derive(Schema)]
+    #[serde(tag = "t", content = "c")]
+    enum AdjacentlyTagged {
+        Variant1,
+        Variant2(bool),
+    }
+
+    assert_eq!(
+        to_json(AdjacentlyTagged::schema()),
+        json!({
+            "oneOf": [
+                {
+                    "type": "object",
+                    "properties": {
+                        "t": {"type": "string", "const": "Variant1"},
+                        "c": {"type": "null"},
+                    },
+                    "required": ["t"],
+                },
+                {
+                    "type": "object",
+                    "properties": {
+                        "t": {"type": "string", "const": "Variant2"},
+                        "c": {"type": "boolean"},
+                    },
+                    "required": ["t", "c"],
+                },
+            ],
+        })
+    );
+}
+
+#[test]
+fn test_validators_schema() {
+    #[derive(Schema)]
+    struct Config {
+        #[serde(validate(range(min = 1, max = 10)))]
+        threads: u32,
+        #[serde(validate(non_empty, length(max = 8)))]
+        name: String,
+    }
+
+    assert_eq!(
+        to_json(Config::schema()),
+        json!({
+            "type": "object",
+            "properties": {
+                "threads": {"type": "integer", "minimum": 1, "maximum": 10},
+                "name": {
+                    "type": "string",
+                    "minLength": 1,
+                    "minItems": 1,
+                    "minProperties": 1,
+                    "maxLength": 8,
+                    "maxItems": 8,
+                    "maxProperties": 8,
+                },
+            },
+            "required": ["threads", "name"],
+        })
+    );
+}
+
+#[test]
+fn test_tuple_field_attributes_schema() {
+    #[derive(Schema)]
+    struct Tuple(
+        u32,
+        #[serde(skip)] u32,
+        #[serde(default)] bool,
+        #[serde(default, validate(length(max = 4)))] String,
+    );
+
+    assert_eq!(
+        to_json(Tuple::schema()),
+        json!({
+            "type": "array",
+            "prefixItems": [
+                {"type": "integer", "minimum": 0, "maximum": 4294967295u32},
+                {"type": "boolean"},
+                {"type": "string", "maxLength": 4, "maxItems": 4, "maxProperties": 4},
+            ],
+            "minItems": 1,
+        })
+    );
+}
+
+#[test]
+fn test_container_default_schema() {
+    #[derive(Default, Schema)]
+    #[serde(default)]
+    struct Options {
+        threads: usize,
+        name: String,
+    }
+
+    #[derive(Default, Schema)]
+    #[serde(default)]
+    struct Pair(u8, bool);
+
+    assert_eq!(
+        to_json(Options::schema()),
+        json!({
+            "type": "object",
+            "properties": {
+                "threads": {"type": "integer", "minimum": 0},
+                "name": {"type": "string"},
+            },
+        })
+    );
+
+    assert_eq!(
+        to_json(Pair::schema()),
+        json!({
+            "type": "array",
+            "prefixItems": [
+                {"type": "integer", "minimum": 0, "maximum": 255},
+                {"type": "boolean"},
+            ],
+            "minItems": 0,
+        })
+    );
+}
+
+#[test]
+fn test_patch_schema() {
+    #[derive(Schema)]
+    struct Patch {
+        id: u32,
+        name: serde_lite::Patch<String>,
+    }
+
+    assert_eq!(
+        to_json(Patch::schema()),
+        json!({
+            "type": "object",
+            "properties": {
+                "id": {"type": "integer", "minimum": 0, "maximum": 4294967295u32},
+                "name": {"anyOf": [{"type": "string"}, {"type": "null"}]},
+            },
+            "required": ["id"],
+        })
+    );
+}
+
+#[test]
+fn test_transparent_schema() {
+    #[derive(Schema)]
+    #[serde(transparent)]
+    struct Wrapper<T> {
+        inner: T,
+        #[serde(skip)]
+        _cache: Option<usize>,
+    }
+
+    assert_eq!(
+        to_json(Wrapper::<bool>::schema()),
+        json!({"type": "boolean"})
+    );
+}
+
+#[test]
+fn test_generic_schema() {
+    struct Opaque;
+
+    #[derive(Schema)]
+    struct Page<T, C> {
+        items: Vec<T>,
+        #[serde(skip)]
+        _cache: Option<C>,
+    }
+
+    assert_eq!(
+        to_json(Page::<bool, Opaque>::schema()),
+        json!({
+            "type": "object",
+            "properties": {
+                "items": {"type": "array", "items": {"type": "boolean"}},
+            },
+            "required": ["items"],
+        })
+    );
+}
+
+#[test]
+fn test_other_variant_schema() {
+    #[derive(Schema)]
+    enum TestEnum {
+        Variant1,
+        #[serde(other)]
+        Unknown(String),
+    }
+
+    assert_eq!(
+        to_json(TestEnum::schema()),
+        json!({"type": "string", "enum": ["Variant1"]})
+    );
+}
+
+#[test]
+fn test_repr_enum_schema() {
+    #[derive(Schema)]
+    #[serde(repr = "u8")]
+    enum Status {
+        Ok = 1,
+        Failed,
+    }
+
+    assert_eq!(
+        to_json(Status::schema()),
+        json!({"type": "integer", "minimum": 0, "maximum": 255, "enum": [1, 2]})
+    );
+}
+
+#[test]
+fn test_flattened_option_schema() {
+    #[derive(Schema)]
+    struct Loc {
+        lat: f64,
+        lon: f64,
+    }
+
+    #[derive(Schema)]
+    #[serde(tag = "kind")]
+    enum Source {
+        Gps { accuracy: u32 },
+        Manual,
+    }
+
+    #[derive(Schema)]
+    struct Event {
+        code: u32,
+        #[serde(flatten)]
+        location: Option<Loc>,
+        #[serde(flatten)]
+        source: Option<Source>,
+    }
+
+    let validator = SchemaValidator::new(&Event::schema()).unwrap();
+
+    // both flattened values are absent
+    assert!(validator.validate(&intermediate!({ "code": 1 })).is_ok());
+
+    assert!(validator
+        .validate(&intermediate!({
+            "code": 1,
+            "lat": 1.5,
+            "lon": 2.5,
+            "kind": "Gps",
+            "accuracy": 5,
+        }))
+        .is_ok());
+
+    assert!(validator
+        .validate(&intermediate!({ "code": 1, "kind": "Manual" }))
+        .is_ok());
+
+    // partially present values are rejected
+    assert!(validator
+        .validate(&intermediate!({ "code": 1, "lat": 1.5 }))
+        .is_err());
+    assert!(validator
+        .validate(&intermediate!({ "code": 1, "accuracy": 5 }))
+        .is_err());
+    assert!(validator
+        .validate(&intermediate!({ "code": 1, "lat": "north", "lon": 2.5 }))
+        .is_err());
+}
+
+/// Helper.
+#[test]
+fn test_recursive_schema() {
+    #[derive(Schema)]
+    struct Tree {
+        name: String,
+        children: Vec<Tree>,
+    }
+
+    #[derive(Schema)]
+    struct Forest {
+        first: Tree,
+        second: Option<Tree>,
+    }
+
+    assert_eq!(
+        to_json(Tree::schema()),
+        json!({
+            "type": "object",
+            "properties": {
+                "name": { "type": "string" },
+                "children": {
+                    "type": "array",
+                    "items": { "$ref": "#Tree" },
+                },
+            },
+            "required": ["name", "children"],
+            "$anchor": "Tree",
+        })
+    );
+
+    // the anchor is defined only once within the whole schema document
+    assert_eq!(
+        to_json(Forest::schema())["properties"]["second"],
+        json!({
+            "anyOf": [
+                { "$ref": "#Tree" },
+                { "type": "null" },
+            ],
+        })
+    );
+
+    let tree = intermediate!({
+        "name": "root",
+        "children": [
+            { "name": "a", "children": [] },
+            { "name": "b", "children": [{ "name": "c", "children": [] }] },
+        ],
+    });
+
+    let validator = SchemaValidator::new(&Tree::schema()).unwrap();
+
+    assert!(validator.validate(&tree).is_ok());
+    assert!(validator
+        .validate(&intermediate!({
+            "name": "root",
+            "children": [{ "name": "a", "children": [{ "name": 1, "children": [] }] }],
+        }))
+        .is_err());
+
+    // the anchor can be resolved even if the schema is nested
+    let validator = SchemaValidator::new(&Vec::<Tree>::schema()).unwrap();
+
+    assert!(validator
+        .validate(&intermediate!([{ "name": "a", "children": [{ "name": "b", "children": [] }] }]))
+        .is_ok());
+    assert!(validator
+        .validate(&intermediate!([{ "name": "a", "children": [{ "name": "b" }] }]))
+        .is_err());
+
+    let validator = SchemaValidator::new(&Forest::schema()).unwrap();
+
+    assert!(validator
+        .validate(&intermediate!({
+            "first": { "name": "a", "children": [] },
+            "second": { "name": "b", "children": [{ "name": "c", "children": [] }] },
+        }))
+        .is_ok());
+    assert!(validator
+        .validate(&intermediate!({
+            "first": { "name": "a", "children": [] },
+            "second": { "name": "b", "children": [{ "children": [] }] },
+        }))
+        .is_err());
+}
+
+fn to_json(schema: Intermediate) -> serde_json::Value {
+    serde_json::to_value(&schema).unwrap()
+}
@@ -0,0 +1,6 @@
+#[test]
+fn test_ui() {
+    let t = trybuild::TestCases::new();
+
+    t.compile_fail("tests/ui/*.rs");
+}
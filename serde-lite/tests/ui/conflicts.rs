@@ -0,0 +1,96 @@
+use serde_lite_derive::{Deserialize, Patchable, Serialize, Update};
+
+#[derive(Serialize)]
+#[serde(tag = "type")]
+struct Foo {
+    #[serde(flatten, rename = "bar")]
+    bar: Bar,
+    #[serde(with = "foo", serialize_with = "bar")]
+    baz: u32,
+}
+
+#[derive(Serialize)]
+struct Bar(#[serde(rename = "foo")] u32);
+
+#[derive(Update)]
+#[serde(content = "value")]
+enum Baz {
+    #[serde(with = "foo")]
+    Foo,
+    #[serde(with = "foo")]
+    Bar(),
+    #[serde(with = "foo")]
+    Baz {},
+}
+
+#[derive(Update)]
+enum Qux {
+    #[serde(other)]
+    Foo { a: u32 },
+    #[serde(other)]
+    Bar,
+    #[serde(other)]
+    Baz(String),
+}
+
+#[derive(Serialize)]
+#[serde(repr = "usize")]
+enum Quux {
+    #[serde(rename = "foo")]
+    Foo,
+    Bar(u32),
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+enum Corge {
+    Foo,
+}
+
+#[derive(Deserialize)]
+#[serde(transparent, default)]
+struct Grault(u32);
+
+#[derive(Update)]
+struct Garply {
+    #[serde(read_only, skip_updating)]
+    a: u32,
+    #[serde(update_once)]
+    b: u32,
+    #[serde(update_if = "foo", skip_updating)]
+    c: u32,
+}
+
+#[derive(Update)]
+enum Waldo {
+    Foo {
+        #[serde(on_update = "foo", read_only)]
+        a: u32,
+    },
+}
+
+#[derive(Serialize)]
+struct Fred(
+    #[serde(default, skip_serializing_if = "Option::is_none")] Option<u32>,
+    String,
+    #[serde(skip_serializing_if = "String::is_empty")] String,
+);
+
+#[derive(Patchable)]
+struct Plugh {
+    #[serde(flatten, with = "foo")]
+    a: u32,
+}
+
+#[derive(Patchable)]
+#[serde(patch_derive = "Debug, 1")]
+struct Xyzzy {
+    #[serde(patchable, read_only)]
+    a: Plugh,
+    #[serde(patchable)]
+    b: Option<Plugh>,
+    #[serde(patchable)]
+    c: [u32; 2],
+}
+
+fn main() {}
@@ -0,0 +1,36 @@
+use serde_lite_derive::Deserialize;
+
+#[derive(Deserialize)]
+struct Foo {
+    #[serde(rename = 1)]
+    a: u32,
+    #[serde(skip = "yes")]
+    b: u32,
+    #[serde(deserialize_with = "not a path")]
+    c: u32,
+    #[serde(validate(range(min = 1), positive))]
+    d: u32,
+    #[serde(bound = "T")]
+    e: u32,
+    #[serde(update_by_key(key = "id", unmatched = "drop"))]
+    f: Vec<u32>,
+    #[serde(update_by_key(unmatched = "keep"))]
+    g: Vec<u32>,
+}
+
+#[derive(Deserialize)]
+#[serde = "bar"]
+struct Bar {
+    #[serde(rename)]
+    a: u32,
+    #[serde(default = 1)]
+    b: u32,
+    #[serde(validate)]
+    c: u32,
+    #[serde(bound(deserialize))]
+    d: u32,
+    #[serde(with = "foo" skip)]
+    e: u32,
+}
+
+fn main() {}
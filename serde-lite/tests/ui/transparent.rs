@@ -0,0 +1,20 @@
+use serde_lite_derive::Serialize;
+
+#[derive(Serialize)]
+#[serde(transparent)]
+struct Foo {
+    a: u32,
+    b: u32,
+}
+
+#[derive(Serialize)]
+#[serde(transparent, into = "u32")]
+struct Bar(u32);
+
+#[derive(Serialize)]
+#[serde(transparent)]
+enum Baz {
+    Foo,
+}
+
+fn main() {}
@@ -0,0 +1,32 @@
+use serde_lite_derive::Serialize;
+
+#[derive(Serialize)]
+#[serde(deny_unknown_fields)]
+struct Foo {
+    #[serde(skip_serialising)]
+    bar: u32,
+}
+
+#[derive(Serialize)]
+enum Bar {
+    #[serde(alias = "foo")]
+    Foo,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "serde_lite")]
+struct Baz;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+#[serde_lite(rename_all = "camelCase")]
+struct Qux;
+
+#[derive(Serialize)]
+struct Quux {
+    #[serde(alias = "b", skip_serialising)]
+    #[serde_lite(renam = "c")]
+    a: u32,
+}
+
+fn main() {}
@@ -0,0 +1,217 @@
+#![allow(dead_code)]
+
+use serde_lite::{intermediate, Error, Intermediate, Schema, SchemaValidator};
+use serde_lite_derive::Schema;
+
+#[test]
+fn test_validate_primitives() {
+    let validator = SchemaValidator::new(&u8::schema()).unwrap();
+
+    assert!(validator.validate(&intermediate!(10)).is_ok());
+    assert!(matches!(
+        validator.validate(&intermediate!(256)),
+        Err(Error::OutOfBounds)
+    ));
+    assert!(matches!(
+        validator.validate(&intermediate!(1.5)),
+        Err(Error::InvalidValue(_))
+    ));
+    assert!(matches!(
+        validator.validate(&intermediate!("foo")),
+        Err(Error::InvalidValue(_))
+    ));
+
+    let schema = intermediate!({
+        "type": ["string", "null"],
+        "enum": ["foo", "bar", null],
+        "maxLength": 3,
+    });
+
+    let validator = SchemaValidator::new(&schema).unwrap();
+
+    assert!(validator.validate(&intermediate!("foo")).is_ok());
+    assert!(validator.validate(&intermediate!(null)).is_ok());
+    assert!(validator.validate(&intermediate!("baz")).is_err());
+    assert!(validator.validate(&intermediate!(1)).is_err());
+}
+
+#[test]
+fn test_validate_object() {
+    let schema = intermediate!({
+        "type": "object",
+        "properties": {
+            "id": { "type": "integer" },
+            "name": { "type": "string", "minLength": 1 },
+            "tags": {
+                "type": "array",
+                "items": { "type": "string" },
+            },
+        },
+        "required": ["name", "id"],
+        "additionalProperties": false,
+    });
+
+    let validator = SchemaValidator::new(&schema).unwrap();
+
+    let input = intermediate!({
+        "id": 1,
+        "name": "foo",
+        "tags": ["a", "b"],
+    });
+
+    assert!(validator.validate(&input).is_ok());
+
+    let input = intermediate!({
+        "name": "",
+        "tags": ["a", 2],
+        "foo": true,
+    });
+
+    let errors = match validator.validate(&input) {
+        Err(Error::NamedFieldErrors(errors)) => errors,
+        _ => panic!("unexpected result"),
+    };
+
+    let mut fields = errors.iter().map(|err| err.field()).collect::<Vec<_>>();
+
+    fields.sort_unstable();
+
+    assert_eq!(fields, ["foo", "id", "name", "tags"]);
+
+    for err in &errors {
+        match err.field() {
+            "id" => assert!(matches!(err.error(), Error::MissingField)),
+            "tags" => match err.error() {
+                Error::UnnamedFieldErrors(errors) => {
+                    assert_eq!(errors.len(), 1);
+                    assert_eq!(errors.iter().next().unwrap().field_index(), 1);
+                }
+                _ => panic!("unexpected error"),
+            },
+            _ => assert!(matches!(err.error(), Error::Custom(_))),
+        }
+    }
+}
+
+#[test]
+fn test_validate_alternatives() {
+    let schema = intermediate!({
+        "oneOf": [
+            { "type": "integer" },
+            { "type": "number", "minimum": 10 },
+        ],
+    });
+
+    let validator = SchemaValidator::new(&schema).unwrap();
+
+    assert!(validator.validate(&intermediate!(1)).is_ok());
+    assert!(validator.validate(&intermediate!(10.5)).is_ok());
+    assert!(validator.validate(&intermediate!(10)).is_err());
+    assert!(validator.validate(&intermediate!(1.5)).is_err());
+
+    let schema = intermediate!({
+        "anyOf": [
+            { "type": "string" },
+            {
+                "type": "object",
+                "properties": {
+                    "field": { "type": "boolean" },
+                },
+            },
+        ],
+    });
+
+    let validator = SchemaValidator::new(&schema).unwrap();
+
+    assert!(validator.validate(&intermediate!("foo")).is_ok());
+    assert!(validator
+        .validate(&intermediate!({ "field": true }))
+        .is_ok());
+    assert!(matches!(
+        validator.validate(&intermediate!({ "field": 1 })),
+        Err(Error::NamedFieldErrors(_))
+    ));
+}
+
+#[test]
+fn test_validate_derived_schema() {
+    #[derive(Schema)]
+    #[serde(tag = "type")]
+    enum TestEnum {
+        Variant1,
+        Variant2 { field: u32 },
+    }
+
+    let validator = SchemaValidator::new(&TestEnum::schema()).unwrap();
+
+    assert!(validator
+        .validate(&intermediate!({ "type": "Variant1" }))
+        .is_ok());
+    assert!(validator
+        .validate(&intermediate!({ "type": "Variant2", "field": 3 }))
+        .is_ok());
+    assert!(validator
+        .validate(&intermediate!({ "type": "Variant2" }))
+        .is_err());
+    assert!(validator
+        .validate(&intermediate!({ "type": "Variant3" }))
+        .is_err());
+}
+
+#[test]
+fn test_invalid_schema() {
+    assert!(SchemaValidator::new(&intermediate!(1)).is_err());
+    assert!(SchemaValidator::new(&intermediate!({ "type": "foo" })).is_err());
+    assert!(SchemaValidator::new(&intermediate!({ "$ref": "#/foo" })).is_err());
+    assert!(SchemaValidator::new(&intermediate!({ "$ref": "other.json" })).is_err());
+    assert!(SchemaValidator::new(&Intermediate::Bool(true)).is_ok());
+}
+
+#[test]
+fn test_validate_references() {
+    let schema = intermediate!({
+        "type": "object",
+        "properties": {
+            "value": { "$ref": "#/$defs/positive" },
+            "next": { "anyOf": [{ "$ref": "#" }, { "type": "null" }] },
+            "tags": { "type": "array", "items": { "$ref": "#tag" } },
+        },
+        "required": ["value"],
+        "$defs": {
+            "positive": { "type": "integer", "minimum": 1 },
+            "tag": { "$anchor": "tag", "type": "string", "minLength": 1 },
+        },
+    });
+
+    let validator = SchemaValidator::new(&schema).unwrap();
+
+    assert!(validator
+        .validate(&intermediate!({
+            "value": 1,
+            "next": { "value": 2, "next": { "value": 3, "tags": ["a"] } },
+        }))
+        .is_ok());
+    assert!(validator
+        .validate(&intermediate!({ "value": 1, "next": { "value": 0 } }))
+        .is_err());
+    assert!(validator
+        .validate(&intermediate!({ "value": 1, "next": { "next": null } }))
+        .is_err());
+    assert!(validator
+        .validate(&intermediate!({ "value": 1, "tags": [""] }))
+        .is_err());
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn test_validate_pattern() {
+    let schema = intermediate!({
+        "type": "string",
+        "pattern": "^[a-z]+$",
+    });
+
+    let validator = SchemaValidator::new(&schema).unwrap();
+
+    assert!(validator.validate(&intermediate!("foo")).is_ok());
+    assert!(validator.validate(&intermediate!("Foo")).is_err());
+}